@@ -1,5 +1,6 @@
 use clap::Subcommand;
 use clap::{Parser, ValueEnum};
+use plottery_lib::PdfSettings;
 use plottery_project::{export_plottery_home, LibSource, Project};
 use std::path::PathBuf;
 
@@ -7,6 +8,7 @@ use std::path::PathBuf;
 enum RenderType {
     Svg,
     Png,
+    Pdf,
 }
 
 fn parse_lib_source(value: &str) -> LibSource {
@@ -35,7 +37,7 @@ enum Command {
         lib: Option<String>,
     },
     Render {
-        #[arg(help = "Output format (svg or png)")]
+        #[arg(help = "Output format (svg, png or pdf)")]
         format: RenderType,
         #[arg(help = "Path to the .plottery project file")]
         project_path: String,
@@ -148,6 +150,11 @@ pub fn main() {
                 RenderType::Png => {
                     project.write_png(out_path_buf, release, &params).unwrap();
                 }
+                RenderType::Pdf => {
+                    project
+                        .write_pdf(out_path_buf, release, &params, &PdfSettings::new())
+                        .unwrap();
+                }
             }
         }
         Command::SetHome { path } => {
@@ -1,3 +1,4 @@
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::LARGE_EPSILON;
@@ -29,10 +30,93 @@ impl ColorRgb {
     pub fn new(r: f32, g: f32, b: f32) -> Self {
         Self { r, g, b }
     }
+    /// Parses a hexadecimal color like `#ff8800`, `ff8800` or the short form `#f80`.
+    /// ```
+    /// # use plottery_lib::*;
+    /// assert_eq!(ColorRgb::from_hex("#ff0000").unwrap(), ColorRgb::red());
+    /// assert_eq!(ColorRgb::from_hex("0f0").unwrap(), ColorRgb::green());
+    /// assert!(ColorRgb::from_hex("#12345").is_err());
+    /// ```
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits = hex.trim().trim_start_matches('#');
+        let expanded: String = match digits.len() {
+            3 => digits.chars().flat_map(|c| [c, c]).collect(),
+            6 => digits.to_string(),
+            _ => return Err(anyhow!("invalid hex color '{}'", hex)),
+        };
+        let component = |i: usize| -> Result<f32> {
+            let value = u8::from_str_radix(&expanded[i * 2..i * 2 + 2], 16)
+                .map_err(|_| anyhow!("invalid hex color '{}'", hex))?;
+            Ok(value as f32 / 255.0)
+        };
+        Ok(Self::new(component(0)?, component(1)?, component(2)?))
+    }
+    /// Parses a css color as used in svg files: hexadecimal (see [`ColorRgb::from_hex`]), `rgb(255, 128, 0)`,
+    /// `rgb(100%, 50%, 0%)` or one of the basic color keywords like `red` or `navy`.
+    /// ```
+    /// # use plottery_lib::*;
+    /// assert_eq!(ColorRgb::from_css("rgb(255, 0, 0)").unwrap(), ColorRgb::red());
+    /// assert_eq!(ColorRgb::from_css("Blue").unwrap(), ColorRgb::blue());
+    /// assert!(ColorRgb::from_css("rgb(1, 2)").is_err());
+    /// ```
+    pub fn from_css(css: &str) -> Result<Self> {
+        let css = css.trim().to_ascii_lowercase();
+        if css.starts_with('#') {
+            return Self::from_hex(&css);
+        }
+        if let Some(args) = css
+            .strip_prefix("rgb(")
+            .and_then(|args| args.strip_suffix(')'))
+        {
+            let components = args
+                .split(|c: char| c == ',' || c.is_ascii_whitespace())
+                .filter(|component| !component.is_empty())
+                .map(|component| {
+                    let value = match component.strip_suffix('%') {
+                        Some(percent) => percent.parse::<f32>().map(|value| value / 100.0),
+                        None => component.parse::<f32>().map(|value| value / 255.0),
+                    };
+                    value
+                        .map(|value| value.clamp(0.0, 1.0))
+                        .map_err(|_| anyhow!("invalid css color '{}'", css))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return match components[..] {
+                [r, g, b] => Ok(Self::new(r, g, b)),
+                _ => Err(anyhow!("invalid css color '{}'", css)),
+            };
+        }
+
+        let hex = match css.as_str() {
+            "black" => "000000",
+            "silver" => "c0c0c0",
+            "gray" | "grey" => "808080",
+            "white" => "ffffff",
+            "maroon" => "800000",
+            "red" => "ff0000",
+            "purple" => "800080",
+            "fuchsia" | "magenta" => "ff00ff",
+            "green" => "008000",
+            "lime" => "00ff00",
+            "olive" => "808000",
+            "yellow" => "ffff00",
+            "navy" => "000080",
+            "blue" => "0000ff",
+            "teal" => "008080",
+            "aqua" | "cyan" => "00ffff",
+            "orange" => "ffa500",
+            _ => return Err(anyhow!("unsupported css color '{}'", css)),
+        };
+        Self::from_hex(hex)
+    }
     /// Converts this RGB color to HSV format.
     pub fn hsv(&self) -> ColorHsv {
         (*self).into()
     }
+    /// Converts this RGB color to the perceptual OKLab color space.
+    pub fn oklab(&self) -> ColorOklab {
+        (*self).into()
+    }
     /// Returns the hexadecimal representation of this color.
     /// ```
     /// # use plottery_lib::*;
@@ -65,6 +149,12 @@ impl ColorRgb {
         (dr * dr + dg * dg + db * db).sqrt()
     }
 
+    /// Returns the perceptual distance between this color and another color, the euclidean distance in [ColorOklab] space.
+    /// A distance below `0.02` is hardly noticeable.
+    pub fn dist_perceptual(&self, other: ColorRgb) -> f32 {
+        self.oklab().dist(&other.oklab())
+    }
+
     /// Returns the closest [ColorName] from the list of predefined named colors [COLOR_NAMES].
     pub fn get_name(&self) -> ColorName {
         *COLOR_NAMES
@@ -164,6 +254,91 @@ impl From<ColorHsv> for ColorRgb {
     }
 }
 
+/// A color represented in the perceptual OKLab color space <https://bottosson.github.io/posts/oklab/>.
+///
+/// - Lightness (l): \[0-1\] for colors in RGB range
+/// - a: green (negative) to red (positive)
+/// - b: blue (negative) to yellow (positive)
+///
+/// Euclidean distances in OKLab match perceived color differences much better than in RGB.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct ColorOklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl ColorOklab {
+    pub fn new(l: f32, a: f32, b: f32) -> Self {
+        Self { l, a, b }
+    }
+    /// Converts this color to RGB format. Components outside of the RGB range are not clamped.
+    pub fn rgb(&self) -> ColorRgb {
+        (*self).into()
+    }
+    /// Returns the euclidean distance to another color.
+    pub fn dist(&self, other: &ColorOklab) -> f32 {
+        let dl = self.l - other.l;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        (dl * dl + da * da + db * db).sqrt()
+    }
+    /// Returns the chroma, the distance from the neutral gray axis.
+    pub fn chroma(&self) -> f32 {
+        (self.a * self.a + self.b * self.b).sqrt()
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl From<ColorRgb> for ColorOklab {
+    #[allow(clippy::excessive_precision)]
+    fn from(rgb: ColorRgb) -> Self {
+        let r = srgb_to_linear(rgb.r.clamp(0.0, 1.0));
+        let g = srgb_to_linear(rgb.g.clamp(0.0, 1.0));
+        let b = srgb_to_linear(rgb.b.clamp(0.0, 1.0));
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        Self {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+}
+
+impl From<ColorOklab> for ColorRgb {
+    #[allow(clippy::excessive_precision)]
+    fn from(lab: ColorOklab) -> Self {
+        let l = (lab.l + 0.3963377774 * lab.a + 0.2158037573 * lab.b).powi(3);
+        let m = (lab.l - 0.1055613458 * lab.a - 0.0638541728 * lab.b).powi(3);
+        let s = (lab.l - 0.0894841775 * lab.a - 1.2914855480 * lab.b).powi(3);
+
+        Self {
+            r: linear_to_srgb(4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s),
+            g: linear_to_srgb(-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s),
+            b: linear_to_srgb(-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s),
+        }
+    }
+}
+
 impl PartialEq for ColorRgb {
     fn eq(&self, other: &Self) -> bool {
         (self.r - other.r).abs() < LARGE_EPSILON
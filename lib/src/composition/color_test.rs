@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test_color {
-    use crate::ColorRgb;
+    use crate::{ColorOklab, ColorRgb};
 
     #[test]
     fn conversion() {
@@ -53,4 +53,62 @@ mod test_color {
             .get_name()
         );
     }
+
+    #[test]
+    fn hex() {
+        let color = ColorRgb::new(0.2, 0.4, 0.6);
+        assert_eq!(ColorRgb::from_hex(&color.hex()).unwrap(), color);
+        assert_eq!(ColorRgb::from_hex(" #FFF ").unwrap(), ColorRgb::white());
+        assert!(ColorRgb::from_hex("#gg0000").is_err());
+        assert!(ColorRgb::from_hex("").is_err());
+    }
+
+    #[test]
+    fn css() {
+        assert_eq!(ColorRgb::from_css(" #00F").unwrap(), ColorRgb::blue());
+        assert_eq!(
+            ColorRgb::from_css("rgb(51 102 153)").unwrap(),
+            ColorRgb::new(0.2, 0.4, 0.6)
+        );
+        assert_eq!(
+            ColorRgb::from_css("rgb(100%,0%,50%)").unwrap(),
+            ColorRgb::new(1.0, 0.0, 0.5)
+        );
+        assert_eq!(
+            ColorRgb::from_css("grey").unwrap(),
+            ColorRgb::from_hex("808080").unwrap()
+        );
+        assert!(ColorRgb::from_css("rgb(a, b, c)").is_err());
+        assert!(ColorRgb::from_css("none").is_err());
+        assert!(ColorRgb::from_css("url(#gradient)").is_err());
+    }
+
+    #[test]
+    fn oklab() {
+        let white = ColorRgb::white().oklab();
+        assert!((white.l - 1.0).abs() < 0.001);
+        assert!(white.chroma() < 0.001);
+        assert!(ColorRgb::black().oklab().l.abs() < 0.001);
+
+        // reference value from https://bottosson.github.io/posts/oklab/
+        let red = ColorRgb::red().oklab();
+        assert!((red.l - 0.628).abs() < 0.001);
+        assert!((red.a - 0.2249).abs() < 0.001);
+        assert!((red.b - 0.1258).abs() < 0.001);
+
+        let rgb = ColorRgb::new(0.3, 0.9, 0.5);
+        assert_eq!(rgb.oklab().rgb(), rgb);
+        assert_eq!(ColorOklab::new(0.5, 0.0, 0.0).rgb().hsv().s, 0.0);
+    }
+
+    #[test]
+    fn dist_perceptual() {
+        // equal rgb distances, but the blue change is much harder to see
+        let base = ColorRgb::new(0.5, 0.5, 0.5);
+        let greener = ColorRgb::new(0.5, 0.6, 0.5);
+        let bluer = ColorRgb::new(0.5, 0.5, 0.6);
+        assert!((base.dist_euclidean(greener) - base.dist_euclidean(bluer)).abs() < 0.0001);
+        assert!(base.dist_perceptual(bluer) < base.dist_perceptual(greener));
+        assert_eq!(base.dist_perceptual(base), 0.0);
+    }
 }
@@ -0,0 +1,877 @@
+use std::{f32::consts::PI, fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{Circle, Layer, Path, Shape, V2};
+
+use super::{
+    svg_curve::{flatten_arc, flatten_ellipse, segment_count, SvgArc},
+    ColorRgb, LayerPropsInheritable,
+};
+
+/// Maximum deviation in cm of flattened arcs, ellipses and splines from the original in [`Layer::new_from_dxf`].
+pub const DXF_IMPORT_TOLERANCE_CM: f32 = 0.002;
+
+/// Drawing units of a DXF file, stored as `$INSUNITS` in its header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DxfUnits {
+    Unitless,
+    Inches,
+    Feet,
+    Millimeters,
+    Centimeters,
+    Meters,
+}
+
+impl DxfUnits {
+    /// Returns the units for an `$INSUNITS` code.
+    pub fn from_code(code: i32) -> Result<Self> {
+        match code {
+            0 => Ok(DxfUnits::Unitless),
+            1 => Ok(DxfUnits::Inches),
+            2 => Ok(DxfUnits::Feet),
+            4 => Ok(DxfUnits::Millimeters),
+            5 => Ok(DxfUnits::Centimeters),
+            6 => Ok(DxfUnits::Meters),
+            _ => bail!("unsupported DXF units $INSUNITS {}", code),
+        }
+    }
+    pub fn code(&self) -> i32 {
+        match self {
+            DxfUnits::Unitless => 0,
+            DxfUnits::Inches => 1,
+            DxfUnits::Feet => 2,
+            DxfUnits::Millimeters => 4,
+            DxfUnits::Centimeters => 5,
+            DxfUnits::Meters => 6,
+        }
+    }
+    /// Returns the length of one unit in cm. Unitless drawings are interpreted as millimeters, the common CAD default.
+    pub fn cm_per_unit(&self) -> f32 {
+        match self {
+            DxfUnits::Unitless | DxfUnits::Millimeters => 0.1,
+            DxfUnits::Inches => 2.54,
+            DxfUnits::Feet => 30.48,
+            DxfUnits::Centimeters => 1.0,
+            DxfUnits::Meters => 100.0,
+        }
+    }
+}
+
+/// AutoCAD Color Index colors `1` to `7`, with `7` (white on dark, black on light backgrounds) as black.
+const DXF_ACI_COLORS: [(i32, [f32; 3]); 7] = [
+    (1, [1.0, 0.0, 0.0]),
+    (2, [1.0, 1.0, 0.0]),
+    (3, [0.0, 1.0, 0.0]),
+    (4, [0.0, 1.0, 1.0]),
+    (5, [0.0, 0.0, 1.0]),
+    (6, [1.0, 0.0, 1.0]),
+    (7, [0.0, 0.0, 0.0]),
+];
+
+/// Lineweights in 1/100 mm allowed by DXF.
+const DXF_LINEWEIGHTS: [i32; 24] = [
+    0, 5, 9, 13, 15, 18, 20, 25, 30, 35, 40, 50, 53, 60, 70, 80, 90, 100, 106, 120, 140, 158, 200,
+    211,
+];
+
+struct DxfWriter {
+    dxf: String,
+    next_handle: u32,
+}
+
+impl DxfWriter {
+    fn pair(&mut self, code: i32, value: impl std::fmt::Display) {
+        self.dxf += &format!("{:>3}\n{}\n", code, value);
+    }
+    fn point(&mut self, code: i32, point: V2) {
+        self.pair(code, point.x);
+        self.pair(code + 10, point.y);
+    }
+    fn handle(&mut self) -> String {
+        let handle = format!("{:X}", self.next_handle);
+        self.next_handle += 1;
+        handle
+    }
+    /// Starts an object with a new handle owned by `owner` and returns its handle.
+    fn object(&mut self, kind: &str, owner: &str) -> String {
+        let handle = self.handle();
+        self.object_with_handle(kind, &handle, owner);
+        handle
+    }
+    fn object_with_handle(&mut self, kind: &str, handle: &str, owner: &str) {
+        self.pair(0, kind);
+        // dimension styles store their handle with code 105
+        self.pair(if kind == "DIMSTYLE" { 105 } else { 5 }, handle);
+        self.pair(330, owner);
+    }
+    /// Starts a symbol table and returns its handle, which owns the entries.
+    fn table(&mut self, name: &str, num_entries: usize) -> String {
+        let handle = self.handle();
+        self.pair(0, "TABLE");
+        self.pair(2, name);
+        self.pair(5, &handle);
+        self.pair(330, 0);
+        self.pair(100, "AcDbSymbolTable");
+        self.pair(70, num_entries);
+        handle
+    }
+    fn table_entry(&mut self, kind: &str, table: &str, subclass: &str, name: &str) -> String {
+        let handle = self.object(kind, table);
+        self.pair(100, "AcDbSymbolTableRecord");
+        self.pair(100, subclass);
+        self.pair(2, name);
+        self.pair(70, 0);
+        handle
+    }
+    fn entity(&mut self, kind: &str, owner: &str, layer: &str, subclass: &str) {
+        self.object(kind, owner);
+        self.pair(100, "AcDbEntity");
+        self.pair(8, layer);
+        self.pair(100, subclass);
+    }
+    fn polyline(&mut self, owner: &str, layer: &str, points: &[V2]) {
+        let closed = points.len() > 2 && points.first() == points.last();
+        let points = if closed {
+            &points[..points.len() - 1]
+        } else {
+            points
+        };
+        if points.len() < 2 {
+            return;
+        }
+        if points.len() == 2 && !closed {
+            self.entity("LINE", owner, layer, "AcDbLine");
+            self.point(10, points[0]);
+            self.point(11, points[1]);
+            return;
+        }
+        self.entity("LWPOLYLINE", owner, layer, "AcDbPolyline");
+        self.pair(90, points.len());
+        self.pair(70, if closed { 1 } else { 0 });
+        for point in points {
+            self.point(10, *point);
+        }
+    }
+}
+
+impl Layer {
+    /// Returns the layer as an ASCII DXF drawing, with coordinates in cm converted to `units`.
+    ///
+    /// Every layer with shapes becomes a DXF layer named by the names of it and its ancestors joined with ` - `
+    /// (unnamed layers are numbered, the unnamed root is DXF layer `0`), using the layer's color and pen width.
+    /// [`Circle`]s are written as `CIRCLE`, two point [`Path`]s as `LINE` and all other shapes as `LWPOLYLINE`.
+    /// The drawing is a complete DXF R2000 (`AC1015`) file with handles, subclass markers, the required symbol tables,
+    /// blocks and a root dictionary, so CAD tools accept it.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Circle::new_shape(V2::new(1.0, 1.0), 0.5)]);
+    /// let dxf = layer.to_dxf(DxfUnits::Millimeters);
+    /// assert!(dxf.contains("  8\n0\n100\nAcDbCircle\n 10\n10\n 20\n10\n 40\n5\n"));
+    /// ```
+    pub fn to_dxf(&self, units: DxfUnits) -> String {
+        let scale = 1.0 / units.cm_per_unit();
+
+        let mut layers: Vec<(String, &Layer, ColorRgb, f32)> = Vec::new();
+        let mut unnamed_count = 0;
+        self.collect_dxf_layers(
+            None,
+            &LayerPropsInheritable::default(),
+            &mut unnamed_count,
+            &mut layers,
+        );
+
+        let mut layer_names: Vec<&str> = Vec::new();
+        for (name, _, _, _) in layers.iter() {
+            if !layer_names.contains(&name.as_str()) {
+                layer_names.push(name);
+            }
+        }
+        if !layer_names.contains(&"0") {
+            layer_names.insert(0, "0");
+        }
+
+        // everything after the header is written first, so the header can store the next free handle
+        let mut writer = DxfWriter {
+            dxf: String::new(),
+            next_handle: 1,
+        };
+        writer.pair(0, "SECTION");
+        writer.pair(2, "CLASSES");
+        writer.pair(0, "ENDSEC");
+
+        writer.pair(0, "SECTION");
+        writer.pair(2, "TABLES");
+        let table = writer.table("VPORT", 1);
+        writer.table_entry("VPORT", &table, "AcDbViewportTableRecord", "*Active");
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("LTYPE", 3);
+        for name in ["ByBlock", "ByLayer", "Continuous"] {
+            writer.table_entry("LTYPE", &table, "AcDbLinetypeTableRecord", name);
+            writer.pair(3, "");
+            writer.pair(72, 65);
+            writer.pair(73, 0);
+            writer.pair(40, 0.0);
+        }
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("LAYER", layer_names.len());
+        for name in layer_names.iter() {
+            let (color, pen_width_cm) = layers
+                .iter()
+                .find(|(n, ..)| n == name)
+                .map(|(_, _, color, pen_width_cm)| (*color, *pen_width_cm))
+                .unwrap_or_else(|| {
+                    let props = LayerPropsInheritable::default();
+                    (props.color.unwrap(), props.pen_width_cm.unwrap())
+                });
+            let aci = DXF_ACI_COLORS
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    let dist = |rgb: &[f32; 3]| {
+                        color.dist_euclidean(ColorRgb::new(rgb[0], rgb[1], rgb[2]))
+                    };
+                    dist(a).total_cmp(&dist(b))
+                })
+                .unwrap()
+                .0;
+            let lineweight = DXF_LINEWEIGHTS
+                .iter()
+                .min_by_key(|weight| (**weight - (pen_width_cm * 1000.0).round() as i32).abs())
+                .unwrap();
+            let [r, g, b] =
+                [color.r, color.g, color.b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u32);
+
+            writer.table_entry("LAYER", &table, "AcDbLayerTableRecord", name);
+            writer.pair(62, aci);
+            writer.pair(420, (r << 16) | (g << 8) | b);
+            writer.pair(6, "Continuous");
+            writer.pair(370, lineweight);
+        }
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("STYLE", 1);
+        writer.table_entry("STYLE", &table, "AcDbTextStyleTableRecord", "Standard");
+        writer.pair(40, 0.0);
+        writer.pair(41, 1.0);
+        writer.pair(3, "txt");
+        writer.pair(0, "ENDTAB");
+
+        writer.table("VIEW", 0);
+        writer.pair(0, "ENDTAB");
+        writer.table("UCS", 0);
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("APPID", 1);
+        writer.table_entry("APPID", &table, "AcDbRegAppTableRecord", "ACAD");
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("DIMSTYLE", 1);
+        writer.pair(100, "AcDbDimStyleTable");
+        writer.table_entry("DIMSTYLE", &table, "AcDbDimStyleTableRecord", "Standard");
+        writer.pair(0, "ENDTAB");
+
+        let table = writer.table("BLOCK_RECORD", 2);
+        let block_records: Vec<String> = ["*Model_Space", "*Paper_Space"]
+            .iter()
+            .map(|name| writer.table_entry("BLOCK_RECORD", &table, "AcDbBlockTableRecord", name))
+            .collect();
+        writer.pair(0, "ENDTAB");
+        writer.pair(0, "ENDSEC");
+
+        writer.pair(0, "SECTION");
+        writer.pair(2, "BLOCKS");
+        for (name, block_record) in ["*Model_Space", "*Paper_Space"].iter().zip(&block_records) {
+            writer.object("BLOCK", block_record);
+            writer.pair(100, "AcDbEntity");
+            if *name == "*Paper_Space" {
+                writer.pair(67, 1);
+            }
+            writer.pair(8, 0);
+            writer.pair(100, "AcDbBlockBegin");
+            writer.pair(2, name);
+            writer.pair(70, 0);
+            writer.point(10, V2::zero());
+            writer.pair(3, name);
+            writer.pair(1, "");
+            writer.object("ENDBLK", block_record);
+            writer.pair(100, "AcDbEntity");
+            if *name == "*Paper_Space" {
+                writer.pair(67, 1);
+            }
+            writer.pair(8, 0);
+            writer.pair(100, "AcDbBlockEnd");
+        }
+        writer.pair(0, "ENDSEC");
+
+        let model_space = &block_records[0];
+        writer.pair(0, "SECTION");
+        writer.pair(2, "ENTITIES");
+        for (name, layer, _, _) in layers.iter() {
+            for shape in layer.iter() {
+                match shape {
+                    Shape::Circle(circle) => {
+                        writer.entity("CIRCLE", model_space, name, "AcDbCircle");
+                        writer.point(10, circle.center * scale);
+                        writer.pair(40, circle.radius * scale);
+                    }
+                    Shape::Rect(rect) => writer.polyline(
+                        model_space,
+                        name,
+                        &[rect.bl(), rect.br(), rect.tr(), rect.tl(), rect.bl()].map(|p| p * scale),
+                    ),
+                    Shape::Path(path) => {
+                        let points: Vec<V2> =
+                            path.get_points_ref().iter().map(|p| *p * scale).collect();
+                        writer.polyline(model_space, name, &points);
+                    }
+                    Shape::Compound(compound) => {
+                        for ring in compound.rings() {
+                            let points: Vec<V2> =
+                                ring.get_points_ref().iter().map(|p| *p * scale).collect();
+                            writer.polyline(model_space, name, &points);
+                        }
+                    }
+                }
+            }
+        }
+        writer.pair(0, "ENDSEC");
+
+        writer.pair(0, "SECTION");
+        writer.pair(2, "OBJECTS");
+        let (root, groups) = (writer.handle(), writer.handle());
+        writer.object_with_handle("DICTIONARY", &root, "0");
+        writer.pair(100, "AcDbDictionary");
+        writer.pair(281, 1);
+        writer.pair(3, "ACAD_GROUP");
+        writer.pair(350, &groups);
+        writer.object_with_handle("DICTIONARY", &groups, &root);
+        writer.pair(100, "AcDbDictionary");
+        writer.pair(281, 1);
+        writer.pair(0, "ENDSEC");
+        writer.pair(0, "EOF");
+
+        let mut header = DxfWriter {
+            dxf: String::new(),
+            next_handle: 0,
+        };
+        header.pair(0, "SECTION");
+        header.pair(2, "HEADER");
+        header.pair(9, "$ACADVER");
+        header.pair(1, "AC1015");
+        header.pair(9, "$HANDSEED");
+        header.pair(5, format!("{:X}", writer.next_handle));
+        header.pair(9, "$INSUNITS");
+        header.pair(70, units.code());
+        header.pair(0, "ENDSEC");
+        header.dxf + &writer.dxf
+    }
+
+    fn collect_dxf_layers<'a>(
+        &'a self,
+        parent_name: Option<&str>,
+        parent_props: &LayerPropsInheritable,
+        unnamed_count: &mut usize,
+        layers: &mut Vec<(String, &'a Layer, ColorRgb, f32)>,
+    ) {
+        let props = parent_props.overwrite_with(&self.props_inheritable);
+        let own_name = match &self.props.name {
+            Some(name) => name.clone(),
+            None if parent_name.is_none() => "0".to_string(),
+            None => {
+                *unnamed_count += 1;
+                format!("layer {}", unnamed_count)
+            }
+        };
+        let name = match parent_name {
+            Some(parent_name) if parent_name != "0" => format!("{} - {}", parent_name, own_name),
+            _ => own_name,
+        };
+        if !self.shapes.is_empty() {
+            layers.push((
+                name.clone(),
+                self,
+                props.color.unwrap(),
+                props.pen_width_cm.unwrap(),
+            ));
+        }
+        for sublayer in self.iter_sublayers() {
+            sublayer.collect_dxf_layers(Some(&name), &props, unnamed_count, layers);
+        }
+    }
+
+    /// Writes the layer to a DXF file, see [`Layer::to_dxf`].
+    pub fn write_dxf(&self, path: &PathBuf, units: DxfUnits) -> Result<()> {
+        fs::write(path, self.to_dxf(units))?;
+        Ok(())
+    }
+
+    /// Creates a new `Layer` from a DXF file, see [`Layer::new_from_dxf_str`].
+    pub fn new_from_dxf(path: &PathBuf) -> Result<Layer> {
+        Self::new_from_dxf_str(&fs::read_to_string(path)?, DXF_IMPORT_TOLERANCE_CM)
+    }
+
+    /// Creates a new `Layer` from an ASCII DXF drawing, converting its `$INSUNITS` to cm.
+    ///
+    /// Imports `LINE`, `LWPOLYLINE` and `POLYLINE` (including bulges), `CIRCLE`, `ARC`, `ELLIPSE` and `SPLINE` entities
+    /// of the `ENTITIES` section. Arcs, ellipses and splines are flattened so they deviate at most `tolerance_cm` from the original.
+    /// Entities on DXF layer `0` are added to the returned `Layer`, other DXF layers become sublayers with their color and lineweight.
+    /// Entities on layers that are turned off or frozen are skipped, other entity types are ignored.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let dxf = "0\nSECTION\n2\nENTITIES\n0\nLINE\n8\nguides\n10\n0\n20\n0\n11\n10\n21\n0\n0\nENDSEC\n0\nEOF\n";
+    /// let layer = Layer::new_from_dxf_str(dxf, DXF_IMPORT_TOLERANCE_CM).unwrap();
+    /// assert_eq!(layer.get_path("guides").unwrap().shapes[0].length(), 1.0);
+    /// ```
+    pub fn new_from_dxf_str(dxf: &str, tolerance_cm: f32) -> Result<Layer> {
+        let pairs = parse_dxf_pairs(dxf)?;
+
+        let mut units = DxfUnits::Unitless;
+        let mut tables: Vec<DxfEntity> = Vec::new();
+        let mut entities: Vec<DxfEntity> = Vec::new();
+        let mut section: Option<&str> = None;
+        let mut i = 0;
+        while i < pairs.len() {
+            let (code, value) = (pairs[i].0, pairs[i].1.as_str());
+            i += 1;
+            match (code, value) {
+                (0, "SECTION") => {
+                    section = pairs
+                        .get(i)
+                        .filter(|(code, _)| *code == 2)
+                        .map(|(_, name)| name.as_str());
+                    i += 1;
+                }
+                (0, "ENDSEC") => section = None,
+                (0, "EOF") => break,
+                (9, "$INSUNITS") if section == Some("HEADER") => {
+                    let (_, units_code) = pairs.get(i).context("missing value of $INSUNITS")?;
+                    units = DxfUnits::from_code(parse_dxf_value(units_code, "$INSUNITS")?)?;
+                    i += 1;
+                }
+                (0, kind) if section == Some("TABLES") || section == Some("ENTITIES") => {
+                    let mut entity = DxfEntity {
+                        kind: kind.to_string(),
+                        pairs: Vec::new(),
+                    };
+                    while i < pairs.len() && pairs[i].0 != 0 {
+                        entity.pairs.push(pairs[i].clone());
+                        i += 1;
+                    }
+                    if section == Some("TABLES") {
+                        tables.push(entity);
+                    } else {
+                        entities.push(entity);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let scale = units.cm_per_unit();
+        let tolerance = tolerance_cm / scale;
+
+        let mut layer = Layer::new();
+        let mut hidden_layers: Vec<String> = Vec::new();
+        for table_entry in tables.iter().filter(|entity| entity.kind == "LAYER") {
+            let name = table_entry.text(2).context("DXF layer without a name")?;
+            let color = table_entry.optional::<i32>(62)?.unwrap_or(7);
+            let flags = table_entry.optional::<i32>(70)?.unwrap_or(0);
+            if color < 0 || flags & 1 != 0 {
+                hidden_layers.push(name.to_string());
+                continue;
+            }
+            if name == "0" {
+                continue;
+            }
+
+            let mut sublayer = Layer::new().with_name(name);
+            let true_color = table_entry.optional::<u32>(420)?;
+            let aci_color = DXF_ACI_COLORS.iter().find(|(aci, _)| *aci == color);
+            if let Some(true_color) = true_color {
+                let [r, g, b] =
+                    [16, 8, 0].map(|shift| ((true_color >> shift) & 0xff) as f32 / 255.0);
+                sublayer = sublayer.with_color(ColorRgb::new(r, g, b));
+            } else if let Some((_, [r, g, b])) = aci_color {
+                sublayer = sublayer.with_color(ColorRgb::new(*r, *g, *b));
+            }
+            if let Some(lineweight) = table_entry.optional::<i32>(370)?.filter(|w| *w > 0) {
+                sublayer = sublayer.with_pen_width_cm(lineweight as f32 / 1000.0);
+            }
+            layer.push_layer(sublayer);
+        }
+
+        let mut i = 0;
+        while i < entities.len() {
+            let entity = &entities[i];
+            i += 1;
+            let layer_name = entity.text(8).unwrap_or("0").to_string();
+
+            let shapes = match entity.kind.as_str() {
+                "POLYLINE" => {
+                    let mut vertices = Vec::new();
+                    while i < entities.len() && entities[i].kind == "VERTEX" {
+                        vertices.push(&entities[i]);
+                        i += 1;
+                    }
+                    if i < entities.len() && entities[i].kind == "SEQEND" {
+                        i += 1;
+                    }
+                    let closed = entity.optional::<i32>(70)?.unwrap_or(0) & 1 != 0;
+                    let vertices = vertices
+                        .iter()
+                        .map(|vertex| {
+                            Ok((
+                                vertex.point(10)?,
+                                vertex.optional::<f32>(42)?.unwrap_or(0.0),
+                            ))
+                        })
+                        .collect::<Result<Vec<_>>>()
+                        .context("malformed DXF POLYLINE")?;
+                    vec![Path::new_shape_from(dxf_polyline_points(
+                        &vertices, closed, tolerance,
+                    ))]
+                }
+                _ => entity
+                    .to_shapes(tolerance)
+                    .with_context(|| format!("malformed DXF {}", entity.kind))?,
+            };
+            if hidden_layers.contains(&layer_name) {
+                continue;
+            }
+
+            let shapes: Vec<Shape> = shapes
+                .into_iter()
+                .map(|shape| match shape {
+                    Shape::Circle(circle) => {
+                        Circle::new_shape(circle.center * scale, circle.radius * scale)
+                    }
+                    Shape::Path(path) => Path::new_shape_from(
+                        path.get_points_ref()
+                            .iter()
+                            .map(|p| *p * scale)
+                            .collect::<Vec<_>>(),
+                    ),
+                    shape => shape,
+                })
+                .collect();
+            if layer_name == "0" {
+                layer.shapes.extend(shapes);
+                continue;
+            }
+            let index = match layer
+                .sublayers
+                .iter()
+                .position(|sublayer| sublayer.props.name.as_deref() == Some(&layer_name))
+            {
+                Some(index) => index,
+                None => {
+                    layer.push_layer(Layer::new().with_name(&layer_name));
+                    layer.sublayers.len() - 1
+                }
+            };
+            layer.sublayers[index].shapes.extend(shapes);
+        }
+        layer.sublayers.retain(|sublayer| !sublayer.is_empty());
+
+        Ok(layer)
+    }
+}
+
+/// An entity or table entry with its group code and value pairs.
+struct DxfEntity {
+    kind: String,
+    pairs: Vec<(i32, String)>,
+}
+
+impl DxfEntity {
+    fn text(&self, code: i32) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, value)| value.as_str())
+    }
+    fn optional<T: std::str::FromStr>(&self, code: i32) -> Result<Option<T>> {
+        self.text(code)
+            .map(|value| parse_dxf_value(value, &format!("group code {}", code)))
+            .transpose()
+    }
+    fn required(&self, code: i32) -> Result<f32> {
+        self.optional(code)?
+            .ok_or_else(|| anyhow!("missing group code {}", code))
+    }
+    fn point(&self, code: i32) -> Result<V2> {
+        Ok(V2::new(self.required(code)?, self.required(code + 10)?))
+    }
+    fn all(&self, code: i32) -> Result<Vec<f32>> {
+        self.pairs
+            .iter()
+            .filter(|(c, _)| *c == code)
+            .map(|(_, value)| parse_dxf_value(value, &format!("group code {}", code)))
+            .collect()
+    }
+    fn all_points(&self, code: i32) -> Result<Vec<V2>> {
+        let xs = self.all(code)?;
+        let ys = self.all(code + 10)?;
+        if xs.len() != ys.len() {
+            bail!("unequal number of x and y coordinates");
+        }
+        Ok(xs.into_iter().zip(ys).map(|(x, y)| V2::new(x, y)).collect())
+    }
+
+    /// Returns the shapes of the entity in drawing units, or nothing for unsupported entity types.
+    fn to_shapes(&self, tolerance: f32) -> Result<Vec<Shape>> {
+        let shapes = match self.kind.as_str() {
+            "LINE" => vec![Path::new_shape_from(vec![self.point(10)?, self.point(11)?])],
+            "LWPOLYLINE" => {
+                let closed = self.optional::<i32>(70)?.unwrap_or(0) & 1 != 0;
+                // bulges (42) follow the vertex (10, 20) they belong to
+                let mut vertices: Vec<(V2, f32)> = Vec::new();
+                let mut x = None;
+                for (code, value) in self.pairs.iter() {
+                    match code {
+                        10 => x = Some(parse_dxf_value::<f32>(value, "group code 10")?),
+                        20 => {
+                            let x = x.take().context("y coordinate without x coordinate")?;
+                            vertices
+                                .push((V2::new(x, parse_dxf_value(value, "group code 20")?), 0.0));
+                        }
+                        42 => {
+                            let vertex = vertices
+                                .last_mut()
+                                .context("bulge before the first vertex")?;
+                            vertex.1 = parse_dxf_value(value, "group code 42")?;
+                        }
+                        _ => {}
+                    }
+                }
+                vec![Path::new_shape_from(dxf_polyline_points(
+                    &vertices, closed, tolerance,
+                ))]
+            }
+            "CIRCLE" => vec![Circle::new_shape(self.point(10)?, self.required(40)?)],
+            "ARC" => {
+                let center = self.point(10)?;
+                let radius = self.required(40)?;
+                let start = self.required(50)?.to_radians();
+                let mut sweep = self.required(51)?.to_radians() - start;
+                if sweep <= 0.0 {
+                    sweep += 2.0 * PI;
+                }
+                vec![Path::new_shape_from(dxf_elliptic_arc(
+                    center,
+                    V2::new(radius, 0.0),
+                    1.0,
+                    start,
+                    sweep,
+                    tolerance,
+                ))]
+            }
+            "ELLIPSE" => {
+                let center = self.point(10)?;
+                let major_axis = self.point(11)?;
+                let ratio = self.required(40)?;
+                let start = self.optional(41)?.unwrap_or(0.0);
+                let mut sweep = self.optional(42)?.unwrap_or(2.0 * PI) - start;
+                if sweep <= 0.0 {
+                    sweep += 2.0 * PI;
+                }
+                if (sweep - 2.0 * PI).abs() < 1e-5 && major_axis.y == 0.0 {
+                    vec![Path::new_shape_from(flatten_ellipse(
+                        center,
+                        V2::new(major_axis.x.abs(), major_axis.x.abs() * ratio),
+                        tolerance,
+                    ))]
+                } else {
+                    vec![Path::new_shape_from(dxf_elliptic_arc(
+                        center, major_axis, ratio, start, sweep, tolerance,
+                    ))]
+                }
+            }
+            "SPLINE" => {
+                let degree = self.optional::<usize>(71)?.unwrap_or(3);
+                let knots = self.all(40)?;
+                let control_points = self.all_points(10)?;
+                let fit_points = self.all_points(11)?;
+                let closed = self.optional::<i32>(70)?.unwrap_or(0) & 1 != 0;
+                if control_points.is_empty() {
+                    let mut points = fit_points;
+                    if closed && points.len() > 2 {
+                        points.push(points[0]);
+                    }
+                    vec![Path::new_shape_from(points)]
+                } else {
+                    let mut weights = self.all(41)?;
+                    if weights.len() != control_points.len() {
+                        weights = vec![1.0; control_points.len()];
+                    }
+                    vec![Path::new_shape_from(dxf_spline_points(
+                        degree,
+                        &knots,
+                        &control_points,
+                        &weights,
+                        tolerance,
+                    )?)]
+                }
+            }
+            _ => vec![],
+        };
+        Ok(shapes)
+    }
+}
+
+/// Splits DXF into group code and value pairs.
+fn parse_dxf_pairs(dxf: &str) -> Result<Vec<(i32, String)>> {
+    let lines: Vec<&str> = dxf.lines().collect();
+    let lines = match lines.last() {
+        Some(last) if last.trim().is_empty() => &lines[..lines.len() - 1],
+        _ => &lines[..],
+    };
+    if lines.len() % 2 != 0 {
+        bail!("DXF group code without a value");
+    }
+    lines
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let code = pair[0].trim().parse::<i32>().map_err(|_| {
+                anyhow!(
+                    "invalid DXF group code '{}' in line {}",
+                    pair[0].trim(),
+                    i * 2 + 1
+                )
+            })?;
+            Ok((code, pair[1].trim().to_string()))
+        })
+        .collect()
+}
+
+fn parse_dxf_value<T: std::str::FromStr>(value: &str, name: &str) -> Result<T> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| anyhow!("invalid value '{}' of {}", value, name))
+}
+
+/// Returns the points of a polyline whose vertices each carry the bulge of the segment to the next vertex.
+///
+/// The bulge is the tangent of a quarter of the segment's included angle, positive for counterclockwise arcs.
+fn dxf_polyline_points(vertices: &[(V2, f32)], closed: bool, tolerance: f32) -> Vec<V2> {
+    let Some(first) = vertices.first() else {
+        return vec![];
+    };
+    let mut points = vec![first.0];
+    let segment_count = if closed {
+        vertices.len()
+    } else {
+        vertices.len() - 1
+    };
+    for i in 0..segment_count {
+        let (from, bulge) = vertices[i];
+        let to = vertices[(i + 1) % vertices.len()].0;
+        if bulge.abs() < 1e-6 || from == to {
+            points.push(to);
+            continue;
+        }
+        let radius = from.dist(to) * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+        points.extend(flatten_arc(
+            from,
+            &SvgArc {
+                radius: V2::new(radius, radius),
+                x_axis_rotation_degrees: 0.0,
+                large_arc: bulge.abs() > 1.0,
+                sweep: bulge > 0.0,
+            },
+            to,
+            tolerance,
+        ));
+    }
+    points
+}
+
+/// Returns points along the ellipse `center + major_axis * cos(t) + minor_axis * sin(t)` from `start` over `sweep` radians.
+fn dxf_elliptic_arc(
+    center: V2,
+    major_axis: V2,
+    ratio: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) -> Vec<V2> {
+    let minor_axis = V2::new(-major_axis.y, major_axis.x) * ratio;
+    let segments = segment_count(sweep, major_axis.len(), tolerance);
+    (0..=segments)
+        .map(|i| {
+            let t = start + sweep * i as f32 / segments as f32;
+            center + major_axis * t.cos() + minor_axis * t.sin()
+        })
+        .collect()
+}
+
+/// Returns points along a NURBS curve, evaluated with de Boor's algorithm.
+fn dxf_spline_points(
+    degree: usize,
+    knots: &[f32],
+    control_points: &[V2],
+    weights: &[f32],
+    tolerance: f32,
+) -> Result<Vec<V2>> {
+    if degree == 0 || control_points.len() <= degree {
+        bail!(
+            "spline of degree {} with {} control points",
+            degree,
+            control_points.len()
+        );
+    }
+    if knots.len() != control_points.len() + degree + 1 {
+        bail!(
+            "spline with {} control points and {} knots",
+            control_points.len(),
+            knots.len()
+        );
+    }
+
+    let evaluate = |t: f32| {
+        let mut span = degree;
+        while span < control_points.len() - 1 && knots[span + 1] <= t {
+            span += 1;
+        }
+        // homogeneous coordinates (x * w, y * w, w)
+        let mut d: Vec<[f32; 3]> = (0..=degree)
+            .map(|j| {
+                let point = control_points[j + span - degree];
+                let weight = weights[j + span - degree];
+                [point.x * weight, point.y * weight, weight]
+            })
+            .collect();
+        for r in 1..=degree {
+            for j in (r..=degree).rev() {
+                let left = knots[j + span - degree];
+                let right = knots[j + 1 + span - r];
+                let alpha = if right > left {
+                    (t - left) / (right - left)
+                } else {
+                    0.0
+                };
+                let (previous, current) = (d[j - 1], d[j]);
+                d[j] = [0, 1, 2].map(|k| (1.0 - alpha) * previous[k] + alpha * current[k]);
+            }
+        }
+        let [x, y, w] = d[degree];
+        V2::new(x / w, y / w)
+    };
+
+    // sample as densely as a circle around the control polygon
+    let polygon_length: f32 = control_points.windows(2).map(|w| w[0].dist(w[1])).sum();
+    let segments = segment_count(2.0 * PI, polygon_length / (2.0 * PI), tolerance)
+        .max(control_points.len() * 4);
+    let (start, end) = (knots[degree], knots[control_points.len()]);
+    Ok((0..=segments)
+        .map(|i| evaluate(start + (end - start) * i as f32 / segments as f32))
+        .collect())
+}
@@ -0,0 +1,328 @@
+#[cfg(test)]
+mod test_dxf {
+    use crate::{
+        Circle, ColorRgb, CutGuideEdge, DxfUnits, Frame, Layer, Path, Plottable, Rect,
+        SampleSettings, Shape, DXF_IMPORT_TOLERANCE_CM, V2,
+    };
+
+    fn dxf_of(header_units: Option<i32>, entities: &str) -> String {
+        let header = match header_units {
+            Some(units) => format!(
+                "0\nSECTION\n2\nHEADER\n9\n$INSUNITS\n70\n{}\n0\nENDSEC\n",
+                units
+            ),
+            None => String::new(),
+        };
+        format!(
+            "{}0\nSECTION\n2\nENTITIES\n{}0\nENDSEC\n0\nEOF\n",
+            header, entities
+        )
+    }
+
+    #[test]
+    fn export_entities_and_layers() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![
+                Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+                Circle::new_shape(V2::new(2.0, 2.0), 1.0),
+            ],
+            vec![Layer::new_from(vec![
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(1.0, 2.0)),
+                Path::new_shape_from(vec![
+                    V2::new(0.0, 0.0),
+                    V2::new(1.0, 0.0),
+                    V2::new(1.0, 1.0),
+                ]),
+            ])
+            .with_name("frame")
+            .with_color(ColorRgb::red())
+            .with_pen_width_cm(0.05)],
+        );
+        let dxf = l.to_dxf(DxfUnits::Centimeters);
+
+        assert!(dxf.contains("$INSUNITS\n 70\n5\n"));
+        assert_eq!(dxf.matches("\nLINE\n").count(), 1);
+        assert_eq!(dxf.matches("\nCIRCLE\n").count(), 1);
+        assert_eq!(dxf.matches("\nLWPOLYLINE\n").count(), 2);
+        assert!(dxf.contains(
+            "AcDbLayerTableRecord\n  2\nframe\n 70\n0\n 62\n1\n420\n16711680\n  6\nContinuous\n370\n50\n"
+        ));
+        // the rect is closed
+        assert!(dxf.contains("  8\nframe\n100\nAcDbPolyline\n 90\n4\n 70\n1\n"));
+        assert!(dxf.ends_with("  0\nEOF\n"));
+    }
+
+    #[test]
+    fn export_is_valid_r2000() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(V2::new(2.0, 2.0), 1.0)],
+            vec![Layer::new_from(vec![
+                Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(1.0, 2.0)),
+            ])
+            .with_name("frame")],
+        );
+        let dxf = l.to_dxf(DxfUnits::Millimeters);
+
+        let lines: Vec<&str> = dxf.lines().collect();
+        assert_eq!(lines.len() % 2, 0);
+        let pairs: Vec<(i32, &str)> = lines
+            .chunks(2)
+            .map(|pair| (pair[0].trim().parse().unwrap(), pair[1]))
+            .collect();
+        let value_after = |code: i32, value: &str, next_code: i32| {
+            let index = pairs
+                .iter()
+                .position(|pair| *pair == (code, value))
+                .unwrap();
+            assert_eq!(pairs[index + 1].0, next_code);
+            pairs[index + 1].1
+        };
+        assert_eq!(value_after(9, "$ACADVER", 1), "AC1015");
+        let handle_seed = u32::from_str_radix(value_after(9, "$HANDSEED", 5), 16).unwrap();
+
+        let sections: Vec<&str> = pairs
+            .windows(2)
+            .filter(|pairs| pairs[0] == (0, "SECTION"))
+            .map(|pairs| pairs[1].1)
+            .collect();
+        assert_eq!(
+            sections,
+            vec!["HEADER", "CLASSES", "TABLES", "BLOCKS", "ENTITIES", "OBJECTS"]
+        );
+        let tables: Vec<&str> = pairs
+            .windows(2)
+            .filter(|pairs| pairs[0] == (0, "TABLE"))
+            .map(|pairs| pairs[1].1)
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                "VPORT",
+                "LTYPE",
+                "LAYER",
+                "STYLE",
+                "VIEW",
+                "UCS",
+                "APPID",
+                "DIMSTYLE",
+                "BLOCK_RECORD"
+            ]
+        );
+
+        // split into objects, each starting with code 0
+        let mut objects: Vec<Vec<(i32, &str)>> = Vec::new();
+        for pair in pairs.iter() {
+            if pair.0 == 0 {
+                objects.push(Vec::new());
+            }
+            objects.last_mut().unwrap().push(*pair);
+        }
+        fn find<'a>(object: &[(i32, &'a str)], code: i32) -> Option<&'a str> {
+            object.iter().find(|pair| pair.0 == code).map(|pair| pair.1)
+        }
+        // the header section stores the handle seed with code 5 as well
+        let handles: Vec<u32> = objects
+            .iter()
+            .filter(|object| object[0].1 != "SECTION")
+            .filter_map(|object| find(object, 5).or_else(|| find(object, 105)))
+            .map(|handle| u32::from_str_radix(handle, 16).unwrap())
+            .collect();
+        let mut unique = handles.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), handles.len());
+        assert!(handles
+            .iter()
+            .all(|handle| *handle > 0 && *handle < handle_seed));
+        for object in objects.iter() {
+            if let Some(owner) = find(object, 330) {
+                let owner = u32::from_str_radix(owner, 16).unwrap();
+                assert!(owner == 0 || handles.contains(&owner), "{:?}", object);
+            }
+            if let Some(layer) = find(object, 8) {
+                assert!(dxf.contains(&format!("AcDbLayerTableRecord\n  2\n{}\n", layer)));
+            }
+        }
+
+        let model_space = objects
+            .iter()
+            .find(|object| object.contains(&(2, "*Model_Space")) && object[0].1 == "BLOCK_RECORD")
+            .map(|object| find(object, 5).unwrap())
+            .unwrap();
+        let start = objects
+            .iter()
+            .position(|object| object.as_slice() == [(0, "SECTION"), (2, "ENTITIES")])
+            .unwrap();
+        let entities: Vec<_> = objects[start + 1..]
+            .iter()
+            .take_while(|object| object[0].1 != "ENDSEC")
+            .collect();
+        assert_eq!(entities.len(), 3);
+        for entity in entities {
+            assert!(find(entity, 5).is_some());
+            assert_eq!(find(entity, 330), Some(model_space));
+            assert_eq!(entity[3], (100, "AcDbEntity"));
+            assert_eq!(entity.iter().filter(|pair| pair.0 == 100).count(), 2);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(V2::new(2.0, 2.0), 1.0)],
+            vec![Layer::new_from(vec![
+                Path::new_shape_from(vec![
+                    V2::new(0.0, 0.0),
+                    V2::new(1.0, 0.5),
+                    V2::new(2.0, 0.0),
+                ]),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(1.0, 2.0)),
+            ])
+            .with_name("art")
+            .with_color(ColorRgb::new(0.2, 0.4, 0.6))],
+        );
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.dxf");
+        l.write_dxf(&path, DxfUnits::Inches).unwrap();
+
+        let imported = Layer::new_from_dxf(&path).unwrap();
+        assert_eq!(imported.len(), 1);
+        let Shape::Circle(circle) = &imported.shapes[0] else {
+            panic!("expected a circle");
+        };
+        assert!(circle.center.dist(V2::new(2.0, 2.0)) < 0.0001);
+        assert!((circle.radius - 1.0).abs() < 0.0001);
+
+        let art = imported.get_path("art").unwrap();
+        assert_eq!(
+            art.props_inheritable.clone().unwrap().color.unwrap(),
+            ColorRgb::new(0.2, 0.4, 0.6)
+        );
+        assert_eq!(art.len(), 2);
+        assert!((art.shapes[0].length() - l.sublayers[0].shapes[0].length()).abs() < 0.0001);
+        let rect_points = art.shapes[1].get_points(SampleSettings::default());
+        assert_eq!(rect_points.len(), 5);
+        assert!((art.shapes[1].length() - 6.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn cut_guides() {
+        let frame = Frame::new_xy(V2::new(10.0, 15.0), 1.0);
+        let guides = frame.cut_guide(CutGuideEdge::All, 0.5).with_name("guides");
+        let art = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(frame.center(), 3.0)],
+            vec![guides.clone()],
+        );
+
+        let imported =
+            Layer::new_from_dxf_str(&art.to_dxf(DxfUnits::Millimeters), DXF_IMPORT_TOLERANCE_CM)
+                .unwrap();
+        let imported_guides = imported.get_path("guides").unwrap();
+        assert_eq!(imported_guides.len(), guides.len());
+        for (a, b) in imported_guides.iter().zip(guides.iter()) {
+            let points_a = a.get_points(SampleSettings::default());
+            let points_b = b.get_points(SampleSettings::default());
+            assert!(points_a[0].dist(points_b[0]) < 0.0001);
+            assert!(points_a[1].dist(points_b[1]) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn import_units_and_curves() {
+        let dxf = dxf_of(
+            Some(4),
+            "0\nARC\n8\n0\n10\n0\n20\n0\n40\n10\n50\n0\n51\n90\n\
+             0\nELLIPSE\n8\n0\n10\n0\n20\n0\n11\n20\n21\n0\n40\n0.5\n\
+             0\nLWPOLYLINE\n8\n0\n90\n2\n70\n0\n10\n0\n20\n0\n42\n1\n10\n20\n20\n0\n",
+        );
+        let l = Layer::new_from_dxf_str(&dxf, DXF_IMPORT_TOLERANCE_CM).unwrap();
+        assert_eq!(l.len(), 3);
+
+        // quarter circle of 1 cm radius counterclockwise from the x axis
+        let arc = l.shapes[0].get_points(SampleSettings::default());
+        assert!(arc[0].dist(V2::new(1.0, 0.0)) < 0.0001);
+        assert!(arc.last().unwrap().dist(V2::new(0.0, 1.0)) < 0.0001);
+        assert!((l.shapes[0].length() - std::f32::consts::FRAC_PI_2).abs() < 0.005);
+
+        // full ellipse with radii 2 cm and 1 cm
+        let ellipse = l.shapes[1].get_points(SampleSettings::default());
+        assert!(ellipse
+            .iter()
+            .all(|p| (p.x / 2.0).powi(2) + p.y.powi(2) < 1.0001));
+        assert!(ellipse.iter().any(|p| p.y > 0.999));
+
+        // a bulge of 1 is a counterclockwise half circle, passing below the chord
+        let bulge = l.shapes[2].get_points(SampleSettings::default());
+        assert!(bulge.last().unwrap().dist(V2::new(2.0, 0.0)) < 0.0001);
+        let lowest = bulge.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        assert!((lowest + 1.0).abs() < 0.005);
+    }
+
+    #[test]
+    fn import_polyline_spline_and_layers() {
+        let dxf = format!(
+            "0\nSECTION\n2\nTABLES\n0\nTABLE\n2\nLAYER\n\
+             0\nLAYER\n2\ncut\n70\n0\n62\n5\n370\n30\n\
+             0\nLAYER\n2\nhidden\n70\n0\n62\n-1\n\
+             0\nENDTAB\n0\nENDSEC\n{}",
+            dxf_of(
+                Some(5),
+                "0\nPOLYLINE\n8\ncut\n66\n1\n70\n1\n\
+                 0\nVERTEX\n8\ncut\n10\n0\n20\n0\n\
+                 0\nVERTEX\n8\ncut\n10\n1\n20\n0\n\
+                 0\nVERTEX\n8\ncut\n10\n1\n20\n1\n\
+                 0\nSEQEND\n8\ncut\n\
+                 0\nLINE\n8\nhidden\n10\n0\n20\n0\n11\n1\n21\n1\n\
+                 0\nSPLINE\n8\ncurves\n71\n2\n72\n6\n73\n3\n\
+                 40\n0\n40\n0\n40\n0\n40\n1\n40\n1\n40\n1\n\
+                 10\n0\n20\n0\n10\n1\n20\n2\n10\n2\n20\n0\n\
+                 0\nTEXT\n8\ncurves\n1\nignored\n",
+            )
+        );
+        let l = Layer::new_from_dxf_str(&dxf, DXF_IMPORT_TOLERANCE_CM).unwrap();
+        assert!(l.shapes.is_empty());
+        assert!(l.get_path("hidden").is_none());
+
+        let cut = l.get_path("cut").unwrap();
+        let props = cut.props_inheritable.clone().unwrap();
+        assert_eq!(props.color.unwrap(), ColorRgb::blue());
+        assert_eq!(props.pen_width_cm.unwrap(), 0.03);
+        assert_eq!(
+            cut.shapes[0].get_points(SampleSettings::default()),
+            vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+                V2::new(1.0, 1.0),
+                V2::new(0.0, 0.0)
+            ]
+        );
+
+        // quadratic bezier spline, peaking at half the control point's height
+        let spline = l.get_path("curves").unwrap().shapes[0].get_points(SampleSettings::default());
+        assert_eq!(spline[0], V2::new(0.0, 0.0));
+        assert!(spline.last().unwrap().dist(V2::new(2.0, 0.0)) < 0.0001);
+        let top = spline.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((top - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn import_errors() {
+        let parse = |dxf: &str| Layer::new_from_dxf_str(dxf, DXF_IMPORT_TOLERANCE_CM);
+        assert!(parse("0\nSECTION\n2\nENTITIES\nx\nLINE\n").is_err());
+        assert!(parse("0\nSECTION\n2\nENTITIES\n0\n").is_err());
+        assert!(parse(&dxf_of(None, "0\nLINE\n10\n0\n20\n0\n11\n1\n")).is_err());
+        assert!(parse(&dxf_of(None, "0\nCIRCLE\n10\na\n20\n0\n40\n1\n")).is_err());
+        assert!(parse(&dxf_of(Some(3), "")).is_err());
+        assert!(parse(&dxf_of(
+            None,
+            "0\nSPLINE\n71\n3\n10\n0\n20\n0\n10\n1\n20\n1\n"
+        ))
+        .is_err());
+
+        // unitless drawings are read as millimeters
+        let l = parse(&dxf_of(None, "0\nLINE\n10\n0\n20\n0\n11\n10\n21\n0\n")).unwrap();
+        assert_eq!(l.shapes[0].length(), 1.0);
+        assert!(matches!(&l.shapes[0], Shape::Path(_)));
+    }
+}
@@ -0,0 +1,464 @@
+use std::{collections::BTreeMap, f32::consts::PI, fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{Layer, Path, Plottable, SampleSettings, Shape, LARGE_EPSILON, V2};
+
+use super::{ColorRgb, Inheritable, LayerPropsInheritable};
+
+/// How GeoJSON longitude and latitude map to layer coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoJsonProjection {
+    /// Longitude and latitude are used as x and y.
+    None,
+    /// Spherical web mercator, scaled so one degree of longitude is `1.0`. Keeps angles and shapes of small areas, like map tiles.
+    WebMercator,
+}
+
+impl GeoJsonProjection {
+    /// Projects `[longitude, latitude]` to layer coordinates.
+    pub fn project(&self, longitude: f32, latitude: f32) -> V2 {
+        match self {
+            GeoJsonProjection::None => V2::new(longitude, latitude),
+            GeoJsonProjection::WebMercator => {
+                let latitude = latitude.clamp(-85.051_13, 85.051_13).to_radians();
+                V2::new(
+                    longitude,
+                    (PI / 4.0 + latitude / 2.0).tan().ln().to_degrees(),
+                )
+            }
+        }
+    }
+    /// Inverse of [`GeoJsonProjection::project`], returning `[longitude, latitude]`.
+    pub fn unproject(&self, point: V2) -> [f32; 2] {
+        match self {
+            GeoJsonProjection::None => [point.x, point.y],
+            GeoJsonProjection::WebMercator => [
+                point.x,
+                (2.0 * point.y.to_radians().exp().atan() - PI / 2.0).to_degrees(),
+            ],
+        }
+    }
+}
+
+/// Settings for [`Layer::to_geojson`] and [`Layer::new_from_geojson`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoJsonSettings {
+    pub projection: GeoJsonProjection,
+    /// Feature property holding the `/` separated path of the layer a feature belongs to, see [`Layer::get_path`].
+    pub layer_property: String,
+}
+
+impl GeoJsonSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_projection(mut self, projection: GeoJsonProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+    pub fn with_layer_property(mut self, layer_property: &str) -> Self {
+        self.layer_property = layer_property.to_string();
+        self
+    }
+}
+
+impl Default for GeoJsonSettings {
+    fn default() -> Self {
+        Self {
+            projection: GeoJsonProjection::None,
+            layer_property: "layer".to_string(),
+        }
+    }
+}
+
+impl Layer {
+    /// Returns the layer as a GeoJSON `FeatureCollection` with one feature per shape.
+    ///
+    /// [`Path`]s become `LineString`s, all other shapes `Polygon`s. Feature properties hold the path of the shape's layer
+    /// (in [`GeoJsonSettings::layer_property`], omitted for the root), the props the layer specifies itself (`color`,
+    /// `pen_width_cm`, `pen_id`, `speed_factor`, `pen_down_depth_cm`, `passes` and `plot_priority`) and the layer's metadata.
+    /// Inherited props are left out, so [`Layer::new_from_geojson`] restores them by inheritance.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let roads = Layer::new_from(vec![Path::new_shape_from(vec![V2::new(13.4, 52.5), V2::new(13.5, 52.5)])]).with_name("roads");
+    /// let layer = Layer::new_from_shapes_and_layers(vec![], vec![roads]);
+    /// let geojson = layer.to_geojson(&GeoJsonSettings::new(), SampleSettings::default()).unwrap();
+    /// assert!(geojson.contains(r#""layer":"roads""#));
+    /// ```
+    pub fn to_geojson(
+        &self,
+        settings: &GeoJsonSettings,
+        sample_settings: SampleSettings,
+    ) -> Result<String> {
+        let mut features = Vec::new();
+        self.collect_geojson_features(None, settings, sample_settings, &mut features);
+        Ok(serde_json::to_string(&json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))?)
+    }
+
+    fn collect_geojson_features(
+        &self,
+        path: Option<&str>,
+        settings: &GeoJsonSettings,
+        sample_settings: SampleSettings,
+        features: &mut Vec<Value>,
+    ) {
+        let mut properties = Map::new();
+        if let Some(path) = path.filter(|path| !path.is_empty()) {
+            properties.insert(settings.layer_property.clone(), json!(path));
+        }
+        if let Inheritable::Specified(own_props) = &self.props_inheritable {
+            if let Inheritable::Specified(color) = &own_props.color {
+                properties.insert("color".to_string(), json!(color.hex()));
+            }
+            let mut insert = |key: &str, value: Option<Value>| {
+                if let Some(value) = value {
+                    properties.insert(key.to_string(), value);
+                }
+            };
+            insert("pen_width_cm", specified_json(&own_props.pen_width_cm));
+            insert("pen_id", specified_json(&own_props.pen_id));
+            insert("speed_factor", specified_json(&own_props.speed_factor));
+            insert(
+                "pen_down_depth_cm",
+                specified_json(&own_props.pen_down_depth_cm),
+            );
+            insert("passes", specified_json(&own_props.passes));
+            insert("plot_priority", specified_json(&own_props.plot_priority));
+        }
+        for (key, value) in self.props.metadata.iter() {
+            properties.entry(key.clone()).or_insert(json!(value));
+        }
+
+        let to_positions = |points: &[V2]| -> Vec<[f32; 2]> {
+            points
+                .iter()
+                .map(|point| settings.projection.unproject(*point))
+                .collect()
+        };
+        // geojson rings repeat their first position exactly
+        let closed = |points: &[V2]| {
+            let mut points = points.to_vec();
+            let first = points[0];
+            match points.last_mut() {
+                Some(last) if last.dist(first) <= LARGE_EPSILON => *last = first,
+                _ => points.push(first),
+            }
+            points
+        };
+        for shape in self.iter() {
+            let geometry = match shape {
+                Shape::Path(path) => json!({
+                    "type": "LineString",
+                    "coordinates": to_positions(path.get_points_ref()),
+                }),
+                Shape::Compound(compound) => json!({
+                    "type": "Polygon",
+                    "coordinates": compound
+                        .rings()
+                        .filter(|ring| !ring.get_points_ref().is_empty())
+                        .map(|ring| to_positions(&closed(ring.get_points_ref())))
+                        .collect::<Vec<_>>(),
+                }),
+                _ => json!({
+                    "type": "Polygon",
+                    "coordinates": [to_positions(&closed(&shape.get_points(sample_settings)))],
+                }),
+            };
+            features.push(json!({
+                "type": "Feature",
+                "geometry": geometry,
+                "properties": properties,
+            }));
+        }
+
+        for sublayer in self.iter_sublayers() {
+            let sublayer_path = match (&sublayer.props.name, path) {
+                (Some(name), Some(path)) if !path.is_empty() => Some(format!("{}/{}", path, name)),
+                (Some(name), _) => Some(name.clone()),
+                (None, path) => path.map(str::to_string),
+            };
+            sublayer.collect_geojson_features(
+                sublayer_path.as_deref(),
+                settings,
+                sample_settings,
+                features,
+            );
+        }
+    }
+
+    /// Writes the layer to a .geojson file, see [`Layer::to_geojson`].
+    pub fn write_geojson(
+        &self,
+        path: &PathBuf,
+        settings: &GeoJsonSettings,
+        sample_settings: SampleSettings,
+    ) -> Result<()> {
+        fs::write(path, self.to_geojson(settings, sample_settings)?)?;
+        Ok(())
+    }
+
+    /// Creates a new `Layer` from a GeoJSON `FeatureCollection`, `Feature` or geometry.
+    ///
+    /// Every line and ring of `LineString`, `MultiLineString`, `Polygon` and `MultiPolygon` geometries becomes a [`Path`],
+    /// points are ignored. Features are put into the layer named by their [`GeoJsonSettings::layer_property`], creating
+    /// sublayers as needed. The properties `color` (or `stroke`), `pen_width_cm`, `pen_id`, `speed_factor`, `pen_down_depth_cm`,
+    /// `passes` and `plot_priority` become the layer's props, features with different props are put into separate layers.
+    /// Other properties that are equal for all features of a layer are added to its metadata.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let geojson = r#"{"type": "Feature", "properties": {"layer": "rivers", "color": "blue"},
+    ///     "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1], [2, 1]]}}"#;
+    /// let layer = Layer::new_from_geojson(geojson, &GeoJsonSettings::new()).unwrap();
+    /// assert_eq!(layer.get_path("rivers").unwrap().len(), 1);
+    /// ```
+    pub fn new_from_geojson(geojson: &str, settings: &GeoJsonSettings) -> Result<Layer> {
+        let document: Value = serde_json::from_str(geojson).context("invalid geojson")?;
+        let features: Vec<&Value> = match geojson_type(&document)? {
+            "FeatureCollection" => document
+                .get("features")
+                .and_then(Value::as_array)
+                .context("FeatureCollection without features")?
+                .iter()
+                .collect(),
+            _ => vec![&document],
+        };
+
+        // layer path, props and the metadata shared by all features of each group
+        let mut groups: Vec<GeoJsonGroup> = Vec::new();
+        let mut layer = Layer::new();
+        for (i, feature) in features.into_iter().enumerate() {
+            let (geometry, properties) = match geojson_type(feature)? {
+                "Feature" => (
+                    feature.get("geometry").unwrap_or(&Value::Null),
+                    feature.get("properties").and_then(Value::as_object),
+                ),
+                _ => (feature, None),
+            };
+            let mut paths = Vec::new();
+            collect_geojson_paths(geometry, settings.projection, &mut paths)
+                .with_context(|| format!("malformed geojson feature {}", i))?;
+            let shapes = paths.into_iter().map(Path::new_shape_from);
+
+            let empty = Map::new();
+            let properties = properties.unwrap_or(&empty);
+            let layer_path = properties
+                .get(&settings.layer_property)
+                .map(geojson_property_string)
+                .filter(|path| !path.is_empty());
+            let (props, metadata) = geojson_props(properties, &settings.layer_property)
+                .with_context(|| format!("invalid properties of geojson feature {}", i))?;
+
+            if layer_path.is_none() && props == Inheritable::Inherit {
+                layer.shapes.extend(shapes);
+                continue;
+            }
+            match groups
+                .iter_mut()
+                .find(|group| group.path == layer_path && group.props == props)
+            {
+                Some(group) => {
+                    group
+                        .metadata
+                        .retain(|key, value| metadata.get(key) == Some(value));
+                    group.shapes.extend(shapes);
+                }
+                None => groups.push(GeoJsonGroup {
+                    path: layer_path,
+                    props,
+                    metadata,
+                    shapes: shapes.collect(),
+                }),
+            }
+        }
+
+        for group in groups {
+            let names: Vec<&str> = group
+                .path
+                .as_deref()
+                .unwrap_or("")
+                .split('/')
+                .filter(|name| !name.is_empty())
+                .collect();
+            let mut parent = &mut layer;
+            for (depth, name) in names.iter().enumerate() {
+                let is_leaf = depth + 1 == names.len();
+                let index = parent.sublayers.iter().position(|sublayer| {
+                    sublayer.props.name.as_deref() == Some(*name)
+                        && (!is_leaf || sublayer.props_inheritable == group.props)
+                });
+                let index = index.unwrap_or_else(|| {
+                    let mut sublayer = Layer::new().with_name(name);
+                    if is_leaf {
+                        sublayer.props_inheritable = group.props.clone();
+                    }
+                    parent.push_layer(sublayer);
+                    parent.sublayers.len() - 1
+                });
+                parent = &mut parent.sublayers[index];
+            }
+            if names.is_empty() {
+                let mut sublayer = Layer::new();
+                sublayer.props_inheritable = group.props.clone();
+                layer.push_layer(sublayer);
+                parent = layer.sublayers.last_mut().unwrap();
+            }
+            parent.shapes.extend(group.shapes);
+            parent.props.metadata.extend(group.metadata);
+        }
+
+        Ok(layer)
+    }
+
+    /// Creates a new `Layer` from a .geojson file, see [`Layer::new_from_geojson`].
+    pub fn new_from_geojson_file(path: &PathBuf, settings: &GeoJsonSettings) -> Result<Layer> {
+        Self::new_from_geojson(&fs::read_to_string(path)?, settings)
+    }
+}
+
+struct GeoJsonGroup {
+    path: Option<String>,
+    props: Inheritable<LayerPropsInheritable>,
+    metadata: BTreeMap<String, String>,
+    shapes: Vec<Shape>,
+}
+
+fn geojson_type(object: &Value) -> Result<&str> {
+    object
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("geojson object without type"))
+}
+
+fn specified_json<T: Clone + Serialize>(value: &Inheritable<T>) -> Option<Value> {
+    match value {
+        Inheritable::Specified(value) => Some(json!(value)),
+        Inheritable::Inherit => None,
+    }
+}
+
+fn geojson_property_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Splits feature properties into layer props and metadata.
+fn geojson_props(
+    properties: &Map<String, Value>,
+    layer_property: &str,
+) -> Result<(Inheritable<LayerPropsInheritable>, BTreeMap<String, String>)> {
+    let mut props = LayerPropsInheritable::inherit_all();
+    let mut metadata = BTreeMap::new();
+    let number = |key: &str, value: &Value| {
+        value
+            .as_f64()
+            .ok_or_else(|| anyhow!("property '{}' is not a number", key))
+    };
+    let integer = |key: &str, value: &Value| {
+        value
+            .as_i64()
+            .ok_or_else(|| anyhow!("property '{}' is not an integer", key))
+    };
+    for (key, value) in properties {
+        match key.as_str() {
+            key if key == layer_property => {}
+            "color" | "stroke" => {
+                let color = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("property '{}' is not a string", key))?;
+                props = props.with_color(ColorRgb::from_css(color)?);
+            }
+            "pen_width_cm" => props = props.with_pen_width_cm(number(key, value)? as f32),
+            "pen_id" => props = props.with_pen_id(integer(key, value)? as u32),
+            "speed_factor" => props = props.with_speed_factor(number(key, value)? as f32),
+            "pen_down_depth_cm" => props = props.with_pen_down_depth_cm(number(key, value)? as f32),
+            "passes" => props = props.with_passes(integer(key, value)? as u32),
+            "plot_priority" => props = props.with_plot_priority(integer(key, value)? as i32),
+            _ => {
+                if !value.is_null() {
+                    metadata.insert(key.clone(), geojson_property_string(value));
+                }
+            }
+        }
+    }
+    let props = if props == LayerPropsInheritable::inherit_all() {
+        Inheritable::Inherit
+    } else {
+        Inheritable::Specified(props)
+    };
+    Ok((props, metadata))
+}
+
+fn collect_geojson_paths(
+    geometry: &Value,
+    projection: GeoJsonProjection,
+    paths: &mut Vec<Vec<V2>>,
+) -> Result<()> {
+    if geometry.is_null() {
+        return Ok(());
+    }
+    let coordinates = || {
+        geometry
+            .get("coordinates")
+            .ok_or_else(|| anyhow!("geometry without coordinates"))
+    };
+    let line = |value: &Value| -> Result<Vec<V2>> {
+        value
+            .as_array()
+            .context("expected a list of positions")?
+            .iter()
+            .map(|position| {
+                let position = position.as_array().context("expected a position")?;
+                let (Some(longitude), Some(latitude)) = (
+                    position.first().and_then(Value::as_f64),
+                    position.get(1).and_then(Value::as_f64),
+                ) else {
+                    bail!("position without two numbers");
+                };
+                Ok(projection.project(longitude as f32, latitude as f32))
+            })
+            .collect()
+    };
+    let list = |value: &Value| -> Result<Vec<Value>> {
+        Ok(value.as_array().context("expected a list")?.clone())
+    };
+
+    match geojson_type(geometry)? {
+        "Point" | "MultiPoint" => {}
+        "LineString" => paths.push(line(coordinates()?)?),
+        "MultiLineString" | "Polygon" => {
+            for ring in list(coordinates()?)? {
+                paths.push(line(&ring)?);
+            }
+        }
+        "MultiPolygon" => {
+            for polygon in list(coordinates()?)? {
+                for ring in list(&polygon)? {
+                    paths.push(line(&ring)?);
+                }
+            }
+        }
+        "GeometryCollection" => {
+            let geometries = geometry
+                .get("geometries")
+                .and_then(Value::as_array)
+                .context("GeometryCollection without geometries")?;
+            for geometry in geometries {
+                collect_geojson_paths(geometry, projection, paths)?;
+            }
+        }
+        other => bail!("unsupported geometry type '{}'", other),
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod test_geojson {
+    use crate::{
+        Circle, ColorRgb, GeoJsonProjection, GeoJsonSettings, Inheritable, Layer, Path, Plottable,
+        SampleSettings, V2,
+    };
+
+    #[test]
+    fn export_features() {
+        let roads = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(0.0, 0.0),
+            V2::new(1.0, 0.5),
+        ])])
+        .with_name("roads")
+        .with_color(ColorRgb::red());
+        let parks = Layer::new_from(vec![Circle::new_shape(V2::new(2.0, 2.0), 1.0)])
+            .with_name("parks")
+            .with_pen_width_cm(0.1);
+        let map = Layer::new_from_shapes_and_layers(vec![], vec![roads, parks]).with_name("map");
+        let layer = Layer::new_from_shapes_and_layers(vec![], vec![map]);
+
+        let geojson = layer
+            .to_geojson(&GeoJsonSettings::new(), SampleSettings::default())
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+        assert_eq!(value["type"], "FeatureCollection");
+        let features = value["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(features[0]["geometry"]["coordinates"][1][0], 1.0);
+        assert_eq!(features[0]["properties"]["layer"], "map/roads");
+        assert_eq!(features[0]["properties"]["color"], ColorRgb::red().hex());
+
+        assert_eq!(features[1]["geometry"]["type"], "Polygon");
+        let ring = features[1]["geometry"]["coordinates"][0]
+            .as_array()
+            .unwrap();
+        assert_eq!(ring.first(), ring.last());
+        assert_eq!(features[1]["properties"]["layer"], "map/parks");
+        assert_eq!(features[1]["properties"]["pen_width_cm"], 0.1_f32);
+    }
+
+    #[test]
+    fn import_groups_features_into_layers() {
+        let geojson = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "properties": {"layer": "map/roads", "stroke": "red", "kind": "road", "id": 1},
+             "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 0]]}},
+            {"type": "Feature", "properties": {"layer": "map/roads", "stroke": "red", "kind": "road", "id": 2},
+             "geometry": {"type": "MultiLineString", "coordinates": [[[0, 1], [1, 1]], [[0, 2], [1, 2]]]}},
+            {"type": "Feature", "properties": {"layer": "map/roads", "stroke": "blue"},
+             "geometry": {"type": "LineString", "coordinates": [[0, 3], [1, 3]]}},
+            {"type": "Feature", "properties": {"layer": "map/lakes", "passes": 2},
+             "geometry": {"type": "MultiPolygon", "coordinates": [[[[0, 0], [1, 0], [1, 1], [0, 0]]]]}},
+            {"type": "Feature", "properties": null,
+             "geometry": {"type": "GeometryCollection", "geometries": [
+                {"type": "Point", "coordinates": [5, 5]},
+                {"type": "LineString", "coordinates": [[5, 5], [6, 6]]}
+             ]}}
+        ]}"#;
+        let layer = Layer::new_from_geojson(geojson, &GeoJsonSettings::new()).unwrap();
+        assert_eq!(layer.len(), 1);
+
+        let map = layer.get_path("map").unwrap();
+        assert_eq!(map.props_inheritable, Inheritable::Inherit);
+        assert_eq!(map.sublayers.len(), 3);
+
+        let red_roads = &map.sublayers[0];
+        assert_eq!(red_roads.props.name.as_deref(), Some("roads"));
+        assert_eq!(red_roads.len(), 3);
+        assert_eq!(red_roads.props.metadata.get("kind").unwrap(), "road");
+        assert!(!red_roads.props.metadata.contains_key("id"));
+        let Inheritable::Specified(props) = &red_roads.props_inheritable else {
+            panic!("props should be specified");
+        };
+        assert_eq!(props.color, Inheritable::Specified(ColorRgb::red()));
+
+        let blue_roads = &map.sublayers[1];
+        assert_eq!(blue_roads.props.name.as_deref(), Some("roads"));
+        assert_eq!(blue_roads.len(), 1);
+
+        let lakes = layer.get_path("map/lakes").unwrap();
+        assert_eq!(lakes.len(), 1);
+        let Inheritable::Specified(props) = &lakes.props_inheritable else {
+            panic!("props should be specified");
+        };
+        assert_eq!(props.passes, Inheritable::Specified(2));
+    }
+
+    #[test]
+    fn round_trip() {
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![Layer::new_from(vec![Path::new_shape_from(vec![
+                V2::new(10.0, 50.0),
+                V2::new(11.0, 51.0),
+                V2::new(12.0, 49.5),
+            ])])
+            .with_name("track")
+            .with_color(ColorRgb::blue())],
+        );
+        let settings = GeoJsonSettings::new().with_layer_property("group");
+        let geojson = layer
+            .to_geojson(&settings, SampleSettings::default())
+            .unwrap();
+        let read = Layer::new_from_geojson(&geojson, &settings).unwrap();
+        let track = read.get_path("track").unwrap();
+        assert_eq!(track.shapes, layer.sublayers[0].shapes);
+        let Inheritable::Specified(props) = &track.props_inheritable else {
+            panic!("props should be specified");
+        };
+        assert_eq!(props.color, Inheritable::Specified(ColorRgb::blue()));
+    }
+
+    #[test]
+    fn round_trip_keeps_root_shapes_and_inheritance() {
+        let path = Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 1.0)]);
+        let inner = Layer::new_from(vec![path.clone()])
+            .with_name("inner")
+            .with_pen_width_cm(0.2);
+        let outer = Layer::new_from_shapes_and_layers(vec![path.clone()], vec![inner])
+            .with_name("outer")
+            .with_color(ColorRgb::red());
+        let layer = Layer::new_from_shapes_and_layers(vec![path.clone()], vec![outer]);
+
+        let geojson = layer
+            .to_geojson(&GeoJsonSettings::new(), SampleSettings::default())
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+        let inner_properties = &value["features"][2]["properties"];
+        assert_eq!(inner_properties["layer"], "outer/inner");
+        assert!(inner_properties.get("color").is_none());
+
+        let read = Layer::new_from_geojson(&geojson, &GeoJsonSettings::new()).unwrap();
+        assert_eq!(read.shapes, vec![path]);
+        assert_eq!(read.props_inheritable, Inheritable::Inherit);
+        assert_eq!(read.sublayers.len(), 1);
+        for name in ["outer", "outer/inner"] {
+            let original = layer.get_path(name).unwrap();
+            let restored = read.get_path(name).unwrap();
+            assert_eq!(restored.shapes, original.shapes);
+            assert_eq!(restored.props_inheritable, original.props_inheritable);
+            assert_eq!(restored.sublayers.len(), original.sublayers.len());
+        }
+    }
+
+    #[test]
+    fn web_mercator() {
+        let projection = GeoJsonProjection::WebMercator;
+        assert!(projection.project(13.4, 0.0).dist(V2::new(13.4, 0.0)) < 1e-5);
+        // mercator stretches high latitudes
+        assert!(projection.project(0.0, 60.0).y > 60.0);
+        for latitude in [-70.0, -12.5, 0.0, 45.0, 80.0] {
+            let [longitude, unprojected] = projection.unproject(projection.project(7.0, latitude));
+            assert!((longitude - 7.0).abs() < 1e-5);
+            assert!((unprojected - latitude).abs() < 1e-3);
+        }
+
+        let geojson = r#"{"type": "LineString", "coordinates": [[0, 0], [10, 60]]}"#;
+        let settings = GeoJsonSettings::new().with_projection(projection);
+        let layer = Layer::new_from_geojson(geojson, &settings).unwrap();
+        let points = layer.shapes[0].get_points(SampleSettings::default());
+        assert!(points[1].dist(projection.project(10.0, 60.0)) < 1e-5);
+    }
+
+    #[test]
+    fn rejects_invalid_geojson() {
+        let settings = GeoJsonSettings::new();
+        assert!(Layer::new_from_geojson("[]", &settings).is_err());
+        assert!(Layer::new_from_geojson(r#"{"type": "Curve"}"#, &settings).is_err());
+        assert!(Layer::new_from_geojson(
+            r#"{"type": "LineString", "coordinates": [[0, "a"]]}"#,
+            &settings
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{Layer, Path, Plottable, SampleSettings, Shape, V2};
+
+use super::ColorRgb;
+
+/// Plotter units per cm of most HP and Roland plotters (0.025 mm per unit).
+pub const HPGL_UNITS_PER_CM: f32 = 400.0;
+
+/// How [`Layer::to_hpgl`] selects pens with the `SP` command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HpglPenSelection {
+    /// Pen `pen_id + 1` of [`crate::LayerPropsInheritable::pen_id`], as HPGL pen `0` means putting the pen away.
+    PenIds,
+    /// One pen per distinct layer color, numbered from `1` in order of first use.
+    Colors,
+    /// Always the same pen.
+    Single(u32),
+}
+
+/// Settings for [`Layer::to_hpgl`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HpglSettings {
+    pub units_per_cm: f32,
+    pub pen_selection: HpglPenSelection,
+    /// Pen down velocity in cm/s written with `VS`, scaled by [`crate::LayerPropsInheritable::plot_speed_factor`]. `None` keeps the plotter's default.
+    pub velocity: Option<f32>,
+    /// Page size in cm written with `PS` (HPGL/2). `None` keeps the plotter's default.
+    pub page_size: Option<V2>,
+}
+
+impl HpglSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_units_per_cm(mut self, units_per_cm: f32) -> Self {
+        self.units_per_cm = units_per_cm;
+        self
+    }
+    pub fn with_pen_selection(mut self, pen_selection: HpglPenSelection) -> Self {
+        self.pen_selection = pen_selection;
+        self
+    }
+    pub fn with_velocity(mut self, velocity: Option<f32>) -> Self {
+        self.velocity = velocity;
+        self
+    }
+    pub fn with_page_size(mut self, page_size: Option<V2>) -> Self {
+        self.page_size = page_size;
+        self
+    }
+}
+
+impl Default for HpglSettings {
+    fn default() -> Self {
+        Self {
+            units_per_cm: HPGL_UNITS_PER_CM,
+            pen_selection: HpglPenSelection::PenIds,
+            velocity: None,
+            page_size: None,
+        }
+    }
+}
+
+impl Layer {
+    /// Returns the layer as HPGL commands, interpreting all coordinates as cm.
+    ///
+    /// Layers are written in plot order (see [`Layer::layers_in_plot_order`]) with all passes of a shape after each other,
+    /// each continuous stroke as `PU` to its start followed by `PD` through its points.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Path::new_shape_from(vec![V2::new(1.0, 1.0), V2::new(2.0, 1.0)])]);
+    /// let hpgl = layer.to_hpgl(&HpglSettings::new(), SampleSettings::default());
+    /// assert_eq!(hpgl, "IN;\nSP1;\nPU400,400;\nPD800,400;\nPU;\nSP0;\n");
+    /// ```
+    pub fn to_hpgl(&self, settings: &HpglSettings, sample_settings: SampleSettings) -> String {
+        let to_units = |point: V2| {
+            let units = point * settings.units_per_cm;
+            format!("{},{}", units.x.round() as i64, units.y.round() as i64)
+        };
+
+        let mut hpgl = String::from("IN;\n");
+        if let Some(page_size) = settings.page_size {
+            hpgl += &format!("PS{};\n", to_units(page_size));
+        }
+
+        let mut colors: Vec<ColorRgb> = Vec::new();
+        let mut current_pen = None;
+        let mut current_velocity = None;
+        let mut pos = V2::zero();
+        for (layer, props) in self.layers_in_plot_order() {
+            if layer.shapes.is_empty() {
+                continue;
+            }
+
+            let pen = match settings.pen_selection {
+                HpglPenSelection::PenIds => props.pen_id.unwrap() + 1,
+                HpglPenSelection::Colors => {
+                    let color = props.color.unwrap();
+                    let index = colors.iter().position(|c| *c == color).unwrap_or_else(|| {
+                        colors.push(color);
+                        colors.len() - 1
+                    });
+                    index as u32 + 1
+                }
+                HpglPenSelection::Single(pen) => pen,
+            };
+            if current_pen != Some(pen) {
+                hpgl += &format!("SP{};\n", pen);
+                current_pen = Some(pen);
+            }
+
+            let velocity = settings
+                .velocity
+                .map(|velocity| velocity * props.plot_speed_factor());
+            if let Some(velocity) = velocity.filter(|v| current_velocity != Some(*v)) {
+                hpgl += &format!("VS{};\n", velocity);
+                current_velocity = Some(velocity);
+            }
+
+            let mut write_stroke = |stroke: &Shape| {
+                let points = stroke.get_points_from(pos, sample_settings);
+                if points.len() < 2 {
+                    return;
+                }
+                hpgl += &format!("PU{};\n", to_units(points[0]));
+                let down: Vec<_> = points[1..].iter().map(|point| to_units(*point)).collect();
+                hpgl += &format!("PD{};\n", down.join(","));
+                pos = *points.last().unwrap();
+            };
+            for shape in layer.iter() {
+                for _ in 0..props.plot_passes() {
+                    match shape {
+                        Shape::Compound(compound) => {
+                            compound.rings().for_each(|ring| write_stroke(&ring.into()))
+                        }
+                        _ => write_stroke(shape),
+                    }
+                }
+            }
+        }
+
+        hpgl += "PU;\nSP0;\n";
+        hpgl
+    }
+
+    /// Writes the layer to an HPGL file, see [`Layer::to_hpgl`].
+    pub fn write_hpgl(
+        &self,
+        path: &PathBuf,
+        settings: &HpglSettings,
+        sample_settings: SampleSettings,
+    ) -> Result<()> {
+        fs::write(path, self.to_hpgl(settings, sample_settings))?;
+        Ok(())
+    }
+
+    /// Creates a new `Layer` from an HPGL file, see [`Layer::new_from_hpgl_str`].
+    pub fn new_from_hpgl(path: &PathBuf, units_per_cm: f32) -> Result<Layer> {
+        Self::new_from_hpgl_str(&fs::read_to_string(path)?, units_per_cm)
+    }
+
+    /// Creates a new `Layer` from HPGL commands, converting plotter units to cm with `units_per_cm`.
+    ///
+    /// Pen movement is read from `PU`, `PD`, `PA` and `PR`, every pen down stroke becomes a [`Path`].
+    /// Strokes drawn after selecting a pen with `SP` are put into a sublayer per pen, named `pen <number>` and with the
+    /// [`crate::LayerPropsInheritable::pen_id`] `number - 1`. Other commands are ignored.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from_hpgl_str("IN;SP2;PU0,0;PD400,0,400,400;PR;PD-400,0;", HPGL_UNITS_PER_CM).unwrap();
+    /// let pen = layer.get_path("pen 2").unwrap();
+    /// assert_eq!(pen.shapes[0].get_points(SampleSettings::default()).len(), 4);
+    /// ```
+    pub fn new_from_hpgl_str(hpgl: &str, units_per_cm: f32) -> Result<Layer> {
+        let mut layer = Layer::new();
+        let mut pen_index: Option<usize> = None;
+        let mut pos = V2::zero();
+        let mut is_down = false;
+        let mut is_relative = false;
+        let mut stroke: Vec<V2> = Vec::new();
+
+        let finish_stroke = |stroke: &mut Vec<V2>, layer: &mut Layer, pen_index: Option<usize>| {
+            if stroke.len() >= 2 {
+                let path = Path::new_from(std::mem::take(stroke));
+                match pen_index {
+                    Some(index) => layer.sublayers[index].push(path),
+                    None => layer.push(path),
+                }
+            }
+            stroke.clear();
+        };
+
+        for (mnemonic, params) in tokenize_hpgl(hpgl)? {
+            match mnemonic.as_str() {
+                "PU" | "PD" | "PA" | "PR" => {
+                    match mnemonic.as_str() {
+                        "PU" => {
+                            finish_stroke(&mut stroke, &mut layer, pen_index);
+                            is_down = false;
+                        }
+                        "PD" => is_down = true,
+                        "PA" => is_relative = false,
+                        _ => is_relative = true,
+                    }
+                    let values = parse_hpgl_numbers(&mnemonic, &params)?;
+                    if values.len() % 2 != 0 {
+                        bail!("odd number of coordinates in '{}{}'", mnemonic, params);
+                    }
+                    for pair in values.chunks_exact(2) {
+                        let point = V2::new(pair[0], pair[1]) / units_per_cm;
+                        let from = pos;
+                        pos = if is_relative { from + point } else { point };
+                        if is_down {
+                            if stroke.is_empty() {
+                                stroke.push(from);
+                            }
+                            stroke.push(pos);
+                        }
+                    }
+                }
+                "SP" => {
+                    finish_stroke(&mut stroke, &mut layer, pen_index);
+                    is_down = false;
+                    let values = parse_hpgl_numbers(&mnemonic, &params)?;
+                    let pen = values.first().copied().unwrap_or(0.0) as u32;
+                    pen_index = if pen == 0 {
+                        None
+                    } else {
+                        let name = format!("pen {}", pen);
+                        match layer
+                            .sublayers
+                            .iter()
+                            .position(|sublayer| sublayer.props.name.as_deref() == Some(&name))
+                        {
+                            Some(index) => Some(index),
+                            None => {
+                                layer
+                                    .push_layer(Layer::new().with_name(&name).with_pen_id(pen - 1));
+                                Some(layer.sublayers.len() - 1)
+                            }
+                        }
+                    };
+                }
+                "IN" => {
+                    finish_stroke(&mut stroke, &mut layer, pen_index);
+                    pos = V2::zero();
+                    is_down = false;
+                    is_relative = false;
+                }
+                _ => {}
+            }
+        }
+        finish_stroke(&mut stroke, &mut layer, pen_index);
+
+        Ok(layer)
+    }
+}
+
+/// Splits HPGL into commands of a two letter mnemonic and its parameters. Terminators (`;`) are optional.
+fn tokenize_hpgl(hpgl: &str) -> Result<Vec<(String, String)>> {
+    let mut commands = Vec::new();
+    let mut chars = hpgl.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_whitespace() || ch == ';' {
+            continue;
+        }
+        if !ch.is_ascii_alphabetic() {
+            bail!("expected an HPGL command, found '{}'", ch);
+        }
+        let Some(second) = chars.next().filter(|c| c.is_ascii_alphabetic()) else {
+            bail!("incomplete HPGL command starting with '{}'", ch);
+        };
+        let mnemonic = format!("{}{}", ch, second).to_ascii_uppercase();
+
+        let mut params = String::new();
+        if mnemonic == "LB" {
+            // labels run until the end of text character
+            for c in chars.by_ref() {
+                if c == '\u{3}' {
+                    break;
+                }
+                params.push(c);
+            }
+        } else {
+            while let Some(c) = chars.peek().copied() {
+                if c.is_ascii_alphabetic() || c == ';' {
+                    break;
+                }
+                params.push(c);
+                chars.next();
+            }
+        }
+        commands.push((mnemonic, params.trim().to_string()));
+    }
+    Ok(commands)
+}
+
+fn parse_hpgl_numbers(mnemonic: &str, params: &str) -> Result<Vec<f32>> {
+    params
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse::<f32>()
+                .map_err(|_| anyhow!("invalid number '{}' in '{}{}'", token, mnemonic, params))
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test_hpgl {
+    use crate::{
+        Circle, ColorRgb, HpglPenSelection, HpglSettings, Layer, Path, Plottable, Rect,
+        SampleSettings, HPGL_UNITS_PER_CM, V2,
+    };
+
+    #[test]
+    fn pen_selection() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+            ])],
+            vec![
+                Layer::new_from(vec![Rect::new_shape(V2::new(2.0, 2.0), V2::new(3.0, 3.0))])
+                    .with_color(ColorRgb::red())
+                    .with_pen_id(2),
+            ],
+        );
+        let by_id = l.to_hpgl(&HpglSettings::new(), SampleSettings::default());
+        assert!(by_id.contains("SP1;"));
+        assert!(by_id.contains("SP3;"));
+
+        let by_color = l.to_hpgl(
+            &HpglSettings::new().with_pen_selection(HpglPenSelection::Colors),
+            SampleSettings::default(),
+        );
+        assert!(by_color.contains("SP1;"));
+        assert!(by_color.contains("SP2;"));
+        assert!(!by_color.contains("SP3;"));
+
+        let single = l.to_hpgl(
+            &HpglSettings::new().with_pen_selection(HpglPenSelection::Single(4)),
+            SampleSettings::default(),
+        );
+        assert_eq!(single.matches("SP4;").count(), 1);
+        assert!(single.ends_with("PU;\nSP0;\n"));
+    }
+
+    #[test]
+    fn velocity_and_page() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+                V2::new(1.0, 1.0),
+            ])],
+            vec![
+                Layer::new_from(vec![Rect::new_shape(V2::new(2.0, 2.0), V2::new(3.0, 3.0))])
+                    .with_speed_factor(0.5),
+            ],
+        );
+        let hpgl = l.to_hpgl(
+            &HpglSettings::new()
+                .with_units_per_cm(40.0)
+                .with_velocity(Some(20.0))
+                .with_page_size(Some(V2::new(21.0, 29.7))),
+            SampleSettings::default(),
+        );
+        assert!(hpgl.starts_with("IN;\nPS840,1188;\n"));
+        assert!(hpgl.contains("VS20;"));
+        assert!(hpgl.contains("VS10;"));
+        assert!(hpgl.contains("PU0,0;\nPD40,0,40,40;"));
+    }
+
+    #[test]
+    fn passes_per_shape() {
+        let l = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(0.0, 2.0), V2::new(1.0, 2.0)]),
+        ])
+        .with_passes(2);
+        let hpgl = l.to_hpgl(&HpglSettings::new(), SampleSettings::default());
+        assert_eq!(
+            hpgl,
+            "IN;\nSP1;\nPU0,0;\nPD400,0;\nPU400,0;\nPD0,0;\nPU0,800;\nPD400,800;\nPU400,800;\nPD0,800;\nPU;\nSP0;\n"
+        );
+    }
+
+    #[test]
+    fn import_commands() {
+        let hpgl = "IN;PA;PU100,100;PD200,100,200,200;PU;\
+                    SP2;PR;PU0,100;PD100,0;PD0,100 PU\n\
+                    SP1;PA;PD300,300;PU;SP0;";
+        let l = Layer::new_from_hpgl_str(hpgl, 100.0).unwrap();
+
+        assert_eq!(l.len(), 1);
+        assert_eq!(
+            l.shapes[0].get_points(SampleSettings::default()),
+            vec![V2::new(1.0, 1.0), V2::new(2.0, 1.0), V2::new(2.0, 2.0)]
+        );
+
+        let pen_2 = l.get_path("pen 2").unwrap();
+        assert_eq!(pen_2.props_inheritable.clone().unwrap().pen_id.unwrap(), 1);
+        assert_eq!(
+            pen_2.shapes[0].get_points(SampleSettings::default()),
+            vec![V2::new(2.0, 3.0), V2::new(3.0, 3.0), V2::new(3.0, 4.0)]
+        );
+
+        // strokes start at the position left by the previous pen
+        let pen_1 = l.get_path("pen 1").unwrap();
+        assert_eq!(
+            pen_1.shapes[0].get_points(SampleSettings::default()),
+            vec![V2::new(3.0, 4.0), V2::new(3.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn import_errors() {
+        assert!(Layer::new_from_hpgl_str("PU0,0;PD1,x;", HPGL_UNITS_PER_CM).is_err());
+        assert!(Layer::new_from_hpgl_str("PU0,0;PD1;", HPGL_UNITS_PER_CM).is_err());
+        assert!(Layer::new_from_hpgl_str("PU0,0;5;", HPGL_UNITS_PER_CM).is_err());
+        assert!(
+            Layer::new_from_hpgl_str("IN;LBtext;with;PD\u{3};VS5;", HPGL_UNITS_PER_CM)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let l = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.5, 0.5), V2::new(4.0, 0.5)]),
+            Circle::new_shape(V2::new(3.0, 3.0), 1.0),
+        ])
+        .with_pen_id(1);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.hpgl");
+        l.write_hpgl(&path, &HpglSettings::new(), SampleSettings::default())
+            .unwrap();
+
+        let imported = Layer::new_from_hpgl(&path, HPGL_UNITS_PER_CM).unwrap();
+        let pen = imported.get_path("pen 2").unwrap();
+        assert_eq!(pen.len(), 2);
+        assert!((pen.shapes[0].length() - 3.5).abs() < 0.001);
+        assert!((pen.shapes[1].length() - l.shapes[1].length()).abs() < 0.05);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{BoundingBox, Layer, Rect, Rotate90, Shape, Translate, LARGE_EPSILON, V2};
+
+use super::{CutGuideEdge, Frame};
+
+/// Settings for [`Frame::impose`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImpositionSettings {
+    /// Space between pieces. Pieces touch the frame's inner rect without a gutter.
+    pub gutter: f32,
+    /// Whether pieces may be rotated by 90° to fit more of them on the sheet.
+    pub allow_rotation: bool,
+    /// Whether to draw cut guides centered in the gutter around each piece.
+    pub cut_guides: bool,
+    /// Distance the cut guides keep from the corners of each piece, see [`Frame::cut_guide`].
+    pub cut_guide_start_from_edge: f32,
+}
+
+impl ImpositionSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_gutter(mut self, gutter: f32) -> Self {
+        self.gutter = gutter;
+        self
+    }
+    pub fn with_allow_rotation(mut self, allow_rotation: bool) -> Self {
+        self.allow_rotation = allow_rotation;
+        self
+    }
+    pub fn with_cut_guides(mut self, cut_guides: bool) -> Self {
+        self.cut_guides = cut_guides;
+        self
+    }
+    pub fn with_cut_guide_start_from_edge(mut self, cut_guide_start_from_edge: f32) -> Self {
+        self.cut_guide_start_from_edge = cut_guide_start_from_edge;
+        self
+    }
+}
+
+impl Default for ImpositionSettings {
+    fn default() -> Self {
+        Self {
+            gutter: 0.5,
+            allow_rotation: true,
+            cut_guides: true,
+            cut_guide_start_from_edge: 0.0,
+        }
+    }
+}
+
+/// Where [`Frame::impose`] put a piece on the sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// Index of the piece in the list passed to [`Frame::impose`].
+    pub index: usize,
+    /// The bounds of the piece in its own coordinates.
+    pub source: Rect,
+    /// The bounds of the piece on the sheet.
+    pub area: Rect,
+    /// Whether the piece was rotated by 90° counterclockwise.
+    pub rotated: bool,
+}
+
+impl Placement {
+    /// Maps a point from the piece's own coordinates to sheet coordinates.
+    pub fn map_point(&self, point: V2) -> V2 {
+        if self.rotated {
+            let source_bl = V2::new(-self.source.tr().y, self.source.bl().x);
+            point.rotate_90() - source_bl + self.area.bl()
+        } else {
+            point - self.source.bl() + self.area.bl()
+        }
+    }
+
+    /// Moves and rotates a layer from the piece's own coordinates onto the sheet.
+    pub fn place(&self, layer: &Layer) -> Layer {
+        if self.rotated {
+            let source_bl = V2::new(-self.source.tr().y, self.source.bl().x);
+            layer.map_recursive(|shape| shape.rotate_90().translate(self.area.bl() - source_bl))
+        } else {
+            layer.translate(self.area.bl() - self.source.bl())
+        }
+    }
+}
+
+/// Pieces packed onto one sheet by [`Frame::impose`].
+#[derive(Debug, Clone)]
+pub struct Imposition {
+    /// All placed pieces as sublayers, followed by a sublayer named `cut guides` if enabled.
+    pub layer: Layer,
+    /// One placement per placed piece, in the order of the pieces.
+    pub placements: Vec<Placement>,
+    /// Indices of the pieces that did not fit on the sheet.
+    pub unplaced: Vec<usize>,
+}
+
+impl Imposition {
+    /// Returns the placement of the piece at `index`, or `None` if it did not fit on the sheet.
+    pub fn placement(&self, index: usize) -> Option<&Placement> {
+        self.placements
+            .iter()
+            .find(|placement| placement.index == index)
+    }
+}
+
+impl Frame {
+    /// Packs layers into the inner rect of the frame, using the bounding box of each layer as its size.
+    ///
+    /// see [`Frame::impose_with_bounds`].
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let postcard = Layer::new_from(vec![Rect::new_shape(V2::zero(), V2::a6())]);
+    /// let pieces = vec![postcard.clone(), postcard.clone(), postcard];
+    /// let sheet = Frame::new_xy(V2::new(29.7, 21.0), 1.0);
+    /// let imposition = sheet.impose(&pieces, &ImpositionSettings::new().with_gutter(0.0));
+    /// assert_eq!(imposition.placements.len(), 2);
+    /// assert_eq!(imposition.unplaced, vec![2]);
+    /// ```
+    pub fn impose(&self, pieces: &[Layer], settings: &ImpositionSettings) -> Imposition {
+        let pieces: Vec<(&Layer, Rect)> = pieces
+            .iter()
+            .map(|piece| {
+                let bounds = piece
+                    .bounding_box()
+                    .unwrap_or_else(|| Rect::new(V2::zero(), V2::zero()));
+                (piece, bounds)
+            })
+            .collect();
+        self.impose_with_bounds(&pieces, settings)
+    }
+
+    /// Packs layers into the inner rect of the frame, each with the given bounds, for example the size of a postcard.
+    ///
+    /// Pieces are packed largest first with the maximal rectangles algorithm, so differently sized pieces fill the sheet
+    /// closely. Pieces are moved and, if allowed, rotated without changing their props. Pieces that don't fit are listed in
+    /// [`Imposition::unplaced`] and can be imposed onto the next sheet.
+    pub fn impose_with_bounds(
+        &self,
+        pieces: &[(&Layer, Rect)],
+        settings: &ImpositionSettings,
+    ) -> Imposition {
+        let inner = self.inner_rect();
+        let gutter = settings.gutter.max(0.0);
+        // every piece reserves a gutter to its top right, the bin is extended to keep the outer pieces at the margin
+        let mut free = vec![Rect::new(inner.bl(), inner.tr() + V2::xy(gutter))];
+
+        let mut order: Vec<usize> = (0..pieces.len()).collect();
+        order.sort_by(|a, b| {
+            let size = |index: usize| pieces[index].1.size();
+            let (a, b) = (size(*a), size(*b));
+            b.max_axis()
+                .total_cmp(&a.max_axis())
+                .then(b.min_axis().total_cmp(&a.min_axis()))
+        });
+
+        let mut placements = Vec::new();
+        let mut unplaced = Vec::new();
+        for index in order {
+            let size = pieces[index].1.size();
+            if size.x <= 0.0 && size.y <= 0.0 {
+                unplaced.push(index);
+                continue;
+            }
+            let orientations: &[bool] = if settings.allow_rotation && size.x != size.y {
+                &[false, true]
+            } else {
+                &[false]
+            };
+
+            // best short side fit, ties broken by the position
+            let mut best_score = (f32::MAX, f32::MAX, f32::MAX, f32::MAX);
+            let mut best: Option<(V2, bool)> = None;
+            for free_rect in free.iter() {
+                for &rotated in orientations {
+                    let needed = if rotated {
+                        V2::new(size.y, size.x)
+                    } else {
+                        size
+                    } + V2::xy(gutter);
+                    let left_over = free_rect.size() - needed;
+                    if left_over.x < -LARGE_EPSILON || left_over.y < -LARGE_EPSILON {
+                        continue;
+                    }
+                    let score = (
+                        left_over.min_axis(),
+                        left_over.max_axis(),
+                        free_rect.bl().y,
+                        free_rect.bl().x,
+                    );
+                    if best.is_none() || score < best_score {
+                        best_score = score;
+                        best = Some((free_rect.bl(), rotated));
+                    }
+                }
+            }
+            let Some((bl, rotated)) = best else {
+                unplaced.push(index);
+                continue;
+            };
+
+            let placed_size = if rotated {
+                V2::new(size.y, size.x)
+            } else {
+                size
+            };
+            split_free_rects(&mut free, Rect::new(bl, bl + placed_size + V2::xy(gutter)));
+            placements.push(Placement {
+                index,
+                source: pieces[index].1,
+                area: Rect::new(bl, bl + placed_size),
+                rotated,
+            });
+        }
+        placements.sort_by_key(|placement| placement.index);
+        unplaced.sort();
+
+        let mut layer = Layer::new_from_shapes_and_layers(
+            vec![],
+            placements
+                .iter()
+                .map(|placement| placement.place(pieces[placement.index].0))
+                .collect(),
+        );
+        if settings.cut_guides && !placements.is_empty() {
+            let mut guides: Vec<Shape> = Vec::new();
+            let sheet = self.outer_rect();
+            for placement in placements.iter() {
+                let around = Rect::new(
+                    (placement.area.bl() - V2::xy(gutter / 2.0)).max(sheet.bl()),
+                    (placement.area.tr() + V2::xy(gutter / 2.0)).min(sheet.tr()),
+                );
+                let piece_guides = Frame::new_from_rect(around, V2::zero())
+                    .cut_guide(CutGuideEdge::All, settings.cut_guide_start_from_edge);
+                for guide in piece_guides.shapes {
+                    // neighbouring pieces share their guides
+                    if !guides.iter().any(|existing| same_line(existing, &guide)) {
+                        guides.push(guide);
+                    }
+                }
+            }
+            layer.push_layer(Layer::new_from(guides).with_name("cut guides"));
+        }
+
+        Imposition {
+            layer,
+            placements,
+            unplaced,
+        }
+    }
+}
+
+/// Removes `used` from the maximal free rectangles, keeping the remaining space as overlapping maximal rectangles.
+fn split_free_rects(free: &mut Vec<Rect>, used: Rect) {
+    let mut remaining = Vec::with_capacity(free.len() + 4);
+    for free_rect in free.drain(..) {
+        let overlaps = used.bl().x < free_rect.tr().x - LARGE_EPSILON
+            && used.tr().x > free_rect.bl().x + LARGE_EPSILON
+            && used.bl().y < free_rect.tr().y - LARGE_EPSILON
+            && used.tr().y > free_rect.bl().y + LARGE_EPSILON;
+        if !overlaps {
+            remaining.push(free_rect);
+            continue;
+        }
+        let (bl, tr) = (free_rect.bl(), free_rect.tr());
+        if used.bl().x > bl.x + LARGE_EPSILON {
+            remaining.push(Rect::new(bl, V2::new(used.bl().x, tr.y)));
+        }
+        if used.tr().x < tr.x - LARGE_EPSILON {
+            remaining.push(Rect::new(V2::new(used.tr().x, bl.y), tr));
+        }
+        if used.bl().y > bl.y + LARGE_EPSILON {
+            remaining.push(Rect::new(bl, V2::new(tr.x, used.bl().y)));
+        }
+        if used.tr().y < tr.y - LARGE_EPSILON {
+            remaining.push(Rect::new(V2::new(bl.x, used.tr().y), tr));
+        }
+    }
+
+    // drop rectangles contained in others
+    let contains = |outer: &Rect, inner: &Rect| {
+        outer.bl().x <= inner.bl().x + LARGE_EPSILON
+            && outer.bl().y <= inner.bl().y + LARGE_EPSILON
+            && outer.tr().x >= inner.tr().x - LARGE_EPSILON
+            && outer.tr().y >= inner.tr().y - LARGE_EPSILON
+    };
+    for (i, rect) in remaining.iter().enumerate() {
+        let redundant = remaining
+            .iter()
+            .enumerate()
+            .any(|(j, other)| i != j && contains(other, rect) && (!contains(rect, other) || j < i));
+        if !redundant {
+            free.push(*rect);
+        }
+    }
+}
+
+fn same_line(a: &Shape, b: &Shape) -> bool {
+    match (a, b) {
+        (Shape::Path(a), Shape::Path(b)) => {
+            let (a, b) = (a.get_points_ref(), b.get_points_ref());
+            let equal = |x: &[V2], y: &[V2]| {
+                x.len() == y.len()
+                    && x.iter()
+                        .zip(y.iter())
+                        .all(|(x, y)| x.dist(*y) < LARGE_EPSILON)
+            };
+            let reversed: Vec<V2> = b.iter().rev().copied().collect();
+            equal(a, b) || equal(a, &reversed)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod test_imposition {
+    use crate::{BoundingBox, Circle, ColorRgb, Frame, ImpositionSettings, Layer, Rect, V2};
+
+    fn piece(size: V2) -> Layer {
+        Layer::new_from(vec![
+            Rect::new_shape(V2::zero(), size),
+            Circle::new_shape(V2::new(1.0, 1.0), 0.5),
+        ])
+    }
+
+    fn assert_no_overlap(areas: &[Rect], gutter: f32) {
+        for (i, a) in areas.iter().enumerate() {
+            for b in areas.iter().skip(i + 1) {
+                let apart = a.tr().x + gutter <= b.bl().x + 1e-4
+                    || b.tr().x + gutter <= a.bl().x + 1e-4
+                    || a.tr().y + gutter <= b.bl().y + 1e-4
+                    || b.tr().y + gutter <= a.bl().y + 1e-4;
+                assert!(apart, "{:?} and {:?} overlap", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn packs_mixed_sizes() {
+        let sizes = [
+            V2::new(10.0, 6.0),
+            V2::new(4.0, 4.0),
+            V2::new(6.0, 10.0),
+            V2::new(3.0, 8.0),
+            V2::new(4.0, 4.0),
+            V2::new(2.0, 2.0),
+        ];
+        let pieces: Vec<Layer> = sizes.iter().map(|size| piece(*size)).collect();
+        let sheet = Frame::new_xy(V2::new(24.0, 14.0), 1.0);
+        let settings = ImpositionSettings::new().with_gutter(0.5);
+        let imposition = sheet.impose(&pieces, &settings);
+
+        assert!(imposition.unplaced.is_empty());
+        assert_eq!(imposition.placements.len(), sizes.len());
+        let areas: Vec<Rect> = imposition.placements.iter().map(|p| p.area).collect();
+        assert_no_overlap(&areas, 0.5);
+        let inner = sheet.inner_rect();
+        for (placement, size) in imposition.placements.iter().zip(sizes.iter()) {
+            assert!(placement.area.bl().x >= inner.bl().x - 1e-4);
+            assert!(placement.area.bl().y >= inner.bl().y - 1e-4);
+            assert!(placement.area.tr().x <= inner.tr().x + 1e-4);
+            assert!(placement.area.tr().y <= inner.tr().y + 1e-4);
+            let placed_size = placement.area.size();
+            if placement.rotated {
+                assert_eq!(placed_size, V2::new(size.y, size.x));
+            } else {
+                assert_eq!(placed_size, *size);
+            }
+        }
+    }
+
+    #[test]
+    fn places_layers() {
+        let pieces = vec![
+            piece(V2::new(8.0, 5.0)).with_color(ColorRgb::red()),
+            piece(V2::new(8.0, 5.0)).with_name("second"),
+        ];
+        let sheet = Frame::new_xy(V2::new(13.0, 8.0), 0.0);
+        let settings = ImpositionSettings::new()
+            .with_gutter(0.0)
+            .with_cut_guides(false);
+        let imposition = sheet.impose(&pieces, &settings);
+        assert_eq!(imposition.layer.sublayers.len(), 2);
+
+        for placement in imposition.placements.iter() {
+            let placed = &imposition.layer.sublayers[placement.index];
+            let bounding_box = placed.bounding_box().unwrap();
+            assert!(bounding_box.bl().dist(placement.area.bl()) < 1e-4);
+            assert!(bounding_box.tr().dist(placement.area.tr()) < 1e-4);
+            assert_eq!(placed.props, pieces[placement.index].props);
+            assert_eq!(
+                placed.props_inheritable,
+                pieces[placement.index].props_inheritable
+            );
+            // the circle moves with the piece
+            let circle_center = placement.map_point(V2::new(1.0, 1.0));
+            assert!(placed.iter().any(|shape| match shape {
+                crate::Shape::Circle(circle) => circle.center.dist(circle_center) < 1e-4,
+                _ => false,
+            }));
+        }
+        // only fits when one piece is rotated
+        assert_eq!(imposition.placements.len(), 2);
+        assert!(imposition
+            .placements
+            .iter()
+            .any(|placement| placement.rotated));
+
+        let unrotated = sheet.impose(&pieces, &settings.with_allow_rotation(false));
+        assert_eq!(unrotated.placements.len(), 1);
+        assert_eq!(unrotated.unplaced.len(), 1);
+        assert!(unrotated.placement(unrotated.unplaced[0]).is_none());
+    }
+
+    #[test]
+    fn explicit_bounds() {
+        let drawing = Layer::new_from(vec![Circle::new_shape(V2::new(5.0, 7.0), 1.0)]);
+        let bounds = Rect::new(V2::zero(), V2::new(10.0, 14.0));
+        let sheet = Frame::new_at_xy(V2::new(100.0, 0.0), V2::new(10.0, 14.0), 0.0);
+        let imposition = sheet.impose_with_bounds(
+            &[(&drawing, bounds)],
+            &ImpositionSettings::new().with_cut_guides(false),
+        );
+        let placement = imposition.placement(0).unwrap();
+        assert_eq!(placement.area, sheet.inner_rect());
+        assert!(!placement.rotated);
+        assert_eq!(
+            imposition.layer.bounding_box().unwrap().center(),
+            V2::new(105.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn cut_guides() {
+        let pieces = vec![piece(V2::new(4.0, 4.0)), piece(V2::new(4.0, 4.0))];
+        let sheet = Frame::new_xy(V2::new(12.0, 6.0), 1.0);
+        let imposition = sheet.impose(&pieces, &ImpositionSettings::new().with_gutter(1.0));
+        let guides = imposition.layer.sublayers.last().unwrap();
+        assert_eq!(guides.props.name.as_deref(), Some("cut guides"));
+        // the guide between both pieces is shared
+        assert_eq!(guides.len(), 7);
+
+        let sheet = Frame::new_xy(V2::new(12.0, 6.0), 1.0);
+        let imposition = sheet.impose(
+            &pieces,
+            &ImpositionSettings::new()
+                .with_gutter(1.0)
+                .with_cut_guides(false),
+        );
+        assert_eq!(imposition.layer.sublayers.len(), 2);
+    }
+}
@@ -0,0 +1,311 @@
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use anyhow::Result;
+
+use crate::{BoundingBox, Layer, Line, Plottable, Rect, SampleSettings, Shape, V2};
+
+use super::ColorRgb;
+
+/// Settings for [`Layer::render_ink_preview`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InkPreviewSettings {
+    pub dpi: f32,
+    /// Paper color the ink is printed on. `None` renders the ink on a transparent background.
+    pub paper_color: Option<ColorRgb>,
+    /// Fraction of light a single stroke absorbs where its color differs from the paper. Overlapping strokes multiply.
+    pub ink_opacity: f32,
+    /// The rendered area in layer coordinates (cm). Defaults to the area from the origin to the top right corner of the bounding box.
+    pub area: Option<Rect>,
+}
+
+impl InkPreviewSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_dpi(mut self, dpi: f32) -> Self {
+        self.dpi = dpi;
+        self
+    }
+    pub fn with_paper_color(mut self, paper_color: Option<ColorRgb>) -> Self {
+        self.paper_color = paper_color;
+        self
+    }
+    pub fn with_ink_opacity(mut self, ink_opacity: f32) -> Self {
+        self.ink_opacity = ink_opacity;
+        self
+    }
+    pub fn with_area(mut self, area: Rect) -> Self {
+        self.area = Some(area);
+        self
+    }
+}
+
+impl Default for InkPreviewSettings {
+    fn default() -> Self {
+        Self {
+            dpi: 150.0,
+            paper_color: Some(ColorRgb::white()),
+            ink_opacity: 0.9,
+            area: None,
+        }
+    }
+}
+
+/// A raster preview of a [`Layer`] as plotted with ink, created by [`Layer::render_ink_preview`].
+///
+/// Pixels are stored row by row, starting at the top left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InkPreview {
+    pub width: usize,
+    pub height: usize,
+    pub dpi: f32,
+    pub paper_color: Option<ColorRgb>,
+    /// Fraction of light passing through all ink per pixel and color channel, `1.0` where there is no ink.
+    pub transmittance: Vec<[f32; 3]>,
+    /// Number of strokes covering each pixel, counting every pass. Partially covered pixels count fractionally.
+    pub density: Vec<f32>,
+}
+
+impl InkPreview {
+    /// Returns the pixel index of `x` and `y`, or `None` outside the image.
+    pub fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    pub fn max_density(&self) -> f32 {
+        self.density.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Returns the color of a pixel with its alpha, ink multiplied onto the paper color.
+    pub fn color_at(&self, index: usize) -> (ColorRgb, f32) {
+        let [r, g, b] = self.transmittance[index];
+        match self.paper_color {
+            Some(paper) => (ColorRgb::new(paper.r * r, paper.g * g, paper.b * b), 1.0),
+            None => {
+                // the color that results in the transmittance when composited onto white
+                let alpha = 1.0 - r.min(g).min(b);
+                if alpha <= 0.0 {
+                    return (ColorRgb::white(), 0.0);
+                }
+                let unblend = |t: f32| (t - (1.0 - alpha)) / alpha;
+                (ColorRgb::new(unblend(r), unblend(g), unblend(b)), alpha)
+            }
+        }
+    }
+
+    /// Returns the preview as 8 bit RGBA pixels.
+    pub fn rgba(&self) -> Vec<u8> {
+        (0..self.transmittance.len())
+            .flat_map(|index| {
+                let (color, alpha) = self.color_at(index);
+                [color.r, color.g, color.b, alpha].map(to_u8)
+            })
+            .collect()
+    }
+
+    /// Returns the density as 8 bit RGBA pixels, from transparent without ink over blue, green and yellow to red at `max_density`.
+    ///
+    /// `max_density` defaults to [`InkPreview::max_density`]. Pixels covered by more strokes than `max_density` are red.
+    pub fn density_heatmap_rgba(&self, max_density: Option<f32>) -> Vec<u8> {
+        let max_density = max_density.unwrap_or_else(|| self.max_density()).max(1.0);
+        let stops = [
+            ColorRgb::blue(),
+            ColorRgb::green(),
+            ColorRgb::yellow(),
+            ColorRgb::red(),
+        ];
+        self.density
+            .iter()
+            .flat_map(|density| {
+                if *density <= 0.0 {
+                    return [0, 0, 0, 0];
+                }
+                let t = (density / max_density).min(1.0) * (stops.len() - 1) as f32;
+                let i = (t.floor() as usize).min(stops.len() - 2);
+                let f = t - i as f32;
+                let (a, b) = (stops[i], stops[i + 1]);
+                let mix = |a: f32, b: f32| a + (b - a) * f;
+                [
+                    mix(a.r, b.r),
+                    mix(a.g, b.g),
+                    mix(a.b, b.b),
+                    density.min(1.0),
+                ]
+                .map(to_u8)
+            })
+            .collect()
+    }
+
+    /// Writes the preview to a .png file, storing the dpi so the image keeps its physical size.
+    pub fn write_png(&self, path: &PathBuf) -> Result<()> {
+        self.write_rgba_png(path, &self.rgba())
+    }
+
+    /// Writes the density heatmap to a .png file, see [`InkPreview::density_heatmap_rgba`].
+    pub fn write_density_heatmap_png(
+        &self,
+        path: &PathBuf,
+        max_density: Option<f32>,
+    ) -> Result<()> {
+        self.write_rgba_png(path, &self.density_heatmap_rgba(max_density))
+    }
+
+    fn write_rgba_png(&self, path: &PathBuf, rgba: &[u8]) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let pixels_per_meter = (self.dpi / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: pixels_per_meter,
+            yppu: pixels_per_meter,
+            unit: png::Unit::Meter,
+        }));
+        encoder.write_header()?.write_image_data(rgba)?;
+        Ok(())
+    }
+}
+
+impl Layer {
+    /// Renders the layer as plotted with ink at the resolution of [`InkPreviewSettings::dpi`], interpreting all coordinates as cm.
+    ///
+    /// Strokes are drawn with their layer's color and pen width, including round caps and joins. Overlapping strokes
+    /// and passes blend multiplicatively like layered ink, and are counted in [`InkPreview::density`] to find overdraw.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Path::new_shape_from(vec![V2::new(0.0, 1.0), V2::new(2.54, 1.0)])]);
+    /// let settings = InkPreviewSettings::new().with_dpi(100.0);
+    /// let preview = layer.render_ink_preview(&settings, SampleSettings::default());
+    /// assert_eq!(preview.width, 100);
+    /// ```
+    pub fn render_ink_preview(
+        &self,
+        settings: &InkPreviewSettings,
+        sample_settings: SampleSettings,
+    ) -> InkPreview {
+        let area = settings.area.unwrap_or_else(|| match self.bounding_box() {
+            Some(bounding_box) => Rect::new(
+                bounding_box.bl().min(V2::zero()),
+                bounding_box.tr().max(V2::zero()),
+            ),
+            None => Rect::new(V2::zero(), V2::zero()),
+        });
+        let pixels_per_cm = settings.dpi / 2.54;
+        let width = (area.width() * pixels_per_cm).round().max(0.0) as usize;
+        let height = (area.height() * pixels_per_cm).round().max(0.0) as usize;
+
+        let mut preview = InkPreview {
+            width,
+            height,
+            dpi: settings.dpi,
+            paper_color: settings.paper_color,
+            transmittance: vec![[1.0; 3]; width * height],
+            density: vec![0.0; width * height],
+        };
+
+        // coverage of the current stroke, so a stroke overlapping itself at joins only counts once
+        let mut coverage = vec![0.0_f32; width * height];
+        let mut touched: Vec<usize> = Vec::new();
+
+        let to_pixels = |point: V2| {
+            V2::new(
+                (point.x - area.bl().x) * pixels_per_cm,
+                (area.tr().y - point.y) * pixels_per_cm,
+            )
+        };
+
+        let mut pos = V2::zero();
+        for (layer, props) in self.layers_in_plot_order() {
+            if layer.shapes.is_empty() {
+                continue;
+            }
+            let color = props.color.unwrap();
+            let radius = props.pen_width_cm.unwrap() * pixels_per_cm / 2.0;
+            // strokes thinner than a pixel are widened and made lighter
+            let thin_factor = (radius * 2.0).min(1.0);
+            let radius = radius.max(0.5);
+            let absorbed = [color.r, color.g, color.b]
+                .map(|channel| (1.0 - channel.clamp(0.0, 1.0)) * settings.ink_opacity);
+
+            let mut draw_stroke = |stroke: &Shape| {
+                let stroke_points = stroke.get_points_from(pos, sample_settings);
+                let Some(last) = stroke_points.last() else {
+                    return;
+                };
+                pos = *last;
+                let points: Vec<V2> = stroke_points.into_iter().map(to_pixels).collect();
+                let last = to_pixels(pos);
+                let segments: Vec<(V2, V2)> = if points.len() == 1 {
+                    vec![(last, last)]
+                } else {
+                    points.windows(2).map(|w| (w[0], w[1])).collect()
+                };
+
+                for (from, to) in segments {
+                    let segment = Line::new(from, to);
+                    let min = from.min(to) - V2::xy(radius + 1.0);
+                    let max = from.max(to) + V2::xy(radius + 1.0);
+                    let x_range = min.x.floor().max(0.0) as usize
+                        ..(max.x.ceil().max(0.0) as usize).min(width);
+                    let y_range = min.y.floor().max(0.0) as usize
+                        ..(max.y.ceil().max(0.0) as usize).min(height);
+                    for y in y_range {
+                        for x in x_range.clone() {
+                            let center = V2::new(x as f32 + 0.5, y as f32 + 0.5);
+                            let distance = center.dist(segment.closest_point(center));
+                            let pixel_coverage =
+                                (radius + 0.5 - distance).clamp(0.0, 1.0) * thin_factor;
+                            if pixel_coverage <= 0.0 {
+                                continue;
+                            }
+                            let index = y * width + x;
+                            if coverage[index] == 0.0 {
+                                touched.push(index);
+                            }
+                            coverage[index] = coverage[index].max(pixel_coverage);
+                        }
+                    }
+                }
+
+                for index in touched.drain(..) {
+                    let pixel_coverage = coverage[index];
+                    let transmittance = &mut preview.transmittance[index];
+                    for (channel, absorbed) in transmittance.iter_mut().zip(absorbed.iter()) {
+                        *channel *= 1.0 - absorbed * pixel_coverage;
+                    }
+                    preview.density[index] += pixel_coverage;
+                    coverage[index] = 0.0;
+                }
+            };
+            for _ in 0..props.plot_passes() {
+                for shape in layer.iter() {
+                    match shape {
+                        Shape::Compound(compound) => {
+                            compound.rings().for_each(|ring| draw_stroke(&ring.into()))
+                        }
+                        _ => draw_stroke(shape),
+                    }
+                }
+            }
+        }
+
+        preview
+    }
+
+    /// Renders the layer with [`Layer::render_ink_preview`] and writes it to a .png file.
+    pub fn write_ink_preview_png(
+        &self,
+        path: &PathBuf,
+        settings: &InkPreviewSettings,
+        sample_settings: SampleSettings,
+    ) -> Result<()> {
+        self.render_ink_preview(settings, sample_settings)
+            .write_png(path)
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
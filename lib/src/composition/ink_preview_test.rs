@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod test_ink_preview {
+    use crate::{
+        ColorRgb, InkPreview, InkPreviewSettings, Layer, Path, Rect, SampleSettings, Shape, V2,
+    };
+
+    // 100 dpi over 2.54 cm, so pixel centers are at multiples of 0.0254 cm plus half a pixel
+    fn settings() -> InkPreviewSettings {
+        InkPreviewSettings::new()
+            .with_dpi(100.0)
+            .with_area(Rect::new(V2::zero(), V2::new(2.54, 2.54)))
+    }
+
+    fn pixel(preview: &InkPreview, x: usize, y: usize) -> usize {
+        preview.index(x, y).unwrap()
+    }
+
+    fn horizontal(y: f32) -> Shape {
+        Path::new_shape_from(vec![V2::new(0.508, y), V2::new(2.032, y)])
+    }
+
+    #[test]
+    fn pen_width_and_round_caps() {
+        let layer = Layer::new_from(vec![horizontal(1.27)]).with_pen_width_cm(0.254);
+        let preview = layer.render_ink_preview(&settings(), SampleSettings::default());
+        assert_eq!((preview.width, preview.height), (100, 100));
+
+        // 10 px wide line along rows 45 to 54
+        let center = preview.transmittance[pixel(&preview, 50, 50)];
+        assert!((center[0] - 0.1).abs() < 0.001);
+        assert_eq!(preview.transmittance[pixel(&preview, 50, 44)], [1.0; 3]);
+        assert!(preview.transmittance[pixel(&preview, 50, 54)][0] < 0.2);
+        // round cap beyond the end at x = 80, but not at the corner of a square cap
+        assert!(preview.transmittance[pixel(&preview, 83, 50)][0] < 0.2);
+        assert_eq!(preview.transmittance[pixel(&preview, 84, 45)], [1.0; 3]);
+    }
+
+    #[test]
+    fn multiply_and_density() {
+        let vertical = Path::new_shape_from(vec![V2::new(1.27, 0.508), V2::new(1.27, 2.032)]);
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![
+                Layer::new_from(vec![horizontal(1.27)]).with_color(ColorRgb::red()),
+                Layer::new_from(vec![vertical])
+                    .with_color(ColorRgb::blue())
+                    .with_passes(2),
+            ],
+        )
+        .with_pen_width_cm(0.1);
+        let preview = layer.render_ink_preview(&settings(), SampleSettings::default());
+
+        let red = preview.transmittance[pixel(&preview, 30, 50)];
+        assert!((red[0] - 1.0).abs() < 0.001 && (red[1] - 0.1).abs() < 0.001);
+        let crossing = pixel(&preview, 50, 50);
+        let [r, g, b] = preview.transmittance[crossing];
+        assert!((r - 0.01).abs() < 0.001 && (g - 0.001).abs() < 0.001 && (b - 0.1).abs() < 0.001);
+        assert!((preview.density[crossing] - 3.0).abs() < 0.001);
+        assert!((preview.max_density() - 3.0).abs() < 0.001);
+
+        let heatmap = preview.density_heatmap_rgba(None);
+        assert_eq!(heatmap[crossing * 4..crossing * 4 + 4], [255, 0, 0, 255]);
+        assert_eq!(heatmap[0..4], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn joins_count_once() {
+        let zigzag = Path::new_shape_from(vec![
+            V2::new(0.508, 0.508),
+            V2::new(1.27, 1.27),
+            V2::new(2.032, 0.508),
+        ]);
+        let layer = Layer::new_from(vec![zigzag]).with_pen_width_cm(0.2);
+        let preview = layer.render_ink_preview(&settings(), SampleSettings::default());
+        assert!(preview.max_density() <= 1.0);
+        assert!((preview.max_density() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn paper_colors() {
+        let layer = Layer::new_from(vec![horizontal(1.27)]).with_pen_width_cm(0.254);
+        let on_paper = layer.render_ink_preview(
+            &settings().with_paper_color(Some(ColorRgb::yellow())),
+            SampleSettings::default(),
+        );
+        let center = pixel(&on_paper, 50, 50);
+        let (color, alpha) = on_paper.color_at(center);
+        assert_eq!(alpha, 1.0);
+        assert!((color.r - 0.1).abs() < 0.001 && color.b.abs() < 0.001);
+
+        let transparent = layer.render_ink_preview(
+            &settings().with_paper_color(None),
+            SampleSettings::default(),
+        );
+        let (color, alpha) = transparent.color_at(center);
+        assert!((alpha - 0.9).abs() < 0.001);
+        assert!(color.brightness() < 0.001);
+        assert_eq!(transparent.color_at(0).1, 0.0);
+        assert_eq!(transparent.rgba()[center * 4 + 3], 230);
+    }
+
+    #[test]
+    fn write_png() {
+        let layer = Layer::new_from(vec![horizontal(1.27)]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("preview.png");
+        layer
+            .write_ink_preview_png(&path, &settings(), SampleSettings::default())
+            .unwrap();
+        let heatmap_path = temp_dir.path().join("heatmap.png");
+        layer
+            .render_ink_preview(&settings(), SampleSettings::default())
+            .write_density_heatmap_png(&heatmap_path, Some(4.0))
+            .unwrap();
+        for path in [path, heatmap_path] {
+            assert!(std::fs::read(path).unwrap().starts_with(b"\x89PNG"));
+        }
+    }
+}
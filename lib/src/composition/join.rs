@@ -0,0 +1,314 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Angle, Layer, Path, Plottable, Shape, V2};
+
+use super::path_end::PathEnd;
+
+/// Settings for [`Layer::join_paths_recursive`] and [`Layer::join_paths_flat`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JoinSettings {
+    /// Maximum distance between two endpoints that will still be joined.
+    pub tolerance: f32,
+    /// Whether paths may be reversed to be joined.
+    pub allow_reverse: bool,
+    /// Maximum change of direction at a joint. `None` to ignore angles.
+    pub max_angle_delta: Option<Angle>,
+    /// Whether joined endpoints are moved to their midpoint. Otherwise the gap is bridged with a short line.
+    pub snap: bool,
+}
+
+impl JoinSettings {
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance,
+            ..Default::default()
+        }
+    }
+    pub fn with_allow_reverse(mut self, allow_reverse: bool) -> Self {
+        self.allow_reverse = allow_reverse;
+        self
+    }
+    pub fn with_max_angle_delta(mut self, max_angle_delta: Option<Angle>) -> Self {
+        self.max_angle_delta = max_angle_delta;
+        self
+    }
+    pub fn with_snap(mut self, snap: bool) -> Self {
+        self.snap = snap;
+        self
+    }
+}
+
+impl Default for JoinSettings {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.001,
+            allow_reverse: true,
+            max_angle_delta: None,
+            snap: true,
+        }
+    }
+}
+
+impl Layer {
+    /// Returns a new `Layer` with [`Path`]s whose endpoints lie within `settings.tolerance` of each other joined into a single [`Path`],
+    /// recursively for all sublayers individually.
+    ///
+    /// Unlike [`Layer::combine_shapes_recursive`], endpoints don't need to match exactly. Paths whose own start and end lie within the tolerance are closed.
+    /// Closed [`Path`]s and all other [`Shape`]s are left as they are and keep their position, joined paths take the position of their first path.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![
+    ///     Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+    ///     Path::new_shape_from(vec![V2::new(2.0, 0.0), V2::new(1.0001, 0.0)]),
+    /// ]);
+    /// let joined = layer.join_paths_recursive(&JoinSettings::new(0.01));
+    /// assert_eq!(joined.len(), 1);
+    /// ```
+    pub fn join_paths_recursive(&self, settings: &JoinSettings) -> Self {
+        let sublayers = self
+            .sublayers
+            .iter()
+            .map(|sublayer| sublayer.join_paths_recursive(settings))
+            .collect();
+
+        Layer::new_from_shapes_and_layers(Self::join_paths(self.iter(), settings), sublayers)
+            .with_props_inheritable(self.props_inheritable.clone())
+            .with_props(self.props.clone())
+    }
+
+    /// Returns a new flattened `Layer` with [`Path`]s joined. see [`Layer::join_paths_recursive`].
+    pub fn join_paths_flat(&self, settings: &JoinSettings) -> Self {
+        Layer::new_from(Self::join_paths(self.iter_flattened(), settings))
+            .with_props_inheritable(self.props_inheritable.clone())
+            .with_props(self.props.clone())
+    }
+
+    fn join_paths<'a, I>(shapes: I, settings: &JoinSettings) -> Vec<Shape>
+    where
+        I: IntoIterator<Item = &'a Shape>,
+    {
+        // shapes that are not joined keep their position, joined paths take the position of their first path
+        let mut paths = Vec::new();
+        let mut path_positions = Vec::new();
+        let mut others = Vec::new();
+        for (position, shape) in shapes.into_iter().enumerate() {
+            match shape {
+                Shape::Path(path) if path.get_points_ref().len() > 1 && !path.is_closed() => {
+                    paths.push(path);
+                    path_positions.push(position);
+                }
+                _ => others.push((position, shape.clone())),
+            }
+        }
+
+        let starts: Vec<_> = paths
+            .iter()
+            .map(|path| PathEnd::from_path_start(path))
+            .collect();
+        let ends: Vec<_> = paths
+            .iter()
+            .map(|path| PathEnd::from_path_end(path))
+            .collect();
+
+        let mut index = EndpointIndex::new(settings.tolerance);
+        for i in 0..paths.len() {
+            index.insert(starts[i].point, i, false);
+            index.insert(ends[i].point, i, true);
+        }
+
+        let mut used = vec![false; paths.len()];
+        let mut joined = Vec::with_capacity(paths.len());
+        for i in 0..paths.len() {
+            if used[i] {
+                continue;
+            }
+            used[i] = true;
+
+            let mut points: VecDeque<V2> = paths[i].iter().copied().collect();
+            let mut chain_start = starts[i];
+            let mut chain_end = ends[i];
+
+            // extend forward
+            while let Some((j, is_end)) =
+                index.nearest(chain_end.point, settings.tolerance, |j, is_end| {
+                    !used[j]
+                        && (!is_end || settings.allow_reverse)
+                        && chain_end.is_compatible_within(
+                            &if is_end { ends[j].flipped() } else { starts[j] },
+                            settings.tolerance,
+                            &settings.max_angle_delta,
+                        )
+                })
+            {
+                used[j] = true;
+                let candidate: Vec<V2> = if is_end {
+                    paths[j].iter().rev().copied().collect()
+                } else {
+                    paths[j].iter().copied().collect()
+                };
+                Self::join_points_back(&mut points, &candidate, settings.snap);
+                chain_end = if is_end { starts[j].flipped() } else { ends[j] };
+            }
+
+            // extend backward
+            while let Some((j, is_end)) =
+                index.nearest(chain_start.point, settings.tolerance, |j, is_end| {
+                    !used[j]
+                        && (is_end || settings.allow_reverse)
+                        && chain_start.is_compatible_within(
+                            &if is_end { ends[j] } else { starts[j].flipped() },
+                            settings.tolerance,
+                            &settings.max_angle_delta,
+                        )
+                })
+            {
+                used[j] = true;
+                let candidate: Vec<V2> = if is_end {
+                    paths[j].iter().copied().collect()
+                } else {
+                    paths[j].iter().rev().copied().collect()
+                };
+                Self::join_points_front(&mut points, &candidate, settings.snap);
+                chain_start = if is_end { starts[j] } else { ends[j].flipped() };
+            }
+
+            let mut path = Path::new_from(points.into_iter().collect());
+            Self::close_gap(&mut path, settings);
+            joined.push((path_positions[i], Shape::Path(path)));
+        }
+
+        joined.extend(others);
+        joined.sort_by_key(|(position, _)| *position);
+        joined.into_iter().map(|(_, shape)| shape).collect()
+    }
+
+    fn join_points_back(points: &mut VecDeque<V2>, candidate: &[V2], snap: bool) {
+        if snap {
+            let last = points.back_mut().unwrap();
+            *last = last.lerp(candidate[0], 0.5);
+            points.extend(candidate.iter().skip(1));
+        } else if points.back() == candidate.first() {
+            points.extend(candidate.iter().skip(1));
+        } else {
+            points.extend(candidate.iter());
+        }
+    }
+
+    fn join_points_front(points: &mut VecDeque<V2>, candidate: &[V2], snap: bool) {
+        let candidate_last = *candidate.last().unwrap();
+        let skip_last = if snap {
+            let first = points.front_mut().unwrap();
+            *first = first.lerp(candidate_last, 0.5);
+            true
+        } else {
+            points.front() == Some(&candidate_last)
+        };
+
+        let count = if skip_last {
+            candidate.len() - 1
+        } else {
+            candidate.len()
+        };
+        for point in candidate[..count].iter().rev() {
+            points.push_front(*point);
+        }
+    }
+
+    /// Closes `path` if its start and end lie within the tolerance.
+    fn close_gap(path: &mut Path, settings: &JoinSettings) {
+        let points = path.get_points_ref();
+        let (start, end) = (points[0], *points.last().unwrap());
+        if points.len() < 3 || start == end || start.dist(end) > settings.tolerance {
+            return;
+        }
+        if path.length() <= settings.tolerance * 2.0 {
+            return;
+        }
+        if settings.max_angle_delta.is_some() {
+            let path_start = PathEnd::from_path_start(path);
+            let path_end = PathEnd::from_path_end(path);
+            if !path_end.is_compatible_within(
+                &path_start,
+                settings.tolerance,
+                &settings.max_angle_delta,
+            ) {
+                return;
+            }
+        }
+
+        if settings.snap {
+            let mut points = points.to_vec();
+            let mid = start.lerp(end, 0.5);
+            points[0] = mid;
+            *points.last_mut().unwrap() = mid;
+            *path = Path::new_from(points);
+        } else {
+            path.close();
+        }
+    }
+}
+
+struct Endpoint {
+    point: V2,
+    path_index: usize,
+    is_end: bool,
+}
+
+/// Spatial hash of path endpoints.
+struct EndpointIndex {
+    cell_size: f32,
+    cells: HashMap<(i64, i64), Vec<Endpoint>>,
+}
+
+impl EndpointIndex {
+    fn new(tolerance: f32) -> Self {
+        Self {
+            cell_size: tolerance.max(f32::EPSILON),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, point: V2) -> (i64, i64) {
+        (
+            (point.x / self.cell_size).floor() as i64,
+            (point.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, point: V2, path_index: usize, is_end: bool) {
+        let cell = self.cell(point);
+        self.cells.entry(cell).or_default().push(Endpoint {
+            point,
+            path_index,
+            is_end,
+        });
+    }
+
+    /// Returns `(path_index, is_end)` of the closest endpoint within `tolerance` of `point` that satisfies `accept`.
+    fn nearest<F>(&self, point: V2, tolerance: f32, accept: F) -> Option<(usize, bool)>
+    where
+        F: Fn(usize, bool) -> bool,
+    {
+        let (cell_x, cell_y) = self.cell(point);
+        let mut best: Option<((usize, bool), f32)> = None;
+        for x in (cell_x - 1)..=(cell_x + 1) {
+            for y in (cell_y - 1)..=(cell_y + 1) {
+                let Some(entries) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+                for endpoint in entries {
+                    let dist = endpoint.point.dist(point);
+                    if dist > tolerance || best.is_some_and(|(_, best_dist)| best_dist <= dist) {
+                        continue;
+                    }
+                    if accept(endpoint.path_index, endpoint.is_end) {
+                        best = Some(((endpoint.path_index, endpoint.is_end), dist));
+                    }
+                }
+            }
+        }
+        best.map(|(entry, _)| entry)
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod test_join {
+    use crate::{Angle, Circle, JoinSettings, Layer, Path, Plottable, SampleSettings, Shape, V2};
+
+    fn points(shape: &Shape) -> Vec<V2> {
+        shape.get_points(SampleSettings::default())
+    }
+
+    #[test]
+    fn join_within_tolerance() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(1.005, 0.0), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(5.0, 0.0), V2::new(6.0, 0.0)]),
+        ]);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.01));
+        assert_eq!(joined.len(), 2);
+        assert_eq!(
+            points(&joined.shapes[0]),
+            vec![V2::new(0.0, 0.0), V2::new(1.0025, 0.0), V2::new(2.0, 0.0)]
+        );
+
+        let not_joined = layer.join_paths_recursive(&JoinSettings::new(0.001));
+        assert_eq!(not_joined.len(), 3);
+    }
+
+    #[test]
+    fn join_without_snap_bridges_gap() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(1.005, 0.0), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+        ]);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.01).with_snap(false));
+        assert_eq!(joined.len(), 1);
+        assert_eq!(
+            points(&joined.shapes[0]),
+            vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+                V2::new(1.005, 0.0),
+                V2::new(2.0, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn join_reverse() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(2.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(-1.0, 0.0)]),
+        ]);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.01));
+        assert_eq!(joined.len(), 1);
+        assert_eq!(
+            points(&joined.shapes[0]),
+            vec![
+                V2::new(-1.0, 0.0),
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+                V2::new(2.0, 0.0)
+            ]
+        );
+
+        let no_reverse =
+            layer.join_paths_recursive(&JoinSettings::new(0.01).with_allow_reverse(false));
+        assert_eq!(no_reverse.len(), 3);
+    }
+
+    #[test]
+    fn join_angle_constraint() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(1.0, 1.0)]),
+        ]);
+
+        let straight_only =
+            JoinSettings::new(0.01).with_max_angle_delta(Some(Angle::from_degrees(10.0)));
+        assert_eq!(layer.join_paths_recursive(&straight_only).len(), 2);
+
+        let corners = JoinSettings::new(0.01).with_max_angle_delta(Some(Angle::from_degrees(95.0)));
+        assert_eq!(layer.join_paths_recursive(&corners).len(), 1);
+    }
+
+    #[test]
+    fn closes_gaps() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+                V2::new(1.0, 1.0),
+            ]),
+            Path::new_shape_from(vec![
+                V2::new(1.0, 1.0),
+                V2::new(0.0, 1.0),
+                V2::new(0.0, 0.005),
+            ]),
+        ]);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.01));
+        assert_eq!(joined.len(), 1);
+        assert!(joined.shapes[0].is_closed());
+        assert!((joined.shapes[0].length() - 4.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn keeps_other_shapes_and_sublayers() {
+        let circle = Circle::new_shape(V2::zero(), 1.0);
+        let closed =
+            Path::new_shape_from(vec![V2::zero(), V2::new(1.0, 0.0), V2::xy(1.0), V2::zero()]);
+        let sublayer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(2.0, 0.0)]),
+        ])
+        .with_name("sub");
+        let layer =
+            Layer::new_from_shapes_and_layers(vec![circle.clone(), closed.clone()], vec![sublayer]);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.01));
+        assert_eq!(joined.shapes, vec![circle, closed]);
+        assert_eq!(joined.sublayers[0].len(), 1);
+        assert_eq!(joined.sublayers[0].props.name, Some("sub".to_string()));
+
+        let flat = layer.join_paths_flat(&JoinSettings::new(0.01));
+        assert_eq!(flat.len(), 3);
+        assert_eq!(flat.len_sublayers(), 0);
+    }
+
+    #[test]
+    fn keeps_shape_order() {
+        let circle = Circle::new_shape(V2::new(5.0, 5.0), 1.0);
+        let closed =
+            Path::new_shape_from(vec![V2::zero(), V2::new(1.0, 0.0), V2::xy(1.0), V2::zero()]);
+        let layer = Layer::new_from(vec![
+            circle.clone(),
+            Path::new_shape_from(vec![V2::new(0.0, 3.0), V2::new(1.0, 3.0)]),
+            closed.clone(),
+            Path::new_shape_from(vec![V2::new(1.0, 3.0), V2::new(2.0, 3.0)]),
+        ]);
+
+        let joined = layer.join_paths_flat(&JoinSettings::new(0.01));
+        assert_eq!(
+            joined.shapes,
+            vec![
+                circle,
+                Path::new_shape_from(vec![
+                    V2::new(0.0, 3.0),
+                    V2::new(1.0, 3.0),
+                    V2::new(2.0, 3.0)
+                ]),
+                closed,
+            ]
+        );
+    }
+
+    #[test]
+    fn join_many() {
+        // a long line made of many short segments in shuffled order
+        let num = 10_000;
+        let mut shapes: Vec<Shape> = (0..num)
+            .map(|i| {
+                let from = V2::new(i as f32 * 0.01, 0.0);
+                let to = V2::new((i + 1) as f32 * 0.01 + 0.0001, 0.0);
+                if i % 2 == 0 {
+                    Path::new_shape_from(vec![from, to])
+                } else {
+                    Path::new_shape_from(vec![to, from])
+                }
+            })
+            .collect();
+        shapes.reverse();
+        let layer = Layer::new_from(shapes);
+
+        let joined = layer.join_paths_recursive(&JoinSettings::new(0.001));
+        assert_eq!(joined.len(), 1);
+        let joined_points = points(&joined.shapes[0]);
+        assert_eq!(joined_points.len(), num + 1);
+        assert_eq!(joined_points[0].x.min(joined_points[num].x), 0.0);
+        assert!((joined_points[0].x.max(joined_points[num].x) - num as f32 * 0.01).abs() < 0.01);
+    }
+}
@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{Circle, Compound, FillRule, Layer, Path, Rect, Shape, V2};
+
+use super::{ColorRgb, Inheritable, LayerProps, LayerPropsInheritable};
+
+/// Value of the `format` field of documents written by [`Layer::to_json`].
+pub const LAYER_JSON_FORMAT: &str = "plottery-layer";
+/// Version of the JSON schema written by [`Layer::to_json`].
+pub const LAYER_JSON_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct JsonDocument {
+    format: String,
+    version: u32,
+    units: String,
+    layer: JsonLayer,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonLayer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    props: Option<JsonProps>,
+    #[serde(default)]
+    shapes: Vec<JsonShape>,
+    #[serde(default)]
+    sublayers: Vec<JsonLayer>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonProps {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pen_width_cm: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pen_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed_factor: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pen_down_depth_cm: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    passes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    plot_priority: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonShape {
+    Path {
+        points: Vec<[f32; 2]>,
+    },
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+    },
+    Rect {
+        bl: [f32; 2],
+        tr: [f32; 2],
+    },
+    Compound {
+        outer: Vec<[f32; 2]>,
+        #[serde(default)]
+        holes: Vec<Vec<[f32; 2]>>,
+        #[serde(default)]
+        fill_rule: JsonFillRule,
+    },
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum JsonFillRule {
+    #[default]
+    EvenOdd,
+    NonZero,
+}
+
+fn specified<T: Clone>(value: &Inheritable<T>) -> Option<T> {
+    match value {
+        Inheritable::Inherit => None,
+        Inheritable::Specified(value) => Some(value.clone()),
+    }
+}
+
+fn inheritable<T: Clone>(value: Option<T>) -> Inheritable<T> {
+    match value {
+        Some(value) => Inheritable::Specified(value),
+        None => Inheritable::Inherit,
+    }
+}
+
+fn to_json_points(points: &[V2]) -> Vec<[f32; 2]> {
+    points.iter().map(|point| [point.x, point.y]).collect()
+}
+
+fn from_json_points(points: &[[f32; 2]]) -> Vec<V2> {
+    points.iter().map(|[x, y]| V2::new(*x, *y)).collect()
+}
+
+impl JsonLayer {
+    fn new(layer: &Layer) -> Self {
+        let props = match &layer.props_inheritable {
+            Inheritable::Inherit => None,
+            Inheritable::Specified(props) => Some(JsonProps {
+                color: specified(&props.color).map(|color| color.hex()),
+                pen_width_cm: specified(&props.pen_width_cm),
+                pen_id: specified(&props.pen_id),
+                speed_factor: specified(&props.speed_factor),
+                pen_down_depth_cm: specified(&props.pen_down_depth_cm),
+                passes: specified(&props.passes),
+                plot_priority: specified(&props.plot_priority),
+            }),
+        };
+        Self {
+            name: layer.props.name.clone(),
+            tags: layer.props.tags.clone(),
+            metadata: layer.props.metadata.clone(),
+            props,
+            shapes: layer
+                .iter()
+                .map(|shape| match shape {
+                    Shape::Path(path) => JsonShape::Path {
+                        points: to_json_points(path.get_points_ref()),
+                    },
+                    Shape::Circle(circle) => JsonShape::Circle {
+                        center: [circle.center.x, circle.center.y],
+                        radius: circle.radius,
+                    },
+                    Shape::Rect(rect) => JsonShape::Rect {
+                        bl: [rect.bl().x, rect.bl().y],
+                        tr: [rect.tr().x, rect.tr().y],
+                    },
+                    Shape::Compound(compound) => JsonShape::Compound {
+                        outer: to_json_points(compound.outer().get_points_ref()),
+                        holes: compound
+                            .holes()
+                            .iter()
+                            .map(|hole| to_json_points(hole.get_points_ref()))
+                            .collect(),
+                        fill_rule: match compound.fill_rule() {
+                            FillRule::EvenOdd => JsonFillRule::EvenOdd,
+                            FillRule::NonZero => JsonFillRule::NonZero,
+                        },
+                    },
+                })
+                .collect(),
+            sublayers: layer.iter_sublayers().map(JsonLayer::new).collect(),
+        }
+    }
+
+    fn into_layer(self) -> Result<Layer> {
+        let shapes = self
+            .shapes
+            .into_iter()
+            .map(|shape| match shape {
+                JsonShape::Path { points } => Path::new_shape_from(from_json_points(&points)),
+                JsonShape::Circle { center, radius } => {
+                    Circle::new_shape(V2::new(center[0], center[1]), radius)
+                }
+                JsonShape::Rect { bl, tr } => {
+                    Rect::new_shape(V2::new(bl[0], bl[1]), V2::new(tr[0], tr[1]))
+                }
+                JsonShape::Compound {
+                    outer,
+                    holes,
+                    fill_rule,
+                } => Shape::Compound(
+                    Compound::new(
+                        Path::new_from(from_json_points(&outer)),
+                        holes
+                            .iter()
+                            .map(|hole| Path::new_from(from_json_points(hole)))
+                            .collect(),
+                    )
+                    .with_fill_rule(match fill_rule {
+                        JsonFillRule::EvenOdd => FillRule::EvenOdd,
+                        JsonFillRule::NonZero => FillRule::NonZero,
+                    }),
+                ),
+            })
+            .collect();
+        let sublayers = self
+            .sublayers
+            .into_iter()
+            .map(JsonLayer::into_layer)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut layer = Layer::new_from_shapes_and_layers(shapes, sublayers);
+        layer.props = LayerProps {
+            name: self.name,
+            tags: self.tags,
+            metadata: self.metadata,
+        };
+        if let Some(props) = self.props {
+            let color = props
+                .color
+                .map(|color| ColorRgb::from_css(&color))
+                .transpose()?;
+            layer.props_inheritable = Inheritable::Specified(LayerPropsInheritable {
+                color: inheritable(color),
+                pen_width_cm: inheritable(props.pen_width_cm),
+                pen_id: inheritable(props.pen_id),
+                speed_factor: inheritable(props.speed_factor),
+                pen_down_depth_cm: inheritable(props.pen_down_depth_cm),
+                passes: inheritable(props.passes),
+                plot_priority: inheritable(props.plot_priority),
+            });
+        }
+        Ok(layer)
+    }
+}
+
+impl Layer {
+    /// Returns the layer as a JSON document, preserving sublayers and props.
+    ///
+    /// The document is an object with the fields
+    /// - `format`: always `"plottery-layer"`
+    /// - `version`: the schema version [`LAYER_JSON_VERSION`]
+    /// - `units`: always `"cm"`
+    /// - `layer`: the root layer
+    ///
+    /// A layer has the optional fields `name`, `tags` (list of strings), `metadata` (object of strings), `props`,
+    /// `shapes` and `sublayers` (list of layers). `props` holds the values the layer specifies instead of inheriting them:
+    /// `color` (hex or CSS color), `pen_width_cm`, `pen_id`, `speed_factor`, `pen_down_depth_cm`, `passes` and `plot_priority`.
+    ///
+    /// Points are `[x, y]` arrays. Shapes are objects with a `type` of
+    /// - `"path"` with `points`
+    /// - `"circle"` with `center` and `radius`
+    /// - `"rect"` with the corners `bl` (bottom left) and `tr` (top right)
+    /// - `"compound"` with the ring `outer`, a list of rings `holes` and `fill_rule` (`"evenodd"` or `"nonzero"`)
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Circle::new_shape(V2::new(1.0, 2.0), 0.5)]).with_name("dots");
+    /// let json = layer.to_json().unwrap();
+    /// assert!(json.contains(r#""type": "circle""#));
+    /// assert_eq!(Layer::new_from_json(&json).unwrap().props.name.unwrap(), "dots");
+    /// ```
+    pub fn to_json(&self) -> Result<String> {
+        let document = JsonDocument {
+            format: LAYER_JSON_FORMAT.to_string(),
+            version: LAYER_JSON_VERSION,
+            units: "cm".to_string(),
+            layer: JsonLayer::new(self),
+        };
+        Ok(serde_json::to_string_pretty(&document)?)
+    }
+
+    /// Writes the layer to a .json file, see [`Layer::to_json`].
+    pub fn write_json(&self, path: &PathBuf) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Creates a new `Layer` from a JSON document, see [`Layer::to_json`] for the schema.
+    pub fn new_from_json(json: &str) -> Result<Layer> {
+        let document: JsonDocument =
+            serde_json::from_str(json).context("invalid plottery layer json")?;
+        if document.format != LAYER_JSON_FORMAT {
+            bail!("unexpected json format '{}'", document.format);
+        }
+        if document.version > LAYER_JSON_VERSION {
+            bail!(
+                "unsupported layer json version {}, the newest supported version is {}",
+                document.version,
+                LAYER_JSON_VERSION
+            );
+        }
+        if document.units != "cm" {
+            bail!("unsupported units '{}'", document.units);
+        }
+        document.layer.into_layer()
+    }
+
+    /// Creates a new `Layer` from a .json file, see [`Layer::new_from_json`].
+    pub fn new_from_json_file(path: &PathBuf) -> Result<Layer> {
+        Self::new_from_json(&fs::read_to_string(path)?)
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod test_json {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        Circle, ColorRgb, Compound, FillRule, Inheritable, Layer, Path, Rect, Shape,
+        LAYER_JSON_FORMAT, LAYER_JSON_VERSION, V2,
+    };
+
+    #[test]
+    fn round_trip() {
+        let mut sublayer = Layer::new_from(vec![Shape::Compound(
+            Compound::new(
+                Path::new_from(vec![
+                    V2::new(0.0, 0.0),
+                    V2::new(4.0, 0.0),
+                    V2::new(4.0, 4.0),
+                ]),
+                vec![Path::new_from(vec![
+                    V2::new(1.0, 1.0),
+                    V2::new(2.0, 1.0),
+                    V2::new(2.0, 2.0),
+                ])],
+            )
+            .with_fill_rule(FillRule::NonZero),
+        )])
+        .with_name("holes")
+        .with_color(ColorRgb::red())
+        .with_passes(2);
+        sublayer.props.tags = vec!["fill".to_string()];
+        sublayer.props.metadata = BTreeMap::from([("author".to_string(), "someone".to_string())]);
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![
+                Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.5, -2.25)]),
+                Circle::new_shape(V2::new(1.0, 2.0), 0.5),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            ],
+            vec![sublayer],
+        );
+
+        let json = layer.to_json().unwrap();
+        let read = Layer::new_from_json(&json).unwrap();
+
+        assert_eq!(read.shapes, layer.shapes);
+        assert_eq!(read.props, layer.props);
+        assert_eq!(read.props_inheritable, Inheritable::Inherit);
+        assert_eq!(read.sublayers.len(), 1);
+        assert_eq!(read.sublayers[0].shapes, layer.sublayers[0].shapes);
+        assert_eq!(read.sublayers[0].props, layer.sublayers[0].props);
+        assert_eq!(
+            read.sublayers[0].props_inheritable,
+            layer.sublayers[0].props_inheritable
+        );
+    }
+
+    #[test]
+    fn schema() {
+        let mut sublayer = Layer::new_from(vec![Compound::new_shape(
+            Path::new_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(4.0, 0.0),
+                V2::new(4.0, 4.0),
+            ]),
+            vec![],
+        )])
+        .with_name("holes")
+        .with_color(ColorRgb::red())
+        .with_passes(2);
+        sublayer.props.metadata = BTreeMap::from([("author".to_string(), "someone".to_string())]);
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![
+                Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.5, -2.25)]),
+                Circle::new_shape(V2::new(1.0, 2.0), 0.5),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            ],
+            vec![sublayer],
+        );
+
+        let json = layer.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["format"], LAYER_JSON_FORMAT);
+        assert_eq!(value["version"], LAYER_JSON_VERSION);
+        assert_eq!(value["units"], "cm");
+        assert_eq!(value["layer"]["shapes"][0]["type"], "path");
+        assert_eq!(value["layer"]["shapes"][1]["radius"], 0.5);
+        assert_eq!(value["layer"]["shapes"][2]["tr"][0], 2.0);
+        let holes = &value["layer"]["sublayers"][0];
+        assert_eq!(holes["name"], "holes");
+        assert_eq!(holes["props"]["color"], ColorRgb::red().hex());
+        assert_eq!(holes["props"]["passes"], 2);
+        assert!(holes["props"].get("pen_id").is_none());
+        assert_eq!(holes["shapes"][0]["fill_rule"], "evenodd");
+        assert_eq!(holes["metadata"]["author"], "someone");
+    }
+
+    #[test]
+    fn minimal_document() {
+        let json = r#"{
+            "format": "plottery-layer", "version": 1, "units": "cm",
+            "layer": {"props": {"color": "blue"}, "shapes": [{"type": "path", "points": [[0, 0], [1, 1]]}]}
+        }"#;
+        let layer = Layer::new_from_json(json).unwrap();
+        assert_eq!(layer.len(), 1);
+        assert!(layer.sublayers.is_empty());
+        let Inheritable::Specified(props) = &layer.props_inheritable else {
+            panic!("props should be specified");
+        };
+        assert_eq!(props.color, Inheritable::Specified(ColorRgb::blue()));
+        assert_eq!(props.pen_width_cm, Inheritable::Inherit);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        let document = |format: &str, version: u32, units: &str| {
+            format!(
+                r#"{{"format": "{}", "version": {}, "units": "{}", "layer": {{}}}}"#,
+                format, version, units
+            )
+        };
+        assert!(Layer::new_from_json(&document(LAYER_JSON_FORMAT, 1, "cm")).is_ok());
+        assert!(Layer::new_from_json(&document("something-else", 1, "cm")).is_err());
+        assert!(
+            Layer::new_from_json(&document(LAYER_JSON_FORMAT, LAYER_JSON_VERSION + 1, "cm"))
+                .is_err()
+        );
+        assert!(Layer::new_from_json(&document(LAYER_JSON_FORMAT, 1, "mm")).is_err());
+        assert!(Layer::new_from_json("{\"layer\": []}").is_err());
+    }
+}
@@ -1,5 +1,4 @@
-use anyhow::{Ok, Result};
-use bincode::{deserialize_from, serialize};
+use anyhow::{anyhow, bail, Context, Ok, Result};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -16,12 +15,20 @@ use svg::{
 };
 
 use crate::{
-    traits::{Normalize, Scale, Scale2D, Translate},
-    Angle, BoundingBox, Circle, Masked, Mirror, Path, Plottable, Rect, Rotate, SampleSettings,
-    Shape, V2,
+    geometry::TransformMatrix,
+    traits::{Normalize, Scale, Scale2D, Transform, Translate},
+    Angle, BoundingBox, Circle, FillRule, Masked, Mirror, Path, Plottable, Rect, Rotate,
+    SampleSettings, Shape, V2,
 };
 
-use super::{path_end::PathEnd, ColorRgb, Inheritable, LayerProps, LayerPropsInheritable};
+use super::{
+    path_end::PathEnd,
+    svg_curve::{flatten_arc, flatten_cubic, flatten_ellipse, flatten_quadratic, SvgArc},
+    ColorRgb, Inheritable, LayerFileHeader, LayerProps, LayerPropsInheritable,
+};
+
+/// Default maximum deviation of flattened curves from the original when importing svg files, see [`Layer::new_from_svg`].
+pub const SVG_IMPORT_TOLERANCE_CM: f32 = 0.002;
 
 /// `Layer` represents a tree of [`Shape`]s by holding a list of [`Shape`]s and other `Layer`s.
 ///
@@ -117,6 +124,142 @@ impl SvgTransform {
             self.b * point.x + self.d * point.y + self.f,
         )
     }
+
+    fn determinant(self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Largest factor a length is scaled by.
+    fn max_scale_factor(self) -> f32 {
+        let sum_squares = self.a * self.a + self.b * self.b + self.c * self.c + self.d * self.d;
+        let det = self.determinant();
+        ((sum_squares
+            + (sum_squares * sum_squares - 4.0 * det * det)
+                .max(0.0)
+                .sqrt())
+            * 0.5)
+            .sqrt()
+    }
+
+    /// Uniform scale factor if the transform keeps circles circular (rotation, reflection and uniform scaling).
+    fn similarity_scale(self) -> Option<f32> {
+        let scale = (self.a * self.a + self.b * self.b).sqrt();
+        let epsilon = scale * 1e-5;
+        let is_rotation = (self.a - self.d).abs() <= epsilon && (self.b + self.c).abs() <= epsilon;
+        let is_reflection =
+            (self.a + self.d).abs() <= epsilon && (self.b - self.c).abs() <= epsilon;
+        (is_rotation || is_reflection).then_some(scale)
+    }
+
+    fn is_axis_aligned(self) -> bool {
+        self.b == 0.0 && self.c == 0.0
+    }
+}
+
+/// Stroke style of an svg node, `None` where neither the node nor its ancestors specify it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct SvgStroke {
+    color: Option<ColorRgb>,
+    width_cm: Option<f32>,
+}
+
+impl SvgStroke {
+    fn or(self, parent: Self) -> Self {
+        Self {
+            color: self.color.or(parent.color),
+            width_cm: self.width_cm.or(parent.width_cm),
+        }
+    }
+
+    /// Props setting the values that differ from `parent`.
+    fn props_inheritable(self, parent: Self) -> Inheritable<LayerPropsInheritable> {
+        let mut props = LayerPropsInheritable::inherit_all();
+        let mut specified_any = false;
+        if let Some(color) = self.color.filter(|color| Some(*color) != parent.color) {
+            props = props.with_color(color);
+            specified_any = true;
+        }
+        if let Some(width_cm) = self
+            .width_cm
+            .filter(|width| Some(*width) != parent.width_cm)
+        {
+            props = props.with_pen_width_cm(width_cm);
+            specified_any = true;
+        }
+        if specified_any {
+            Inheritable::Specified(props)
+        } else {
+            Inheritable::Inherit
+        }
+    }
+}
+
+/// State inherited from the ancestors of an svg node.
+#[derive(Debug, Clone, Copy)]
+struct SvgContext {
+    transform: SvgTransform,
+    stroke: SvgStroke,
+    hidden: bool,
+    /// Whether the node started a [`SvgGroup`] that has to be closed with it.
+    opens_group: bool,
+}
+
+/// A `<g>` node (or the document itself) collected during svg import.
+#[derive(Debug)]
+struct SvgGroup {
+    name: Option<String>,
+    stroke: SvgStroke,
+    shapes: Vec<(Shape, SvgStroke)>,
+    groups: Vec<SvgGroup>,
+}
+
+impl SvgGroup {
+    fn new(name: Option<String>, stroke: SvgStroke) -> Self {
+        Self {
+            name,
+            stroke,
+            shapes: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Converts the group to a [`Layer`] using the most common stroke of its shapes.
+    /// Shapes with other strokes are moved to one unnamed sublayer per stroke.
+    fn into_layer(self, parent_stroke: SvgStroke) -> Layer {
+        let mut strokes: Vec<(SvgStroke, Vec<Shape>)> = Vec::new();
+        for (shape, stroke) in self.shapes {
+            match strokes.iter_mut().find(|(s, _)| *s == stroke) {
+                Some((_, shapes)) => shapes.push(shape),
+                None => strokes.push((stroke, vec![shape])),
+            }
+        }
+        let main_index = strokes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, (_, shapes))| shapes.len())
+            .map(|(i, _)| i);
+        let (stroke, shapes) = match main_index {
+            Some(i) => strokes.remove(i),
+            None => (self.stroke, Vec::new()),
+        };
+
+        let styled_layers = strokes.into_iter().map(|(other_stroke, shapes)| {
+            Layer::new_from(shapes).with_props_inheritable(other_stroke.props_inheritable(stroke))
+        });
+        let group_layers = self
+            .groups
+            .into_iter()
+            .map(|group| group.into_layer(stroke));
+
+        let layer =
+            Layer::new_from_shapes_and_layers(shapes, styled_layers.chain(group_layers).collect())
+                .with_props_inheritable(stroke.props_inheritable(parent_stroke));
+        match self.name {
+            Some(name) => layer.with_name(&name),
+            None => layer,
+        }
+    }
 }
 
 impl Layer {
@@ -154,26 +297,32 @@ impl Layer {
             props: LayerProps::default(),
         }
     }
-    /// Creates a new `Layer` by deserializing binary from a file. see [`Layer::write_file`].
+    /// Creates a new `Layer` by deserializing binary from a file, see [`Layer::new_from_binary_with_header`].
     pub fn new_from_file(path: &PathBuf) -> Result<Layer> {
-        let file = File::open(path)?;
-        let decoded: Layer = deserialize_from(&file)?;
-        Ok(decoded)
+        Ok(Self::new_from_file_with_header(path)?.0)
     }
-    /// Writes the binary representation of the `Layer` to a file. see [`Layer::new_from_file`].
+    /// Creates a new `Layer` and its [`LayerFileHeader`] by deserializing binary from a file, see [`Layer::new_from_binary_with_header`].
+    pub fn new_from_file_with_header(path: &PathBuf) -> Result<(Layer, LayerFileHeader)> {
+        Self::new_from_binary_with_header(&std::fs::read(path)?)
+    }
+    /// Writes the binary representation of the `Layer` with a default [`LayerFileHeader`] to a file. see [`Layer::new_from_file`].
     pub fn write_file(&self, path: &PathBuf) -> Result<()> {
-        let encoded: Vec<u8> = serialize(self)?;
+        self.write_file_with_header(path, &LayerFileHeader::new())
+    }
+    /// Writes the binary representation of the `Layer` with `header` to a file, see [`Layer::to_binary_with_header`].
+    pub fn write_file_with_header(&self, path: &PathBuf, header: &LayerFileHeader) -> Result<()> {
+        let encoded = self.to_binary_with_header(header)?;
         let mut file = File::create(path)?;
         file.write_all(&encoded)?;
         Ok(())
     }
-    /// Creates a new `Layer` by deserializing binary from a vector of bytes. see [`Layer::to_binary`].
-    pub fn new_from_binary(binary_data: &Vec<u8>) -> Result<Layer> {
-        Ok(deserialize_from(binary_data.as_slice())?)
+    /// Creates a new `Layer` by deserializing binary from a vector of bytes, see [`Layer::new_from_binary_with_header`].
+    pub fn new_from_binary(binary_data: &[u8]) -> Result<Layer> {
+        Ok(Self::new_from_binary_with_header(binary_data)?.0)
     }
-    /// Serializes the `Layer` to a vector of bytes. see [`Layer::new_from_binary`].
+    /// Serializes the `Layer` with a default [`LayerFileHeader`] to a vector of bytes. see [`Layer::new_from_binary`].
     pub fn to_binary(&self) -> Result<Vec<u8>> {
-        Ok(serialize(self)?)
+        self.to_binary_with_header(&LayerFileHeader::new())
     }
 
     /// set the inheritable properties of the layer
@@ -196,21 +345,43 @@ impl Layer {
     }
 
     /// Helper to set the color of the layer. see [`Layer::with_props_inheritable`].
-    pub fn with_color(mut self, color: ColorRgb) -> Self {
-        self.props_inheritable = self
-            .props_inheritable
-            .overwrite_with(&Inheritable::Specified(
-                LayerPropsInheritable::inherit_all().with_color(color),
-            ));
-        self
+    pub fn with_color(self, color: ColorRgb) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_color(color))
     }
     /// Helper to set the pen width of the layer. see [`Layer::with_props_inheritable`].
-    pub fn with_pen_width_cm(mut self, pen_width_cm: f32) -> Self {
-        self.props_inheritable = self
-            .props_inheritable
-            .overwrite_with(&Inheritable::Specified(
-                LayerPropsInheritable::inherit_all().with_pen_width_cm(pen_width_cm),
-            ));
+    pub fn with_pen_width_cm(self, pen_width_cm: f32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_pen_width_cm(pen_width_cm))
+    }
+    /// Helper to set the pen id of the layer. see [`Layer::with_props_inheritable`].
+    pub fn with_pen_id(self, pen_id: u32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_pen_id(pen_id))
+    }
+    /// Helper to set the drawing speed factor of the layer. see [`Layer::with_props_inheritable`].
+    pub fn with_speed_factor(self, speed_factor: f32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_speed_factor(speed_factor))
+    }
+    /// Helper to set the pen down depth of the layer. see [`Layer::with_props_inheritable`].
+    pub fn with_pen_down_depth_cm(self, pen_down_depth_cm: f32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_pen_down_depth_cm(pen_down_depth_cm))
+    }
+    /// Helper to set the number of passes of the layer. see [`Layer::with_props_inheritable`].
+    pub fn with_passes(self, passes: u32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_passes(passes))
+    }
+    /// Helper to set the plot priority of the layer. see [`Layer::with_props_inheritable`].
+    pub fn with_plot_priority(self, plot_priority: i32) -> Self {
+        self.with_props_inheritable_updated(|props| props.with_plot_priority(plot_priority))
+    }
+    /// Applies `update` to the inheritable properties, keeping all values that were specified before.
+    fn with_props_inheritable_updated<F>(mut self, update: F) -> Self
+    where
+        F: FnOnce(&LayerPropsInheritable) -> LayerPropsInheritable,
+    {
+        let current = match &self.props_inheritable {
+            Inheritable::Specified(props) => props.clone(),
+            Inheritable::Inherit => LayerPropsInheritable::inherit_all(),
+        };
+        self.props_inheritable = Inheritable::Specified(update(&current));
         self
     }
     /// Helper to set the name of the layer. see [`Layer::with_props`].
@@ -218,6 +389,16 @@ impl Layer {
         self.props = self.props.with_name(name);
         self
     }
+    /// Helper to add a tag to the layer. see [`Layer::with_props`].
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.props = self.props.with_tag(tag);
+        self
+    }
+    /// Helper to set a metadata entry of the layer. see [`Layer::with_props`].
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.props = self.props.with_metadata(key, value);
+        self
+    }
 
     pub fn push<S: Into<Shape>>(&mut self, shape: S) {
         self.shapes.push(shape.into());
@@ -263,6 +444,27 @@ impl Layer {
     pub fn iter_flattened(&self) -> LayerFlattenedIterator<'_> {
         LayerFlattenedIterator::new(self)
     }
+    /// Returns this `Layer` and all sublayers with their resolved [`LayerPropsInheritable`], in the order they are plotted.
+    ///
+    /// Layers are sorted by [`LayerPropsInheritable::plot_priority`], layers with the same priority are kept in the order of [`Layer::iter_flattened`].
+    /// Only the layers' own [`Shape`]s belong to each entry.
+    pub fn layers_in_plot_order(&self) -> Vec<(&Layer, LayerPropsInheritable)> {
+        let mut layers = Vec::new();
+        self.collect_layers_with_props(&LayerPropsInheritable::default(), &mut layers);
+        layers.sort_by_key(|(_, props)| props.plot_priority.unwrap());
+        layers
+    }
+    pub(crate) fn collect_layers_with_props<'a>(
+        &'a self,
+        parent_props: &LayerPropsInheritable,
+        layers: &mut Vec<(&'a Layer, LayerPropsInheritable)>,
+    ) {
+        let props = parent_props.overwrite_with(&self.props_inheritable);
+        layers.push((self, props.clone()));
+        for sublayer in self.iter_sublayers() {
+            sublayer.collect_layers_with_props(&props, layers);
+        }
+    }
 
     /// Returns the number of shapes in the layer, excluding sublayers.
     pub fn len(&self) -> usize {
@@ -300,7 +502,7 @@ impl Layer {
             .set("viewBox", (0, 0, svg_max_coords.x, svg_max_coords.y))
             .set("width", svg_max_coords.x)
             .set("height", svg_max_coords.y)
-            .add(prepared.get_svg_group(scale, &LayerPropsInheritable::default()))
+            .add(prepared.get_svg_group(scale, &LayerPropsInheritable::default(), false))
     }
     fn get_prepared_for_svg(&self) -> Layer {
         let bounding_box = self.bounding_box();
@@ -311,13 +513,25 @@ impl Layer {
 
         self.map_recursive(|shape| shape.mirror_y().translate(bounding_box.size().only_y()))
     }
-    fn get_svg_group(&self, scale: f32, parent_props: &LayerPropsInheritable) -> Group {
+    /// Sublayers are written as Inkscape layers if `inkscape_layers` is set.
+    pub(super) fn get_svg_group(
+        &self,
+        scale: f32,
+        parent_props: &LayerPropsInheritable,
+        inkscape_layers: bool,
+    ) -> Group {
         let props_inheritable = parent_props.overwrite_with(&self.props_inheritable);
 
         let mut group = Group::new();
         if let Some(name) = &self.props.name {
             group = group.set("id", name.as_str());
         }
+        if inkscape_layers {
+            group = group.set("inkscape:groupmode", "layer").set(
+                "inkscape:label",
+                self.props.name.as_deref().unwrap_or("layer"),
+            );
+        }
 
         for shape_svg in self.get_shapes_as_svg_nodes(scale, &props_inheritable) {
             group = group.add(shape_svg);
@@ -325,7 +539,7 @@ impl Layer {
         for sublayer_svg in self
             .sublayers
             .iter()
-            .map(|sublayer| sublayer.get_svg_group(scale, &props_inheritable))
+            .map(|sublayer| sublayer.get_svg_group(scale, &props_inheritable, inkscape_layers))
         {
             group = group.add(sublayer_svg);
         }
@@ -389,6 +603,33 @@ impl Layer {
                             .set("stroke-width", stroke_width),
                     ));
                 }
+                Shape::Compound(c) => {
+                    let mut data = Data::new();
+                    for ring in c.rings() {
+                        let points = ring.get_points_ref();
+                        if points.len() <= 1 {
+                            continue;
+                        }
+                        data = data.move_to((points[0] * scale).as_tuple());
+                        data = points
+                            .iter()
+                            .skip(1)
+                            .fold(data, |data, point| data.line_to((point * scale).as_tuple()));
+                        data = data.close();
+                    }
+                    let fill_rule = match c.fill_rule() {
+                        FillRule::EvenOdd => "evenodd",
+                        FillRule::NonZero => "nonzero",
+                    };
+                    nodes.push(Box::new(
+                        svg::node::element::Path::new()
+                            .set("d", data)
+                            .set("fill", fill)
+                            .set("fill-rule", fill_rule)
+                            .set("stroke", stroke.clone())
+                            .set("stroke-width", stroke_width),
+                    ));
+                }
             }
         }
         nodes
@@ -400,105 +641,116 @@ impl Layer {
         Ok(())
     }
 
-    /// Creates a new `Layer` from an .svg file.
+    /// Creates a new `Layer` from an .svg file, flattening curves with [`SVG_IMPORT_TOLERANCE_CM`].
+    ///
+    /// Imports `<path>`, `<line>`, `<polyline>`, `<polygon>`, `<circle>`, `<ellipse>` and `<rect>` nodes, including
+    /// curves (`C/S/Q/T`) and elliptical arcs (`A`) in path data. Malformed nodes return an error naming the node.
     ///
-    /// Currently this only imports basic `<path d="...">` commands (`M/m`, `L/l`, `H/h`, `V/v`, `Z/z`).
+    /// `<g>` groups and Inkscape layers become sublayers named by their `inkscape:label` or `id`. If the document only
+    /// contains a single group, that group becomes the returned `Layer`, so files written by [`Layer::write_svg`] keep their structure.
+    /// `stroke` and `stroke-width` (from attributes or inline `style`) set the color and pen width of the layers; shapes with a stroke
+    /// different from the rest of their group are moved into an unnamed sublayer. Hidden elements and `<defs>` are skipped.
     pub fn new_from_svg(path: &PathBuf) -> Result<Layer> {
+        Self::new_from_svg_with_tolerance(path, SVG_IMPORT_TOLERANCE_CM)
+    }
+
+    /// Same as [`Layer::new_from_svg`], with curves and arcs flattened so they deviate at most `tolerance_cm` from the original.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// let svg_path = temp_dir.path().join("circle.svg");
+    /// std::fs::write(&svg_path, "<svg><ellipse cx='5' cy='5' rx='4' ry='2'/></svg>").unwrap();
+    ///
+    /// let coarse = Layer::new_from_svg_with_tolerance(&svg_path, 0.1).unwrap();
+    /// let fine = Layer::new_from_svg_with_tolerance(&svg_path, 0.001).unwrap();
+    /// let num_points = |layer: &Layer| layer.shapes[0].get_points(SampleSettings::default()).len();
+    /// assert!(num_points(&coarse) < num_points(&fine));
+    /// ```
+    pub fn new_from_svg_with_tolerance(path: &PathBuf, tolerance_cm: f32) -> Result<Layer> {
         let mut svg_content = String::new();
-        let mut layer = Layer::new();
-        let mut transform_stack: Vec<SvgTransform> = vec![SvgTransform::identity()];
+        let mut context_stack = vec![SvgContext {
+            transform: SvgTransform::identity(),
+            stroke: SvgStroke::default(),
+            hidden: false,
+            opens_group: false,
+        }];
+        let mut group_stack = vec![SvgGroup::new(None, SvgStroke::default())];
         let mut svg_units_to_cm_scale: f32 = 1.0;
 
         for event in svg::open(path, &mut svg_content)? {
-            if let Event::Tag(tag, tag_type, attributes) = event {
-                if tag == "svg" && !matches!(tag_type, SvgTagType::End) {
-                    if let Some(scale) = Self::parse_svg_root_units_to_cm_scale(&attributes) {
-                        svg_units_to_cm_scale = scale;
-                    }
+            let Event::Tag(tag, tag_type, attributes) = event else {
+                continue;
+            };
+            if matches!(tag_type, SvgTagType::End) {
+                if context_stack.len() > 1 && context_stack.pop().unwrap().opens_group {
+                    let group = group_stack.pop().unwrap();
+                    group_stack.last_mut().unwrap().groups.push(group);
                 }
+                continue;
+            }
 
-                let parent_transform = *transform_stack.last().unwrap_or(&SvgTransform::identity());
-                let local_transform =
-                    Self::parse_svg_transform_attr(&attributes).unwrap_or(SvgTransform::identity());
-                let combined_transform = parent_transform.multiply(local_transform);
-
-                if matches!(tag_type, SvgTagType::Start) {
-                    transform_stack.push(combined_transform);
+            if tag == "svg" {
+                if let Some(scale) = Self::parse_svg_root_units_to_cm_scale(&attributes) {
+                    svg_units_to_cm_scale = scale;
                 }
+            }
 
-                if !matches!(tag_type, SvgTagType::End) {
-                    match tag {
-                        "path" => {
-                            let Some(data_value) = attributes.get("d") else {
-                                // TODO: Handle malformed `<path>` nodes without `d` attribute more explicitly.
-                                continue;
-                            };
-
-                            let path_data = data_value.to_string();
-                            let should_close_paths = Self::svg_path_has_fill(&attributes);
-                            for mut path in Self::parse_svg_path_data(path_data.as_str()) {
-                                if should_close_paths {
-                                    Self::close_path_if_needed(&mut path);
-                                }
-
-                                if path.get_points_ref().len() >= 2 {
-                                    layer.push(Self::apply_svg_transform_to_path(
-                                        &path,
-                                        combined_transform,
-                                        svg_units_to_cm_scale,
-                                    ));
-                                }
-                            }
-                        }
-                        "line" => {
-                            if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
-                                Self::parse_svg_attr_f32(&attributes, "x1"),
-                                Self::parse_svg_attr_f32(&attributes, "y1"),
-                                Self::parse_svg_attr_f32(&attributes, "x2"),
-                                Self::parse_svg_attr_f32(&attributes, "y2"),
-                            ) {
-                                layer.push(Self::apply_svg_transform_to_path(
-                                    &Path::new_from(vec![V2::new(x1, y1), V2::new(x2, y2)]),
-                                    combined_transform,
-                                    svg_units_to_cm_scale,
-                                ));
-                            } else {
-                                // TODO: Handle malformed `<line>` nodes more explicitly.
-                            }
-                        }
-                        "polyline" | "polygon" => {
-                            let Some(points_attr) = attributes.get("points") else {
-                                // TODO: Handle malformed polyline/polygon nodes without points attribute.
-                                continue;
-                            };
-
-                            let mut points = Self::parse_svg_points_attr(points_attr);
-                            if tag == "polygon"
-                                && points.len() >= 2
-                                && points.first() != points.last()
-                            {
-                                points.push(*points.first().unwrap());
-                            }
-
-                            if points.len() >= 2 {
-                                layer.push(Self::apply_svg_transform_to_path(
-                                    &Path::new_from(points),
-                                    combined_transform,
-                                    svg_units_to_cm_scale,
-                                ));
-                            }
-                        }
-                        _ => {
-                            // TODO: Support importing non-path SVG elements (`circle`, `rect`, etc.).
-                        }
-                    }
-                }
+            let parent = *context_stack.last().unwrap();
+            let local_transform =
+                Self::parse_svg_transform_attr(&attributes).unwrap_or(SvgTransform::identity());
+            let transform = parent.transform.multiply(local_transform);
+            let mut context = SvgContext {
+                transform,
+                stroke: Self::parse_svg_stroke(&attributes, transform, svg_units_to_cm_scale)
+                    .or(parent.stroke),
+                hidden: parent.hidden || Self::svg_node_is_hidden(tag, &attributes),
+                opens_group: false,
+            };
 
-                if matches!(tag_type, SvgTagType::End) && transform_stack.len() > 1 {
-                    transform_stack.pop();
+            if !context.hidden {
+                if tag == "g" && matches!(tag_type, SvgTagType::Start) {
+                    let name = attributes
+                        .get("inkscape:label")
+                        .or(attributes.get("id"))
+                        .map(|name| name.to_string());
+                    group_stack.push(SvgGroup::new(name, context.stroke));
+                    context.opens_group = true;
+                } else {
+                    let shapes = Self::parse_svg_node(
+                        tag,
+                        &attributes,
+                        transform,
+                        svg_units_to_cm_scale,
+                        tolerance_cm,
+                    )
+                    .with_context(|| match attributes.get("id") {
+                        Some(id) => format!("malformed svg node <{} id=\"{}\">", tag, id),
+                        None => format!("malformed svg node <{}>", tag),
+                    })?;
+                    let group = group_stack.last_mut().unwrap();
+                    group
+                        .shapes
+                        .extend(shapes.into_iter().map(|shape| (shape, context.stroke)));
                 }
             }
+
+            if matches!(tag_type, SvgTagType::Start) {
+                context_stack.push(context);
+            }
+        }
+
+        // groups left open by a truncated document
+        while group_stack.len() > 1 {
+            let group = group_stack.pop().unwrap();
+            group_stack.last_mut().unwrap().groups.push(group);
+        }
+        let mut root = group_stack.pop().unwrap();
+        if root.shapes.is_empty() && root.groups.len() == 1 {
+            root = root.groups.pop().unwrap();
         }
+        let mut layer = root.into_layer(SvgStroke::default());
 
         // Convert SVG y-down coordinates back to Plottery y-up coordinates.
         if let Some(bounds) = layer.bounding_box() {
@@ -508,9 +760,191 @@ impl Layer {
         Ok(layer)
     }
 
-    fn parse_svg_attr_f32(attributes: &Attributes, key: &str) -> Option<f32> {
-        let value = attributes.get(key)?;
-        value.to_string().parse::<f32>().ok()
+    fn parse_svg_node(
+        tag: &str,
+        attributes: &Attributes,
+        transform: SvgTransform,
+        scale_to_cm: f32,
+        tolerance_cm: f32,
+    ) -> Result<Vec<Shape>> {
+        // tolerance in the node's own coordinates, so it holds after transforming to cm
+        let tolerance =
+            tolerance_cm / (scale_to_cm * transform.max_scale_factor()).max(f32::EPSILON);
+        let to_cm = |points: Vec<V2>| {
+            Shape::Path(Self::apply_svg_transform_to_path(
+                &Path::new_from(points),
+                transform,
+                scale_to_cm,
+            ))
+        };
+
+        let shapes = match tag {
+            "path" => {
+                let Some(data_value) = attributes.get("d") else {
+                    bail!("missing attribute 'd'");
+                };
+
+                let should_close_paths = Self::svg_path_has_fill(attributes);
+                Self::parse_svg_path_data(data_value.to_string().as_str(), tolerance)?
+                    .into_iter()
+                    .filter_map(|mut path| {
+                        if should_close_paths {
+                            Self::close_path_if_needed(&mut path);
+                        }
+                        (path.get_points_ref().len() >= 2).then(|| {
+                            Shape::Path(Self::apply_svg_transform_to_path(
+                                &path,
+                                transform,
+                                scale_to_cm,
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+            "line" => {
+                let from = Self::parse_svg_attr_pair(attributes, "x1", "y1")?;
+                let to = Self::parse_svg_attr_pair(attributes, "x2", "y2")?;
+                vec![to_cm(vec![from, to])]
+            }
+            "polyline" | "polygon" => {
+                let Some(points_attr) = attributes.get("points") else {
+                    bail!("missing attribute 'points'");
+                };
+
+                let mut points = Self::parse_svg_points_attr(points_attr)?;
+                if tag == "polygon" && points.len() >= 2 && points.first() != points.last() {
+                    points.push(*points.first().unwrap());
+                }
+
+                if points.len() >= 2 {
+                    vec![to_cm(points)]
+                } else {
+                    Vec::new()
+                }
+            }
+            "circle" => {
+                let center = Self::parse_svg_attr_pair(attributes, "cx", "cy")?;
+                let radius = Self::parse_svg_attr_non_negative(attributes, "r")?.unwrap_or(0.0);
+                if radius == 0.0 {
+                    Vec::new()
+                } else if let Some(scale) = transform.similarity_scale() {
+                    vec![Circle::new_shape(
+                        transform.apply(center) * scale_to_cm,
+                        radius * scale * scale_to_cm,
+                    )]
+                } else {
+                    vec![to_cm(flatten_ellipse(center, V2::xy(radius), tolerance))]
+                }
+            }
+            "ellipse" => {
+                let center = Self::parse_svg_attr_pair(attributes, "cx", "cy")?;
+                let radius = Self::parse_svg_radii(attributes)?;
+                if radius.x == 0.0 || radius.y == 0.0 {
+                    Vec::new()
+                } else {
+                    vec![to_cm(flatten_ellipse(center, radius, tolerance))]
+                }
+            }
+            "rect" => {
+                let bl = Self::parse_svg_attr_pair(attributes, "x", "y")?;
+                let size = V2::new(
+                    Self::parse_svg_attr_non_negative(attributes, "width")?.unwrap_or(0.0),
+                    Self::parse_svg_attr_non_negative(attributes, "height")?.unwrap_or(0.0),
+                );
+                let radius = Self::parse_svg_radii(attributes)?.min(size * 0.5);
+                let is_rounded = radius.x > 0.0 && radius.y > 0.0;
+
+                if size.x == 0.0 || size.y == 0.0 {
+                    Vec::new()
+                } else if !is_rounded && transform.is_axis_aligned() {
+                    vec![Rect::new_shape(
+                        transform.apply(bl) * scale_to_cm,
+                        transform.apply(bl + size) * scale_to_cm,
+                    )]
+                } else {
+                    vec![to_cm(Self::svg_rounded_rect_points(
+                        bl, size, radius, tolerance,
+                    ))]
+                }
+            }
+            _ => Vec::new(),
+        };
+        Ok(shapes)
+    }
+
+    fn svg_rounded_rect_points(bl: V2, size: V2, radius: V2, tolerance: f32) -> Vec<V2> {
+        let tr = bl + size;
+        let corner_arc = SvgArc {
+            radius,
+            x_axis_rotation_degrees: 0.0,
+            large_arc: false,
+            sweep: true,
+        };
+        let segments = [
+            (
+                V2::new(tr.x - radius.x, bl.y),
+                V2::new(tr.x, bl.y + radius.y),
+            ),
+            (
+                V2::new(tr.x, tr.y - radius.y),
+                V2::new(tr.x - radius.x, tr.y),
+            ),
+            (
+                V2::new(bl.x + radius.x, tr.y),
+                V2::new(bl.x, tr.y - radius.y),
+            ),
+            (
+                V2::new(bl.x, bl.y + radius.y),
+                V2::new(bl.x + radius.x, bl.y),
+            ),
+        ];
+
+        let start = V2::new(bl.x + radius.x, bl.y);
+        let mut points = vec![start];
+        for (line_end, arc_end) in segments {
+            if points.last() != Some(&line_end) {
+                points.push(line_end);
+            }
+            points.extend(flatten_arc(line_end, &corner_arc, arc_end, tolerance));
+        }
+        points
+    }
+
+    /// Parses a coordinate attribute, `None` if it is missing. A `px` suffix is accepted.
+    fn parse_svg_attr_f32(attributes: &Attributes, key: &str) -> Result<Option<f32>> {
+        let Some(value) = attributes.get(key) else {
+            return Ok(None);
+        };
+        let value = value.to_string();
+        let number = value.trim().trim_end_matches("px");
+        number
+            .trim()
+            .parse::<f32>()
+            .map(Some)
+            .map_err(|_| anyhow!("invalid value '{}' for attribute '{}'", value, key))
+    }
+
+    fn parse_svg_attr_non_negative(attributes: &Attributes, key: &str) -> Result<Option<f32>> {
+        let value = Self::parse_svg_attr_f32(attributes, key)?;
+        if value.is_some_and(|value| value < 0.0) {
+            bail!("negative value for attribute '{}'", key);
+        }
+        Ok(value)
+    }
+
+    /// Parses a point from two coordinate attributes, missing ones default to `0`.
+    fn parse_svg_attr_pair(attributes: &Attributes, key_x: &str, key_y: &str) -> Result<V2> {
+        Ok(V2::new(
+            Self::parse_svg_attr_f32(attributes, key_x)?.unwrap_or(0.0),
+            Self::parse_svg_attr_f32(attributes, key_y)?.unwrap_or(0.0),
+        ))
+    }
+
+    /// Parses `rx` and `ry`, where a missing radius takes the value of the other one.
+    fn parse_svg_radii(attributes: &Attributes) -> Result<V2> {
+        let rx = Self::parse_svg_attr_non_negative(attributes, "rx")?;
+        let ry = Self::parse_svg_attr_non_negative(attributes, "ry")?;
+        Ok(V2::new(rx.or(ry).unwrap_or(0.0), ry.or(rx).unwrap_or(0.0)))
     }
 
     fn iter_svg_f32_tokens(input: &str) -> impl Iterator<Item = f32> + '_ {
@@ -520,19 +954,25 @@ impl Layer {
             .filter_map(|token| token.parse::<f32>().ok())
     }
 
-    fn parse_svg_points_attr(points_attr: &svg::node::Value) -> Vec<V2> {
+    fn parse_svg_points_attr(points_attr: &svg::node::Value) -> Result<Vec<V2>> {
         let points = points_attr.to_string();
-        let mut tokens = Self::iter_svg_f32_tokens(points.as_str());
-        let mut parsed_points = Vec::new();
-
-        while let Some(x) = tokens.next() {
-            let Some(y) = tokens.next() else {
-                break;
-            };
-            parsed_points.push(V2::new(x, y));
+        let values = points
+            .split(|c: char| c == ',' || c.is_ascii_whitespace())
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<f32>()
+                    .map_err(|_| anyhow!("invalid number '{}' in attribute 'points'", token))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !values.len().is_multiple_of(2) {
+            bail!("odd number of coordinates in attribute 'points'");
         }
 
-        parsed_points
+        Ok(values
+            .chunks_exact(2)
+            .map(|pair| V2::new(pair[0], pair[1]))
+            .collect())
     }
 
     fn apply_svg_transform_to_path(path: &Path, transform: SvgTransform, scale_to_cm: f32) -> Path {
@@ -628,27 +1068,46 @@ impl Layer {
     }
 
     fn svg_path_has_fill(attributes: &Attributes) -> bool {
-        if let Some(fill_value) = attributes.get("fill") {
-            let fill = fill_value.to_string().trim().to_ascii_lowercase();
-            if !fill.is_empty() && fill != "none" {
-                return true;
-            }
-        }
-
-        if let Some(style_value) = attributes.get("style") {
-            let style = style_value.to_string().to_ascii_lowercase();
-            for part in style.split(';') {
-                let part = part.trim();
-                if let Some(value) = part.strip_prefix("fill:") {
-                    let fill = value.trim();
-                    if !fill.is_empty() && fill != "none" {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        false
+        Self::svg_style_value(attributes, "fill")
+            .is_some_and(|fill| !fill.is_empty() && fill != "none")
+    }
+
+    /// Returns the lowercase value of a style property, where the inline `style` attribute takes precedence over presentation attributes.
+    fn svg_style_value(attributes: &Attributes, key: &str) -> Option<String> {
+        let from_style = attributes.get("style").and_then(|style| {
+            style.split(';').find_map(|part| {
+                let (name, value) = part.split_once(':')?;
+                (name.trim().eq_ignore_ascii_case(key)).then(|| value.trim().to_ascii_lowercase())
+            })
+        });
+        from_style.or_else(|| {
+            attributes
+                .get(key)
+                .map(|value| value.to_string().trim().to_ascii_lowercase())
+        })
+    }
+
+    /// Parses the `stroke` and `stroke-width` of a node. Values that are not colors or lengths (like `none` or gradients) are ignored.
+    fn parse_svg_stroke(
+        attributes: &Attributes,
+        transform: SvgTransform,
+        scale_to_cm: f32,
+    ) -> SvgStroke {
+        let color = Self::svg_style_value(attributes, "stroke")
+            .and_then(|stroke| ColorRgb::from_css(&stroke).ok());
+        let width_cm = Self::svg_style_value(attributes, "stroke-width")
+            .and_then(|width| width.trim_end_matches("px").trim().parse::<f32>().ok())
+            .map(|width| width * transform.determinant().abs().sqrt() * scale_to_cm);
+        SvgStroke { color, width_cm }
+    }
+
+    /// Whether a node and its children are not drawn: hidden nodes and definitions that are only drawn when referenced.
+    fn svg_node_is_hidden(tag: &str, attributes: &Attributes) -> bool {
+        matches!(
+            tag,
+            "defs" | "clipPath" | "mask" | "marker" | "pattern" | "symbol"
+        ) || Self::svg_style_value(attributes, "display").as_deref() == Some("none")
+            || Self::svg_style_value(attributes, "visibility").as_deref() == Some("hidden")
     }
 
     fn parse_svg_transform_attr(attributes: &Attributes) -> Option<SvgTransform> {
@@ -712,253 +1171,171 @@ impl Layer {
         }
     }
 
-    fn parse_svg_path_data(path_data: &str) -> Vec<Path> {
+    fn parse_svg_path_data(path_data: &str, tolerance: f32) -> Result<Vec<Path>> {
         let mut parsed_paths: Vec<Path> = Vec::new();
 
+        // an empty `current_points` means no subpath is being drawn
         let mut current_points: Vec<V2> = Vec::new();
-        let mut current_point: Option<V2> = None;
-        let mut subpath_start: Option<V2> = None;
+        let mut current_point = V2::zero();
+        let mut subpath_start = V2::zero();
+        // last control points, for the reflection of smooth curves
+        let mut last_cubic_control: Option<V2> = None;
+        let mut last_quadratic_control: Option<V2> = None;
 
-        let mut active_command = 'M';
-        let mut command_is_set = false;
-
-        let tokens = Self::tokenize_svg_path_data(path_data);
+        let tokens = Self::tokenize_svg_path_data(path_data)?;
         let mut token_index = 0;
 
         while token_index < tokens.len() {
-            if let SvgPathToken::Command(command) = tokens[token_index] {
-                active_command = command;
-                command_is_set = true;
-                token_index += 1;
-            } else if !command_is_set {
-                // TODO: Handle malformed path data where numbers appear before the first command.
-                break;
-            }
-
-            match active_command {
-                'M' | 'm' => {
-                    let mut is_first_pair = true;
-                    while let Some((x, y)) = Self::read_svg_pair(&tokens, &mut token_index) {
-                        let mut next_point = V2::new(x, y);
-                        if active_command == 'm' {
-                            next_point = current_point.unwrap_or(V2::zero()) + next_point;
-                        }
-
-                        if is_first_pair {
-                            if current_points.len() >= 2 {
-                                parsed_paths
-                                    .push(Path::new_from(std::mem::take(&mut current_points)));
-                            }
-                            current_points.push(next_point);
-                            subpath_start = Some(next_point);
-                            is_first_pair = false;
-                        } else {
-                            current_points.push(next_point);
-                        }
-                        current_point = Some(next_point);
-                    }
+            let command = match tokens[token_index] {
+                SvgPathToken::Command(command) => command,
+                SvgPathToken::Number(value) => {
+                    bail!("expected a path command, found number {}", value)
                 }
-                'L' | 'l' => {
-                    while let Some((x, y)) = Self::read_svg_pair(&tokens, &mut token_index) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
+            };
+            token_index += 1;
 
-                        let mut next_point = V2::new(x, y);
-                        if active_command == 'l' {
-                            next_point = current_point.unwrap_or(V2::zero()) + next_point;
-                        }
-                        current_points.push(next_point);
-                        current_point = Some(next_point);
+            if command == 'Z' || command == 'z' {
+                if !current_points.is_empty() {
+                    if current_points.last() != Some(&subpath_start) {
+                        current_points.push(subpath_start);
                     }
+                    parsed_paths.push(Path::new_from(std::mem::take(&mut current_points)));
                 }
-                'H' | 'h' => {
-                    while let Some(x) = Self::read_svg_number(&tokens, &mut token_index) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
+                current_point = subpath_start;
+                last_cubic_control = None;
+                last_quadratic_control = None;
+                continue;
+            }
 
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 'h' {
-                            V2::new(base.x + x, base.y)
-                        } else {
-                            V2::new(x, base.y)
-                        };
-                        current_points.push(next_point);
-                        current_point = Some(next_point);
-                    }
-                }
-                'V' | 'v' => {
-                    while let Some(y) = Self::read_svg_number(&tokens, &mut token_index) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
+            let kind = command.to_ascii_uppercase();
+            let num_args = match kind {
+                'H' | 'V' => 1,
+                'M' | 'L' | 'T' => 2,
+                'S' | 'Q' => 4,
+                'C' => 6,
+                'A' => 7,
+                _ => bail!("unknown path command '{}'", command),
+            };
+            let is_relative = command.is_ascii_lowercase();
 
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 'v' {
-                            V2::new(base.x, base.y + y)
-                        } else {
-                            V2::new(base.x, y)
-                        };
-                        current_points.push(next_point);
-                        current_point = Some(next_point);
+            let mut is_first_set = true;
+            while let Some(args) = Self::read_svg_args(&tokens, &mut token_index, num_args)? {
+                let base = if is_relative {
+                    current_point
+                } else {
+                    V2::zero()
+                };
+                let point_at = |index: usize| base + V2::new(args[index], args[index + 1]);
+
+                if kind == 'M' && is_first_set {
+                    if current_points.len() >= 2 {
+                        parsed_paths.push(Path::new_from(std::mem::take(&mut current_points)));
                     }
+                    current_points.clear();
+                    current_point = point_at(0);
+                    subpath_start = current_point;
+                    current_points.push(current_point);
+                    is_first_set = false;
+                    continue;
                 }
-                'Z' | 'z' => {
-                    if !current_points.is_empty() {
-                        if let Some(start) = subpath_start {
-                            if current_points.last() != Some(&start) {
-                                current_points.push(start);
-                            }
-                        }
+                is_first_set = false;
 
-                        if current_points.len() >= 2 {
-                            parsed_paths.push(Path::new_from(std::mem::take(&mut current_points)));
-                        } else {
-                            current_points.clear();
-                        }
-                        current_point = subpath_start;
-                        subpath_start = None;
-                    }
+                if current_points.is_empty() {
+                    subpath_start = current_point;
+                    current_points.push(current_point);
                 }
-                'C' | 'c' => {
-                    while let (Some(_x1), Some(_y1), Some(_x2), Some(_y2), Some(x), Some(y)) = (
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                    ) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
 
-                        // TODO: Sample bezier curves instead of approximating them as straight segments.
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 'c' {
-                            V2::new(base.x + x, base.y + y)
-                        } else {
-                            V2::new(x, y)
-                        };
+                let mut cubic_control = None;
+                let mut quadratic_control = None;
+                let next_point = match kind {
+                    'M' | 'L' => {
+                        let next_point = point_at(0);
                         current_points.push(next_point);
-                        current_point = Some(next_point);
+                        next_point
                     }
-                }
-                'S' | 's' => {
-                    while let (Some(_x2), Some(_y2), Some(x), Some(y)) = (
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                    ) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
-
-                        // TODO: Sample smooth cubic bezier curves instead of endpoint approximation.
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 's' {
-                            V2::new(base.x + x, base.y + y)
+                    'H' => {
+                        let x = if is_relative {
+                            current_point.x + args[0]
                         } else {
-                            V2::new(x, y)
+                            args[0]
                         };
+                        let next_point = V2::new(x, current_point.y);
                         current_points.push(next_point);
-                        current_point = Some(next_point);
+                        next_point
                     }
-                }
-                'Q' | 'q' => {
-                    while let (Some(_cx), Some(_cy), Some(x), Some(y)) = (
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                    ) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
-
-                        // TODO: Sample quadratic bezier curves instead of endpoint approximation.
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 'q' {
-                            V2::new(base.x + x, base.y + y)
+                    'V' => {
+                        let y = if is_relative {
+                            current_point.y + args[0]
                         } else {
-                            V2::new(x, y)
+                            args[0]
                         };
+                        let next_point = V2::new(current_point.x, y);
                         current_points.push(next_point);
-                        current_point = Some(next_point);
+                        next_point
                     }
-                }
-                'T' | 't' => {
-                    while let Some((x, y)) = Self::read_svg_pair(&tokens, &mut token_index) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
-                            current_point,
-                        );
-
-                        // TODO: Sample smooth quadratic bezier curves instead of endpoint approximation.
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 't' {
-                            V2::new(base.x + x, base.y + y)
+                    'C' | 'S' => {
+                        let (c1, c2, next_point) = if kind == 'C' {
+                            (point_at(0), point_at(2), point_at(4))
                         } else {
-                            V2::new(x, y)
+                            let reflected = last_cubic_control
+                                .map(|control| current_point * 2.0 - control)
+                                .unwrap_or(current_point);
+                            (reflected, point_at(0), point_at(2))
                         };
-                        current_points.push(next_point);
-                        current_point = Some(next_point);
-                    }
-                }
-                'A' | 'a' => {
-                    while let (
-                        Some(_rx),
-                        Some(_ry),
-                        Some(_x_axis_rotation),
-                        Some(_large_arc_flag),
-                        Some(_sweep_flag),
-                        Some(x),
-                        Some(y),
-                    ) = (
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                        Self::read_svg_number(&tokens, &mut token_index),
-                    ) {
-                        Self::ensure_svg_subpath_started(
-                            &mut current_points,
-                            &mut subpath_start,
+                        current_points.extend(flatten_cubic(
                             current_point,
-                        );
-
-                        // TODO: Sample elliptical arcs instead of endpoint approximation.
-                        let base = current_point.unwrap_or(V2::zero());
-                        let next_point = if active_command == 'a' {
-                            V2::new(base.x + x, base.y + y)
+                            c1,
+                            c2,
+                            next_point,
+                            tolerance,
+                        ));
+                        cubic_control = Some(c2);
+                        next_point
+                    }
+                    'Q' | 'T' => {
+                        let (control, next_point) = if kind == 'Q' {
+                            (point_at(0), point_at(2))
                         } else {
-                            V2::new(x, y)
+                            let reflected = last_quadratic_control
+                                .map(|control| current_point * 2.0 - control)
+                                .unwrap_or(current_point);
+                            (reflected, point_at(0))
                         };
-                        current_points.push(next_point);
-                        current_point = Some(next_point);
+                        current_points.extend(flatten_quadratic(
+                            current_point,
+                            control,
+                            next_point,
+                            tolerance,
+                        ));
+                        quadratic_control = Some(control);
+                        next_point
                     }
-                }
-                _ => {
-                    // TODO: Support additional SVG path commands.
-                    while Self::read_svg_number(&tokens, &mut token_index).is_some() {}
-                }
+                    'A' => {
+                        // flags are validated by the tokenizer
+                        let arc = SvgArc {
+                            radius: V2::new(args[0], args[1]),
+                            x_axis_rotation_degrees: args[2],
+                            large_arc: args[3] == 1.0,
+                            sweep: args[4] == 1.0,
+                        };
+                        let next_point = point_at(5);
+                        current_points.extend(flatten_arc(
+                            current_point,
+                            &arc,
+                            next_point,
+                            tolerance,
+                        ));
+                        next_point
+                    }
+                    _ => unreachable!(),
+                };
+                current_point = next_point;
+                last_cubic_control = cubic_control;
+                last_quadratic_control = quadratic_control;
+            }
+
+            if is_first_set {
+                bail!("path command '{}' without arguments", command);
             }
         }
 
@@ -966,17 +1343,22 @@ impl Layer {
             parsed_paths.push(Path::new_from(current_points));
         }
 
-        parsed_paths
+        Ok(parsed_paths)
     }
 
-    fn tokenize_svg_path_data(path_data: &str) -> Vec<SvgPathToken> {
+    fn tokenize_svg_path_data(path_data: &str) -> Result<Vec<SvgPathToken>> {
         let mut tokens = Vec::new();
         let mut chars = path_data.chars().peekable();
+        // arc flags are single characters and may be written without separators, as in `a1 1 0 011 1`
+        let mut is_arc = false;
+        let mut num_args = 0;
 
         while let Some(ch) = chars.peek().copied() {
             if ch.is_ascii_alphabetic() {
                 chars.next();
                 tokens.push(SvgPathToken::Command(ch));
+                is_arc = ch == 'A' || ch == 'a';
+                num_args = 0;
                 continue;
             }
 
@@ -985,11 +1367,34 @@ impl Layer {
                 continue;
             }
 
+            if is_arc && matches!(num_args % 7, 3 | 4) {
+                if ch != '0' && ch != '1' {
+                    bail!("invalid arc flag '{}' in path data", ch);
+                }
+                chars.next();
+                tokens.push(SvgPathToken::Number(if ch == '1' { 1.0 } else { 0.0 }));
+                num_args += 1;
+                continue;
+            }
+
+            if !(ch.is_ascii_digit() || ch == '.' || ch == '-' || ch == '+') {
+                bail!("invalid character '{}' in path data", ch);
+            }
+
             let mut number = String::new();
+            let mut seen_dot = false;
             let mut seen_exponent = false;
 
             while let Some(next_ch) = chars.peek().copied() {
-                if next_ch.is_ascii_digit() || next_ch == '.' {
+                if next_ch.is_ascii_digit() {
+                    number.push(next_ch);
+                    chars.next();
+                    continue;
+                }
+
+                // a second dot starts the next number, as in `0.5.5`
+                if next_ch == '.' && !seen_dot && !seen_exponent {
+                    seen_dot = true;
                     number.push(next_ch);
                     chars.next();
                     continue;
@@ -1014,53 +1419,35 @@ impl Layer {
                 break;
             }
 
-            if !number.is_empty() {
-                if let std::result::Result::Ok(value) = number.parse::<f32>() {
-                    tokens.push(SvgPathToken::Number(value));
-                } else {
-                    // TODO: Handle parse failures with richer diagnostics (line/column/path id).
-                }
-            } else {
-                chars.next();
-            }
+            let std::result::Result::Ok(value) = number.parse::<f32>() else {
+                bail!("invalid number '{}' in path data", number);
+            };
+            tokens.push(SvgPathToken::Number(value));
+            num_args += 1;
         }
 
-        tokens
-    }
-
-    fn ensure_svg_subpath_started(
-        current_points: &mut Vec<V2>,
-        subpath_start: &mut Option<V2>,
-        current_point: Option<V2>,
-    ) {
-        if current_points.is_empty() {
-            if let Some(start) = current_point {
-                current_points.push(start);
-                if subpath_start.is_none() {
-                    *subpath_start = Some(start);
+        Ok(tokens)
+    }
+
+    /// Reads the next `count` numbers. Returns `None` if the next token is a command or the data ended,
+    /// and an error if only some of the numbers are present.
+    fn read_svg_args(
+        tokens: &[SvgPathToken],
+        token_index: &mut usize,
+        count: usize,
+    ) -> Result<Option<[f32; 7]>> {
+        let mut args = [0.0; 7];
+        for (i, arg) in args.iter_mut().take(count).enumerate() {
+            match tokens.get(*token_index) {
+                Some(SvgPathToken::Number(value)) => {
+                    *arg = *value;
+                    *token_index += 1;
                 }
+                _ if i == 0 => return Ok(None),
+                _ => bail!("expected {} arguments, found {}", count, i),
             }
         }
-    }
-
-    fn read_svg_number(tokens: &[SvgPathToken], token_index: &mut usize) -> Option<f32> {
-        if *token_index >= tokens.len() {
-            return None;
-        }
-
-        match tokens[*token_index] {
-            SvgPathToken::Number(value) => {
-                *token_index += 1;
-                Some(value)
-            }
-            SvgPathToken::Command(_) => None,
-        }
-    }
-
-    fn read_svg_pair(tokens: &[SvgPathToken], token_index: &mut usize) -> Option<(f32, f32)> {
-        let x = Self::read_svg_number(tokens, token_index)?;
-        let y = Self::read_svg_number(tokens, token_index)?;
-        Some((x, y))
+        Ok(Some(args))
     }
 
     /// Returns a new `Layer` with [`Shape`]s that start/end at another [`Shape`]'s start/end combined into a single [`Path`].
@@ -1122,7 +1509,7 @@ impl Layer {
                     }
                     combineable.push(path.clone());
                 }
-                Shape::Circle(_) | Shape::Rect(_) => {
+                Shape::Circle(_) | Shape::Rect(_) | Shape::Compound(_) => {
                     noncombineable.push(shape.clone());
                 }
             }
@@ -1280,6 +1667,24 @@ impl Layer {
             .with_props(self.props.clone())
     }
 
+    /// Applies an arbitrary distortion `f` (for example noise displacement, fisheye or a polar mapping) to all [`Shape`]s in the `Layer` and its sublayers.
+    ///
+    /// All [`Shape`]s except [`Compound`]s are converted to [`Path`]s and resampled to a maximum segment length first. see [`Shape::warp`].
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Rect::new_shape(V2::zero(), V2::xy(2.0))]);
+    /// let warped = layer.warp(|p| p.distort_pow(V2::zero(), 2.0), 0.1, SampleSettings::default());
+    /// assert!(warped.len() == 1);
+    /// ```
+    pub fn warp<F>(&self, f: F, max_segment_length: f32, sample_settings: SampleSettings) -> Self
+    where
+        F: Fn(V2) -> V2 + Send + Sync,
+    {
+        self.map_recursive(|shape| shape.warp(&f, max_segment_length, sample_settings))
+    }
+
     /// Filter the [`Shape`]s in the `Layer` and its sublayers with a predicate function.
     pub fn filter_recursive_mut<F>(&mut self, predicate: F)
     where
@@ -1432,6 +1837,10 @@ impl Layer {
                 let start = rect.bl();
                 (start, start)
             }
+            Shape::Compound(compound) => {
+                let start = compound.outer().get_start().copied().unwrap_or(V2::zero());
+                (start, start)
+            }
         }
     }
 
@@ -1678,6 +2087,16 @@ impl Scale2D for Layer {
 
 impl Normalize for Layer {}
 
+impl Transform for Layer {
+    fn transform(&self, matrix: &TransformMatrix) -> Self {
+        self.map_recursive(|shape| shape.transform(matrix))
+    }
+
+    fn transform_mut(&mut self, matrix: &TransformMatrix) {
+        self.map_recursive_mut(|shape| shape.transform_mut(matrix));
+    }
+}
+
 impl Mirror for Layer {
     fn mirror_x(&self) -> Self {
         self.map_recursive(|shape| shape.mirror_x())
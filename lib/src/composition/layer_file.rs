@@ -0,0 +1,441 @@
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use bincode::{deserialize, serialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{traits::Scale, Circle, Compound, FillRule, Layer, Path, Rect, Shape, V2};
+
+use super::{ColorRgb, Inheritable, LayerProps, LayerPropsInheritable};
+
+/// Bytes every layer file starts with, see [`Layer::to_binary_with_header`].
+pub const LAYER_FILE_MAGIC: [u8; 6] = *b"PLOTL\0";
+/// Current version of the layer file format. Files without header are read as version `0`.
+pub const LAYER_FILE_VERSION: u16 = 1;
+/// Default precision of [`LayerFileCompression::DeltaCoordinates`], 1 µm.
+pub const LAYER_FILE_DELTA_PRECISION_CM: f32 = 0.0001;
+
+/// Unit of the coordinates stored in a layer file. Layers are converted from and to cm when writing and reading.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LayerFileUnits {
+    Centimeters,
+    Millimeters,
+    Inches,
+}
+
+impl LayerFileUnits {
+    pub fn units_per_cm(&self) -> f32 {
+        match self {
+            LayerFileUnits::Centimeters => 1.0,
+            LayerFileUnits::Millimeters => 10.0,
+            LayerFileUnits::Inches => 1.0 / 2.54,
+        }
+    }
+}
+
+/// How the shapes of a layer file are stored.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LayerFileCompression {
+    None,
+    /// Path points are rounded to `precision` (in file units) and stored as variable length differences to the previous point.
+    DeltaCoordinates {
+        precision: f32,
+    },
+}
+
+/// Header of a layer file, describing its content. See [`Layer::to_binary_with_header`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayerFileHeader {
+    /// Format version the file was read from. Files are always written with [`LAYER_FILE_VERSION`].
+    pub version: u16,
+    pub units: LayerFileUnits,
+    pub compression: LayerFileCompression,
+    /// Creation time in seconds since the unix epoch, `0` if unknown.
+    pub created_unix_seconds: u64,
+    /// Name and version of the program that wrote the file.
+    pub creator: String,
+    pub metadata: BTreeMap<String, String>,
+    /// Parameters the layer was generated with, by name.
+    pub params: Option<BTreeMap<String, String>>,
+    /// Random seed the layer was generated with.
+    pub seed: Option<u64>,
+}
+
+impl LayerFileHeader {
+    /// Returns a header created now by this version of `plottery_lib`, with cm units and without compression.
+    pub fn new() -> Self {
+        Self {
+            version: LAYER_FILE_VERSION,
+            units: LayerFileUnits::Centimeters,
+            compression: LayerFileCompression::None,
+            created_unix_seconds: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            creator: format!("plottery_lib {}", env!("CARGO_PKG_VERSION")),
+            metadata: BTreeMap::new(),
+            params: None,
+            seed: None,
+        }
+    }
+    pub fn with_units(mut self, units: LayerFileUnits) -> Self {
+        self.units = units;
+        self
+    }
+    pub fn with_compression(mut self, compression: LayerFileCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+    /// Enables [`LayerFileCompression::DeltaCoordinates`] with [`LAYER_FILE_DELTA_PRECISION_CM`] converted to the header's units.
+    pub fn with_delta_compression(self) -> Self {
+        let precision = LAYER_FILE_DELTA_PRECISION_CM * self.units.units_per_cm();
+        self.with_compression(LayerFileCompression::DeltaCoordinates { precision })
+    }
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+    pub fn with_params(mut self, params: BTreeMap<String, String>) -> Self {
+        self.params = Some(params);
+        self
+    }
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Header of files written before the format had a header.
+    fn legacy() -> Self {
+        Self {
+            version: 0,
+            created_unix_seconds: 0,
+            creator: String::new(),
+            ..Self::new()
+        }
+    }
+}
+
+impl Default for LayerFileHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layer {
+    /// Serializes the `Layer` with a [`LayerFileHeader`], see [`Layer::new_from_binary_with_header`].
+    ///
+    /// The result starts with [`LAYER_FILE_MAGIC`] and the little endian [`LAYER_FILE_VERSION`], followed by the header and the layer.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 2.0)])]);
+    /// let header = LayerFileHeader::new().with_seed(42);
+    /// let binary = layer.to_binary_with_header(&header).unwrap();
+    ///
+    /// let (read, read_header) = Layer::new_from_binary_with_header(&binary).unwrap();
+    /// assert_eq!(read.shapes, layer.shapes);
+    /// assert_eq!(read_header.seed, Some(42));
+    /// ```
+    pub fn to_binary_with_header(&self, header: &LayerFileHeader) -> Result<Vec<u8>> {
+        let header = LayerFileHeader {
+            version: LAYER_FILE_VERSION,
+            ..header.clone()
+        };
+        let scaled;
+        let layer = if header.units == LayerFileUnits::Centimeters {
+            self
+        } else {
+            scaled = self.scale(header.units.units_per_cm());
+            &scaled
+        };
+
+        let mut binary = LAYER_FILE_MAGIC.to_vec();
+        binary.extend(LAYER_FILE_VERSION.to_le_bytes());
+        binary.extend(serialize(&header)?);
+        match header.compression {
+            LayerFileCompression::None => binary.extend(serialize(layer)?),
+            LayerFileCompression::DeltaCoordinates { precision } => {
+                if precision.is_nan() || precision <= 0.0 {
+                    bail!("invalid delta compression precision {}", precision);
+                }
+                binary.extend(serialize(&DeltaLayer::new(layer, precision))?)
+            }
+        }
+        Ok(binary)
+    }
+
+    /// Deserializes a `Layer` and its [`LayerFileHeader`], see [`Layer::to_binary_with_header`].
+    ///
+    /// Data without [`LAYER_FILE_MAGIC`] is read as a plain serialized `Layer` of version `0`. Data from newer versions of the format returns an error.
+    pub fn new_from_binary_with_header(binary_data: &[u8]) -> Result<(Layer, LayerFileHeader)> {
+        let Some(data) = binary_data.strip_prefix(LAYER_FILE_MAGIC.as_slice()) else {
+            let layer: LayerV0 =
+                deserialize(binary_data).context("failed to read layer without header")?;
+            return Ok((layer.into(), LayerFileHeader::legacy()));
+        };
+        if data.len() < 2 {
+            bail!("layer file ends before its version");
+        }
+        let version = u16::from_le_bytes([data[0], data[1]]);
+        let data = &data[2..];
+
+        let (header, layer) = match version {
+            1 => {
+                let mut cursor = data;
+                let mut header: LayerFileHeader = bincode::deserialize_from(&mut cursor)
+                    .context("failed to read layer file header")?;
+                header.version = version;
+                let layer = match header.compression {
+                    LayerFileCompression::None => {
+                        deserialize::<Layer>(cursor).context("failed to read layer")?
+                    }
+                    LayerFileCompression::DeltaCoordinates { precision } => {
+                        deserialize::<DeltaLayer>(cursor)
+                            .context("failed to read layer")?
+                            .decode(precision)?
+                    }
+                };
+                (header, layer)
+            }
+            _ => bail!(
+                "unsupported layer file version {}, the newest supported version is {}",
+                version,
+                LAYER_FILE_VERSION
+            ),
+        };
+
+        let layer = if header.units == LayerFileUnits::Centimeters {
+            layer
+        } else {
+            layer.scale(1.0 / header.units.units_per_cm())
+        };
+        Ok((layer, header))
+    }
+}
+
+/// Layout of [`Layer`] in files without header (version `0`), written by plottery 0.10 and earlier.
+///
+/// These types are frozen, bincode depends on the exact order and types of all fields.
+#[derive(Deserialize)]
+struct LayerV0 {
+    shapes: Vec<ShapeV0>,
+    sublayers: Vec<LayerV0>,
+    props: LayerPropsV0,
+    props_inheritable: InheritableV0<LayerPropsInheritableV0>,
+}
+
+#[derive(Deserialize)]
+enum ShapeV0 {
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+    },
+    Rect {
+        bot_left: (f32, f32),
+        top_right: (f32, f32),
+    },
+    Path {
+        points: Vec<(f32, f32)>,
+    },
+}
+
+#[derive(Deserialize)]
+enum InheritableV0<T> {
+    Inherit,
+    Specified(T),
+}
+
+#[derive(Deserialize)]
+struct LayerPropsV0 {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LayerPropsInheritableV0 {
+    color: InheritableV0<(f32, f32, f32)>,
+    pen_width_cm: InheritableV0<f32>,
+}
+
+impl<T> InheritableV0<T> {
+    fn migrate<U: Clone>(self, f: impl FnOnce(T) -> U) -> Inheritable<U> {
+        match self {
+            InheritableV0::Inherit => Inheritable::Inherit,
+            InheritableV0::Specified(value) => Inheritable::Specified(f(value)),
+        }
+    }
+}
+
+impl From<LayerV0> for Layer {
+    fn from(layer: LayerV0) -> Self {
+        let point = |(x, y): (f32, f32)| V2::new(x, y);
+        let shapes = layer
+            .shapes
+            .into_iter()
+            .map(|shape| match shape {
+                ShapeV0::Circle { center, radius } => Circle::new_shape(point(center), radius),
+                ShapeV0::Rect {
+                    bot_left,
+                    top_right,
+                } => Rect::new_shape(point(bot_left), point(top_right)),
+                ShapeV0::Path { points } => {
+                    Path::new_shape_from(points.into_iter().map(point).collect())
+                }
+            })
+            .collect();
+        let sublayers = layer.sublayers.into_iter().map(Layer::from).collect();
+        let props_inheritable = layer.props_inheritable.migrate(|props| {
+            let mut migrated = LayerPropsInheritable::inherit_all();
+            migrated.color = props.color.migrate(|(r, g, b)| ColorRgb::new(r, g, b));
+            migrated.pen_width_cm = props.pen_width_cm.migrate(|width| width);
+            migrated
+        });
+        Layer::new_from_shapes_and_layers(shapes, sublayers)
+            .with_props(LayerProps {
+                name: layer.props.name,
+                ..LayerProps::default()
+            })
+            .with_props_inheritable(props_inheritable)
+    }
+}
+
+/// A [`Layer`] with delta encoded path points, see [`LayerFileCompression::DeltaCoordinates`].
+#[derive(Serialize, Deserialize)]
+struct DeltaLayer {
+    shapes: Vec<DeltaShape>,
+    sublayers: Vec<DeltaLayer>,
+    props: LayerProps,
+    props_inheritable: Inheritable<LayerPropsInheritable>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum DeltaShape {
+    Circle(Circle),
+    Rect(Rect),
+    Path(Vec<u8>),
+    Compound {
+        outer: Vec<u8>,
+        holes: Vec<Vec<u8>>,
+        fill_rule: FillRule,
+    },
+}
+
+impl DeltaLayer {
+    fn new(layer: &Layer, precision: f32) -> Self {
+        let encode = |path: &Path| encode_delta_points(path.get_points_ref(), precision);
+        Self {
+            shapes: layer
+                .iter()
+                .map(|shape| match shape {
+                    Shape::Circle(circle) => DeltaShape::Circle(*circle),
+                    Shape::Rect(rect) => DeltaShape::Rect(*rect),
+                    Shape::Path(path) => DeltaShape::Path(encode(path)),
+                    Shape::Compound(compound) => DeltaShape::Compound {
+                        outer: encode(compound.outer()),
+                        holes: compound.holes().iter().map(encode).collect(),
+                        fill_rule: compound.fill_rule(),
+                    },
+                })
+                .collect(),
+            sublayers: layer
+                .iter_sublayers()
+                .map(|sublayer| DeltaLayer::new(sublayer, precision))
+                .collect(),
+            props: layer.props.clone(),
+            props_inheritable: layer.props_inheritable.clone(),
+        }
+    }
+
+    fn decode(self, precision: f32) -> Result<Layer> {
+        let decode = |bytes: &[u8]| decode_delta_points(bytes, precision).map(Path::new_from);
+        let shapes = self
+            .shapes
+            .into_iter()
+            .map(|shape| {
+                Ok(match shape {
+                    DeltaShape::Circle(circle) => Shape::Circle(circle),
+                    DeltaShape::Rect(rect) => Shape::Rect(rect),
+                    DeltaShape::Path(bytes) => Shape::Path(decode(&bytes)?),
+                    DeltaShape::Compound {
+                        outer,
+                        holes,
+                        fill_rule,
+                    } => Shape::Compound(
+                        Compound::new(
+                            decode(&outer)?,
+                            holes
+                                .iter()
+                                .map(|hole| decode(hole))
+                                .collect::<Result<Vec<_>>>()?,
+                        )
+                        .with_fill_rule(fill_rule),
+                    ),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let sublayers = self
+            .sublayers
+            .into_iter()
+            .map(|sublayer| sublayer.decode(precision))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut layer = Layer::new_from_shapes_and_layers(shapes, sublayers);
+        layer.props = self.props;
+        layer.props_inheritable = self.props_inheritable;
+        Ok(layer)
+    }
+}
+
+/// Encodes points rounded to `precision` as zigzag varints of the differences between consecutive coordinates.
+fn encode_delta_points(points: &[V2], precision: f32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut previous = [0_i64; 2];
+    for point in points {
+        for (axis, value) in [point.x, point.y].into_iter().enumerate() {
+            let quantized = (value as f64 / precision as f64).round() as i64;
+            let delta = quantized.wrapping_sub(previous[axis]);
+            previous[axis] = quantized;
+
+            let mut zigzag = ((delta << 1) ^ (delta >> 63)) as u64;
+            while zigzag >= 0x80 {
+                bytes.push((zigzag as u8) | 0x80);
+                zigzag >>= 7;
+            }
+            bytes.push(zigzag as u8);
+        }
+    }
+    bytes
+}
+
+fn decode_delta_points(bytes: &[u8], precision: f32) -> Result<Vec<V2>> {
+    let mut values = Vec::new();
+    let mut previous = [0_i64; 2];
+    let mut zigzag: u64 = 0;
+    let mut shift = 0;
+    for byte in bytes {
+        if shift >= 64 {
+            bail!("delta encoded coordinate overflows");
+        }
+        zigzag |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        let axis = values.len() % 2;
+        previous[axis] = previous[axis].wrapping_add(delta);
+        values.push((previous[axis] as f64 * precision as f64) as f32);
+        zigzag = 0;
+        shift = 0;
+    }
+    if shift != 0 || values.len() % 2 != 0 {
+        bail!("delta encoded coordinates end unexpectedly");
+    }
+    Ok(values
+        .chunks_exact(2)
+        .map(|xy| V2::new(xy[0], xy[1]))
+        .collect())
+}
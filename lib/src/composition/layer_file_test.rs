@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod test_layer_file {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        Circle, ColorRgb, Compound, FillRule, Inheritable, Layer, LayerFileCompression,
+        LayerFileHeader, LayerFileUnits, LayerPropsInheritable, Path, Plottable, Rect, Shape,
+        LAYER_FILE_DELTA_PRECISION_CM, LAYER_FILE_MAGIC, LAYER_FILE_VERSION, V2,
+    };
+
+    fn example_layer() -> Layer {
+        let wiggle: Vec<V2> = (0..500)
+            .map(|i| V2::new(i as f32 * 0.013, (i as f32 * 0.1).sin() * 3.0 + 10.0))
+            .collect();
+        Layer::new_from_shapes_and_layers(
+            vec![
+                Path::new_shape_from(wiggle),
+                Circle::new_shape(V2::new(1.0, 2.0), 0.5),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            ],
+            vec![Layer::new_from(vec![Shape::Compound(
+                Compound::new(
+                    Path::new_from(vec![
+                        V2::new(0.0, 0.0),
+                        V2::new(4.0, 0.0),
+                        V2::new(4.0, 4.0),
+                    ]),
+                    vec![Path::new_from(vec![
+                        V2::new(1.0, 1.0),
+                        V2::new(2.0, 1.0),
+                        V2::new(2.0, 2.0),
+                    ])],
+                )
+                .with_fill_rule(FillRule::EvenOdd),
+            )])
+            .with_name("holes")
+            .with_color(ColorRgb::red())],
+        )
+    }
+
+    fn assert_shapes_close(a: &Layer, b: &Layer, tolerance: f32) {
+        let shapes_a: Vec<&Shape> = a.iter_flattened().collect();
+        let shapes_b: Vec<&Shape> = b.iter_flattened().collect();
+        assert_eq!(shapes_a.len(), shapes_b.len());
+        for (shape_a, shape_b) in shapes_a.iter().zip(shapes_b.iter()) {
+            let segments_a = shape_a.get_line_segments(crate::SampleSettings::default());
+            let segments_b = shape_b.get_line_segments(crate::SampleSettings::default());
+            assert_eq!(segments_a.len(), segments_b.len());
+            for (segment_a, segment_b) in segments_a.iter().zip(segments_b.iter()) {
+                assert!(segment_a.from.dist(segment_b.from) <= tolerance);
+                assert!(segment_a.to.dist(segment_b.to) <= tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let params = BTreeMap::from([("density".to_string(), "0.5".to_string())]);
+        let header = LayerFileHeader::new()
+            .with_params(params.clone())
+            .with_seed(7)
+            .with_metadata("title", "waves");
+        let layer = example_layer();
+        let binary = layer.to_binary_with_header(&header).unwrap();
+        assert!(binary.starts_with(&LAYER_FILE_MAGIC));
+
+        let (read, read_header) = Layer::new_from_binary_with_header(&binary).unwrap();
+        assert_eq!(read_header, header);
+        assert_eq!(read_header.version, LAYER_FILE_VERSION);
+        assert_eq!(read_header.params, Some(params));
+        assert!(read_header.creator.starts_with("plottery_lib "));
+        assert!(read_header.created_unix_seconds > 0);
+        assert_eq!(read.shapes, layer.shapes);
+        assert_eq!(read.sublayers[0].shapes, layer.sublayers[0].shapes);
+        assert_eq!(read.sublayers[0].props, layer.sublayers[0].props);
+        assert_eq!(
+            read.sublayers[0].props_inheritable,
+            layer.sublayers[0].props_inheritable
+        );
+    }
+
+    /// A layer written by `Layer::to_binary` of plottery_lib 0.10, before layer files had a header:
+    /// a root named `root` with pen width 0.1, a circle and a rect, and a red sublayer `sub` with one path.
+    const LAYER_V0_HEX: &str = "0200000000000000000000000000803f000000400000003f010000000000000000000000000000400000803f0100000000000000010000000000000002000000030000000000000000000000000000000000803f00000040000040400000803f000000000000000001030000000000000073756201000000010000000000803f000000000000000000000000010400000000000000726f6f74010000000000000001000000cdcccc3d";
+
+    #[test]
+    fn migrates_files_without_header() {
+        let legacy: Vec<u8> = (0..LAYER_V0_HEX.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&LAYER_V0_HEX[i..i + 2], 16).unwrap())
+            .collect();
+        let (read, header) = Layer::new_from_binary_with_header(&legacy).unwrap();
+        assert_eq!(header.version, 0);
+        assert_eq!(header.created_unix_seconds, 0);
+
+        assert_eq!(read.props.name.as_deref(), Some("root"));
+        assert_eq!(
+            read.shapes,
+            vec![
+                Circle::new_shape(V2::new(1.0, 2.0), 0.5),
+                Rect::new_shape(V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            ]
+        );
+        assert_eq!(
+            read.props_inheritable,
+            Inheritable::Specified(LayerPropsInheritable::inherit_all().with_pen_width_cm(0.1))
+        );
+
+        let sub = &read.sublayers[0];
+        assert_eq!(sub.props.name.as_deref(), Some("sub"));
+        assert_eq!(
+            sub.shapes,
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 2.0),
+                V2::new(3.0, 1.0),
+            ])]
+        );
+        assert_eq!(
+            sub.props_inheritable,
+            Inheritable::Specified(
+                LayerPropsInheritable::inherit_all().with_color(ColorRgb::red())
+            )
+        );
+        assert_eq!(Layer::new_from_binary(&legacy).unwrap().len_recursive(), 3);
+    }
+
+    #[test]
+    fn rejects_unknown_and_broken_files() {
+        let mut newer = LAYER_FILE_MAGIC.to_vec();
+        newer.extend((LAYER_FILE_VERSION + 1).to_le_bytes());
+        let error = Layer::new_from_binary(&newer).unwrap_err();
+        assert!(error.to_string().contains("unsupported layer file version"));
+
+        assert!(Layer::new_from_binary(&LAYER_FILE_MAGIC).is_err());
+
+        let binary = example_layer().to_binary().unwrap();
+        assert!(Layer::new_from_binary(&binary[..binary.len() / 2]).is_err());
+
+        let invalid_precision = LayerFileHeader::new()
+            .with_compression(LayerFileCompression::DeltaCoordinates { precision: 0.0 });
+        assert!(example_layer()
+            .to_binary_with_header(&invalid_precision)
+            .is_err());
+    }
+
+    #[test]
+    fn units() {
+        let layer = example_layer();
+        let header = LayerFileHeader::new().with_units(LayerFileUnits::Millimeters);
+        let binary = layer.to_binary_with_header(&header).unwrap();
+        let (read, read_header) = Layer::new_from_binary_with_header(&binary).unwrap();
+        assert_eq!(read_header.units, LayerFileUnits::Millimeters);
+        assert_shapes_close(&read, &layer, 0.00001);
+    }
+
+    #[test]
+    fn delta_compression() {
+        let layer = example_layer();
+        let uncompressed = layer.to_binary().unwrap();
+        for units in [LayerFileUnits::Centimeters, LayerFileUnits::Inches] {
+            let header = LayerFileHeader::new()
+                .with_units(units)
+                .with_delta_compression();
+            let compressed = layer.to_binary_with_header(&header).unwrap();
+            assert!((compressed.len() as f32) < uncompressed.len() as f32 * 0.6);
+
+            let (read, read_header) = Layer::new_from_binary_with_header(&compressed).unwrap();
+            assert_eq!(read_header.compression, header.compression);
+            assert_shapes_close(&read, &layer, LAYER_FILE_DELTA_PRECISION_CM);
+            let Shape::Compound(compound) = &read.sublayers[0].shapes[0] else {
+                panic!("expected a compound");
+            };
+            assert_eq!(compound.fill_rule(), FillRule::EvenOdd);
+            assert_eq!(compound.holes().len(), 1);
+            assert_eq!(read.sublayers[0].props.name.as_deref(), Some("holes"));
+        }
+    }
+
+    #[test]
+    fn files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.plotl");
+        let layer = example_layer();
+        layer
+            .write_file_with_header(&path, &LayerFileHeader::new().with_seed(3))
+            .unwrap();
+        let (read, header) = Layer::new_from_file_with_header(&path).unwrap();
+        assert_eq!(header.seed, Some(3));
+        assert_eq!(read.shapes, layer.shapes);
+
+        layer.write_file(&path).unwrap();
+        assert_eq!(Layer::new_from_file(&path).unwrap().shapes, layer.shapes);
+    }
+}
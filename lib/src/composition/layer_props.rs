@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use super::ColorRgb;
@@ -34,28 +36,98 @@ where
 pub struct LayerPropsInheritable {
     pub color: Inheritable<ColorRgb>,
     pub pen_width_cm: Inheritable<f32>,
+    /// Id of the pen or tool the layer is drawn with.
+    pub pen_id: Inheritable<u32>,
+    /// Factor applied to the drawing speed. Plotting uses [`LayerPropsInheritable::plot_speed_factor`], which clamps
+    /// it to at least [`MIN_SPEED_FACTOR`].
+    pub speed_factor: Inheritable<f32>,
+    /// Additional head travel beyond touching the paper, to press the pen down harder.
+    pub pen_down_depth_cm: Inheritable<f32>,
+    /// Number of times every [`crate::Shape`] is drawn. Plotting uses [`LayerPropsInheritable::plot_passes`], which
+    /// draws layers with `0` passes once.
+    pub passes: Inheritable<u32>,
+    /// Layers with a lower priority are plotted first. Layers with the same priority keep their order.
+    pub plot_priority: Inheritable<i32>,
 }
 
+/// The lowest speed factor used for plotting, see [`LayerPropsInheritable::plot_speed_factor`].
+pub const MIN_SPEED_FACTOR: f32 = 0.01;
+
 impl LayerPropsInheritable {
     pub fn inherit_all() -> Self {
         Self {
             color: Inheritable::Inherit,
             pen_width_cm: Inheritable::Inherit,
+            pen_id: Inheritable::Inherit,
+            speed_factor: Inheritable::Inherit,
+            pen_down_depth_cm: Inheritable::Inherit,
+            passes: Inheritable::Inherit,
+            plot_priority: Inheritable::Inherit,
         }
     }
 
     pub fn with_color(&self, color: ColorRgb) -> Self {
         Self {
             color: Inheritable::Specified(color),
-            pen_width_cm: self.pen_width_cm,
+            ..self.clone()
         }
     }
     pub fn with_pen_width_cm(&self, pen_width_cm: f32) -> Self {
         Self {
-            color: self.color,
             pen_width_cm: Inheritable::Specified(pen_width_cm),
+            ..self.clone()
+        }
+    }
+    pub fn with_pen_id(&self, pen_id: u32) -> Self {
+        Self {
+            pen_id: Inheritable::Specified(pen_id),
+            ..self.clone()
+        }
+    }
+    pub fn with_speed_factor(&self, speed_factor: f32) -> Self {
+        Self {
+            speed_factor: Inheritable::Specified(speed_factor),
+            ..self.clone()
         }
     }
+    pub fn with_pen_down_depth_cm(&self, pen_down_depth_cm: f32) -> Self {
+        Self {
+            pen_down_depth_cm: Inheritable::Specified(pen_down_depth_cm),
+            ..self.clone()
+        }
+    }
+    pub fn with_passes(&self, passes: u32) -> Self {
+        Self {
+            passes: Inheritable::Specified(passes),
+            ..self.clone()
+        }
+    }
+    pub fn with_plot_priority(&self, plot_priority: i32) -> Self {
+        Self {
+            plot_priority: Inheritable::Specified(plot_priority),
+            ..self.clone()
+        }
+    }
+}
+
+impl LayerPropsInheritable {
+    /// The speed factor all plotting and export code uses. Values below [`MIN_SPEED_FACTOR`] and `NaN` are clamped to
+    /// it, so a layer never gets a zero or negative speed.
+    ///
+    /// ### Panics
+    /// If the speed factor is [`Inheritable::Inherit`], like [`Inheritable::unwrap`].
+    pub fn plot_speed_factor(&self) -> f32 {
+        self.speed_factor.unwrap().max(MIN_SPEED_FACTOR)
+    }
+
+    /// The number of passes all plotting and export code uses. Layers with `0` passes are drawn once instead of
+    /// being skipped silently.
+    ///
+    /// ### Panics
+    /// If the passes are [`Inheritable::Inherit`], like [`Inheritable::unwrap`].
+    pub fn plot_passes(&self) -> u32 {
+        self.passes.unwrap().max(1)
+    }
 }
 
 impl LayerPropsInheritable {
@@ -65,6 +137,15 @@ impl LayerPropsInheritable {
             Inheritable::Specified(child_props) => Self {
                 color: self.color.overwrite_with(&child_props.color),
                 pen_width_cm: self.pen_width_cm.overwrite_with(&child_props.pen_width_cm),
+                pen_id: self.pen_id.overwrite_with(&child_props.pen_id),
+                speed_factor: self.speed_factor.overwrite_with(&child_props.speed_factor),
+                pen_down_depth_cm: self
+                    .pen_down_depth_cm
+                    .overwrite_with(&child_props.pen_down_depth_cm),
+                passes: self.passes.overwrite_with(&child_props.passes),
+                plot_priority: self
+                    .plot_priority
+                    .overwrite_with(&child_props.plot_priority),
             },
         }
     }
@@ -75,6 +156,11 @@ impl Default for LayerPropsInheritable {
         Self {
             color: Inheritable::Specified(ColorRgb::black()),
             pen_width_cm: Inheritable::Specified(0.05),
+            pen_id: Inheritable::Specified(0),
+            speed_factor: Inheritable::Specified(1.0),
+            pen_down_depth_cm: Inheritable::Specified(0.0),
+            passes: Inheritable::Specified(1),
+            plot_priority: Inheritable::Specified(0),
         }
     }
 }
@@ -82,12 +168,34 @@ impl Default for LayerPropsInheritable {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct LayerProps {
     pub name: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl LayerProps {
     pub fn with_name(&self, name: &str) -> Self {
         Self {
             name: Some(name.to_string()),
+            ..self.clone()
+        }
+    }
+    pub fn with_tag(&self, tag: &str) -> Self {
+        let mut props = self.clone();
+        if !props.has_tag(tag) {
+            props.tags.push(tag.to_string());
         }
+        props
+    }
+    pub fn with_metadata(&self, key: &str, value: &str) -> Self {
+        let mut props = self.clone();
+        props.metadata.insert(key.to_string(), value.to_string());
+        props
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
     }
 }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test_layer_props {
-    use crate::Inheritable;
+    use crate::{ColorRgb, Inheritable, Layer, LayerProps, LayerPropsInheritable};
 
     #[test]
     fn overwrite_with() {
@@ -9,4 +9,48 @@ mod test_layer_props {
 
         assert_eq!(i.overwrite_with(&i2).unwrap(), 5);
     }
+
+    #[test]
+    fn tags_and_metadata() {
+        let props = LayerProps::default()
+            .with_tag("hatch")
+            .with_tag("hatch")
+            .with_metadata("seed", "42")
+            .with_name("background");
+
+        assert_eq!(props.name.as_deref(), Some("background"));
+        assert_eq!(props.tags, vec!["hatch".to_string()]);
+        assert!(props.has_tag("hatch"));
+        assert!(!props.has_tag("outline"));
+        assert_eq!(props.get_metadata("seed"), Some("42"));
+        assert_eq!(props.get_metadata("missing"), None);
+    }
+
+    #[test]
+    fn plot_props_inherit() {
+        let red = ColorRgb::new(1.0, 0.0, 0.0);
+        let child = Layer::new().with_passes(3).with_pen_id(2);
+        let parent = Layer::new_from_shapes_and_layers(vec![], vec![child])
+            .with_color(red)
+            .with_speed_factor(0.5)
+            .with_pen_id(1);
+
+        // chained helpers keep previously specified values
+        let Inheritable::Specified(parent_props) = &parent.props_inheritable else {
+            panic!("expected specified props");
+        };
+        assert_eq!(parent_props.color.unwrap(), red);
+        assert_eq!(parent_props.speed_factor.unwrap(), 0.5);
+        assert_eq!(parent_props.passes, Inheritable::Inherit);
+
+        let resolved = LayerPropsInheritable::default()
+            .overwrite_with(&parent.props_inheritable)
+            .overwrite_with(&parent.sublayers[0].props_inheritable);
+        assert_eq!(resolved.color.unwrap(), red);
+        assert_eq!(resolved.speed_factor.unwrap(), 0.5);
+        assert_eq!(resolved.pen_id.unwrap(), 2);
+        assert_eq!(resolved.passes.unwrap(), 3);
+        assert_eq!(resolved.pen_down_depth_cm.unwrap(), 0.0);
+        assert_eq!(resolved.plot_priority.unwrap(), 0);
+    }
 }
@@ -8,8 +8,8 @@ mod test_layer {
 
     use crate::{
         traits::{normalize::Alignment, Translate},
-        Angle, BoundingBox, Circle, FloatInterpolation, Layer, Normalize, Path, Plottable, Rect,
-        Rotate, SampleSettings, Shape, ToAngle, LARGE_EPSILON, V2,
+        Angle, BoundingBox, Circle, ColorRgb, FloatInterpolation, Layer, Normalize, Path,
+        Plottable, Rect, Rotate, SampleSettings, Shape, ToAngle, LARGE_EPSILON, V2,
     };
 
     #[test]
@@ -120,14 +120,14 @@ mod test_layer {
         let layer: Layer = shapes.into();
 
         assert_eq!(layer.len(), 3);
-        assert!(matches!(layer.iter().nth(0), Some(Shape::Path(_))));
+        assert!(matches!(layer.iter().next(), Some(Shape::Path(_))));
         assert!(matches!(layer.iter().nth(1), Some(Shape::Circle(_))));
         assert!(matches!(layer.iter().nth(2), Some(Shape::Rect(_))));
     }
 
     #[test]
     fn from_vec_ref_paths_into_layer() {
-        let paths = vec![
+        let paths = [
             Path::new_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 1.0)]),
             Path::new_from(vec![V2::new(2.0, 2.0), V2::new(3.0, 3.0)]),
         ];
@@ -140,7 +140,7 @@ mod test_layer {
 
     #[test]
     fn from_vec_ref_circles_into_layer() {
-        let circles = vec![
+        let circles = [
             Circle::new(V2::new(0.0, 0.0), 1.0),
             Circle::new(V2::new(2.0, 2.0), 1.5),
         ];
@@ -153,7 +153,7 @@ mod test_layer {
 
     #[test]
     fn from_vec_ref_rects_into_layer() {
-        let rects = vec![
+        let rects = [
             Rect::new(V2::new(0.0, 0.0), V2::new(1.0, 1.0)),
             Rect::new(V2::new(2.0, 2.0), V2::new(4.0, 5.0)),
         ];
@@ -166,7 +166,7 @@ mod test_layer {
 
     #[test]
     fn from_vec_ref_shapes_into_layer() {
-        let shapes = vec![
+        let shapes = [
             Shape::from(Path::new_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 1.0)])),
             Shape::from(Circle::new(V2::new(2.0, 2.0), 1.5)),
             Shape::from(Rect::new(V2::new(3.0, 3.0), V2::new(5.0, 6.0))),
@@ -175,7 +175,7 @@ mod test_layer {
         let layer: Layer = shapes.iter().collect::<Vec<_>>().into();
 
         assert_eq!(layer.len(), 3);
-        assert!(matches!(layer.iter().nth(0), Some(Shape::Path(_))));
+        assert!(matches!(layer.iter().next(), Some(Shape::Path(_))));
         assert!(matches!(layer.iter().nth(1), Some(Shape::Circle(_))));
         assert!(matches!(layer.iter().nth(2), Some(Shape::Rect(_))));
     }
@@ -537,6 +537,294 @@ mod test_layer {
         assert!((points[1].x - 2.0).abs() < 1e-5);
     }
 
+    #[test]
+    fn new_from_svg_circle_ellipse_rect() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_basic_shapes.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <circle cx='10' cy='10' r='5' />
+            <rect x='20' y='5' width='10' height='5' transform='scale(2)' />
+            <ellipse cx='50' cy='50' rx='10' ry='5' />
+            <rect x='0' y='60' width='20' height='10' rx='2' />
+            <circle r='0' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+        assert_eq!(imported.len(), 4);
+
+        // y is mirrored by the bottom of the bounding box, at y = 70
+        let Shape::Circle(circle) = &imported.shapes[0] else {
+            panic!("Expected imported circle to be Circle");
+        };
+        assert_eq!(circle.center, V2::new(10.0, 60.0));
+        assert_eq!(circle.radius, 5.0);
+
+        let Shape::Rect(rect) = &imported.shapes[1] else {
+            panic!("Expected imported rect to be Rect");
+        };
+        assert_eq!(rect.bl(), V2::new(40.0, 50.0));
+        assert_eq!(rect.tr(), V2::new(60.0, 60.0));
+
+        let Shape::Path(ellipse) = &imported.shapes[2] else {
+            panic!("Expected imported ellipse to be Path");
+        };
+        let ellipse_bounds = ellipse.bounding_box().unwrap();
+        assert!(ellipse_bounds.bl().dist(V2::new(40.0, 15.0)) < 0.01);
+        assert!(ellipse_bounds.tr().dist(V2::new(60.0, 25.0)) < 0.01);
+        assert_eq!(ellipse.get_start(), ellipse.get_end());
+
+        let Shape::Path(rounded) = &imported.shapes[3] else {
+            panic!("Expected imported rounded rect to be Path");
+        };
+        let rounded_bounds = rounded.bounding_box().unwrap();
+        assert!(rounded_bounds.bl().dist(V2::new(0.0, 0.0)) < 0.001);
+        assert!(rounded_bounds.tr().dist(V2::new(20.0, 10.0)) < 0.001);
+        assert_eq!(rounded.get_start(), rounded.get_end());
+        // the corners are cut off by the rounding
+        let corner_dist = rounded
+            .get_points_ref()
+            .iter()
+            .map(|point| point.dist(V2::new(0.0, 0.0)))
+            .fold(f32::MAX, f32::min);
+        assert!((corner_dist - (2.0 * 2.0_f32.sqrt() - 2.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn new_from_svg_compact_arc_flags() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_compact_arc_flags.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <path d='M 0 50 a 50 50 0 0 1 100 0' />
+            <path d='M0 50a50 50 0 01100 0' />
+            <path d='M0 50a50 50 0 0,1,100,0' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert_eq!(imported.shapes[1], imported.shapes[0]);
+        assert_eq!(imported.shapes[2], imported.shapes[0]);
+
+        let invalid_path = temp_dir.path().join("test_import_invalid_arc_flag.svg");
+        let invalid = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <path d='M 0 50 a 50 50 0 2 1 100 0' />
+        </svg>"#;
+        std::fs::write(&invalid_path, invalid).unwrap();
+        assert!(Layer::new_from_svg(&invalid_path).is_err());
+    }
+
+    #[test]
+    fn new_from_svg_curves_within_tolerance() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_curve_tolerance.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <path d='M 0 50 A 50 50 0 0 1 100 50' />
+            <path d='M 0 0 Q 50 100 100 0' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let tolerance = 0.01;
+        let imported = Layer::new_from_svg_with_tolerance(&svg_path, tolerance).unwrap();
+        assert_eq!(imported.len(), 2);
+
+        let Shape::Path(arc) = &imported.shapes[0] else {
+            panic!("Expected imported arc to be Path");
+        };
+        let arc_center = arc.get_start().unwrap().lerp(*arc.get_end().unwrap(), 0.5);
+        for point in arc.get_points_ref() {
+            assert!((point.dist(arc_center) - 50.0).abs() < 0.001);
+        }
+        // the chords stay within the tolerance
+        for segment in arc.get_points_ref().windows(2) {
+            let mid = segment[0].lerp(segment[1], 0.5);
+            assert!(50.0 - mid.dist(arc_center) <= tolerance);
+        }
+
+        let Shape::Path(parabola) = &imported.shapes[1] else {
+            panic!("Expected imported quadratic curve to be Path");
+        };
+        assert!(parabola.get_points_ref().len() > 10);
+        let apex = parabola
+            .get_points_ref()
+            .iter()
+            .map(|point| point.y)
+            .fold(f32::MAX, f32::min);
+        assert!((apex - (imported.bounding_box().unwrap().tr().y - 50.0)).abs() < tolerance);
+    }
+
+    #[test]
+    fn new_from_svg_smooth_curves_reflect_controls() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_smooth.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <path d='M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0' />
+            <path d='M 0 0 C 0 10 10 10 10 0 C 10 -10 20 -10 20 0' />
+            <path d='m 0 0 q 5 10 10 0 t 10 0' />
+            <path d='M 0 0 Q 5 10 10 0 Q 15 -10 20 0' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+        let points = |i: usize| imported.shapes[i].get_points(SampleSettings::default());
+        assert_eq!(points(0), points(1));
+        assert_eq!(points(2), points(3));
+    }
+
+    #[test]
+    fn new_from_svg_malformed_nodes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_malformed.svg");
+        let import = |node: &str| {
+            let svg = format!(
+                "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>{}</svg>",
+                node
+            );
+            std::fs::write(&svg_path, svg).unwrap();
+            Layer::new_from_svg(&svg_path)
+        };
+
+        assert!(import("<path d='M 0 0 L 10 10' />").is_ok());
+        assert!(import("<path id='broken' />")
+            .unwrap_err()
+            .to_string()
+            .contains("broken"));
+        assert!(import("<path d='10 10 L 20 20' />").is_err());
+        assert!(import("<path d='M 0 0 L 10' />").is_err());
+        assert!(import("<path d='M 0 0 C 1 1 2 2' />").is_err());
+        assert!(import("<path d='M 0 0 X 1 1' />").is_err());
+        assert!(import("<path d='M 0 0 L' />").is_err());
+        assert!(import("<path d='M 0 0 A 5 5 0 2 1 10 10' />").is_err());
+        assert!(import("<path d='M 0 0 L 1 1 # 2' />").is_err());
+        assert!(import("<circle cx='5' cy='5' r='-1' />").is_err());
+        assert!(import("<circle cx='five' cy='5' r='1' />").is_err());
+        assert!(import("<rect width='10' height='-2' />").is_err());
+        assert!(import("<ellipse rx='a' />").is_err());
+        assert!(import("<polyline points='0,0 1,1 2' />").is_err());
+        assert!(import("<line x1='1' y1='1' x2='1cm' />").is_err());
+    }
+
+    #[test]
+    fn new_from_svg_compact_path_numbers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_compact.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+            <path d='M.5.5L1-1.5e1h2' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+        let Shape::Path(path) = &imported.shapes[0] else {
+            panic!("Expected imported shape to be Path");
+        };
+        // mirrored at y = 0.5
+        assert_eq!(
+            path.get_points_ref(),
+            &vec![V2::new(0.5, 0.0), V2::new(1.0, 15.5), V2::new(3.0, 15.5)]
+        );
+    }
+
+    #[test]
+    fn new_from_svg_round_trip_structure_and_pens() {
+        let hatch = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(1.0, 1.0),
+            V2::new(4.0, 1.0),
+        ])])
+        .with_name("hatch")
+        .with_color(ColorRgb::red())
+        .with_pen_width_cm(0.1);
+        let outline = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(5.0, 0.0),
+                V2::new(5.0, 3.0),
+            ])],
+            vec![hatch],
+        )
+        .with_name("outline")
+        .with_color(ColorRgb::blue());
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 5.0),
+                V2::new(2.0, 5.0),
+            ])],
+            vec![outline],
+        )
+        .with_name("root");
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_round_trip.svg");
+        l.write_svg(svg_path.clone(), 1.0).unwrap();
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+
+        assert_eq!(imported.sublayer_paths(), l.sublayer_paths());
+        assert_eq!(imported.props.name, l.props.name);
+        let original_layers = l.layers_in_plot_order();
+        let imported_layers = imported.layers_in_plot_order();
+        assert_eq!(imported_layers.len(), original_layers.len());
+        for ((original, original_props), (imported, imported_props)) in
+            original_layers.iter().zip(imported_layers.iter())
+        {
+            assert_eq!(imported.len(), original.len());
+            assert_eq!(imported_props.color, original_props.color);
+            assert_eq!(imported_props.pen_width_cm, original_props.pen_width_cm);
+            assert_eq!(
+                imported.shapes[0].get_points(SampleSettings::default()),
+                original.shapes[0].get_points(SampleSettings::default())
+            );
+        }
+    }
+
+    #[test]
+    fn new_from_svg_inkscape_layers_and_styles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let svg_path = temp_dir.path().join("test_import_inkscape.svg");
+        let svg = r#"<svg xmlns='http://www.w3.org/2000/svg' xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape' viewBox='0 0 100 100'>
+            <defs>
+                <path id='template' d='M 0 0 L 100 100' />
+            </defs>
+            <g id='layer1' inkscape:groupmode='layer' inkscape:label='Background' style='stroke:#00ff00;stroke-width:2'>
+                <path d='M 0 0 L 10 0' />
+                <path d='M 0 10 L 10 10' stroke='red' />
+                <path d='M 0 20 L 10 20' />
+                <g id='details' transform='scale(2)' stroke-width='0.5'>
+                    <path d='M 0 5 L 5 5' />
+                </g>
+            </g>
+            <g id='hidden' style='display:none'>
+                <path d='M 0 30 L 10 30' />
+            </g>
+            <path d='M 0 40 L 10 40' visibility='hidden' />
+            <path d='M 0 50 L 10 50' style='stroke: rgb(0, 0, 255)' stroke='red' />
+        </svg>"#;
+        std::fs::write(&svg_path, svg).unwrap();
+
+        let imported = Layer::new_from_svg(&svg_path).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported.len_recursive(), 5);
+        assert_eq!(
+            imported.sublayer_paths(),
+            vec!["Background", "Background/", "Background/details"]
+        );
+
+        let props: Vec<_> = imported
+            .layers_in_plot_order()
+            .into_iter()
+            .map(|(_, props)| props)
+            .collect();
+        assert_eq!(props[0].color.unwrap(), ColorRgb::blue());
+        assert_eq!(props[1].color.unwrap(), ColorRgb::green());
+        assert_eq!(props[1].pen_width_cm.unwrap(), 2.0);
+        assert_eq!(props[2].color.unwrap(), ColorRgb::red());
+        assert_eq!(props[3].color.unwrap(), ColorRgb::green());
+        // stroke widths are scaled with the transform
+        assert_eq!(props[3].pen_width_cm.unwrap(), 1.0);
+
+        let background = imported.get_path("Background").unwrap();
+        assert_eq!(background.len(), 2);
+        assert_eq!(background.sublayers[0].len(), 1);
+    }
+
     #[test]
     fn translate() {
         let mut l = Layer::new();
@@ -916,4 +1204,54 @@ mod test_layer {
         assert_eq!(l.shapes[1], o2.shapes[2]);
         assert_eq!(l.shapes[2], o2.shapes[1]);
     }
+
+    #[test]
+    fn layers_in_plot_order() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![
+                Layer::new().with_name("a").with_plot_priority(1),
+                Layer::new_from_shapes_and_layers(
+                    vec![],
+                    vec![
+                        Layer::new().with_name("c"),
+                        Layer::new().with_name("d").with_plot_priority(-1),
+                    ],
+                )
+                .with_name("b"),
+                Layer::new().with_name("e"),
+            ],
+        )
+        .with_name("root");
+
+        let names: Vec<_> = l
+            .layers_in_plot_order()
+            .into_iter()
+            .map(|(layer, _)| layer.props.name.clone().unwrap())
+            .collect();
+        assert_eq!(names, vec!["d", "root", "b", "c", "e", "a"]);
+    }
+
+    #[test]
+    fn warp() {
+        let sublayer = Layer::new_from(vec![Circle::new_shape(V2::xy(1.0), 0.5)]).with_name("sub");
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(2.0, 0.0),
+            ])],
+            vec![sublayer],
+        );
+        let warped = l.warp(|p| V2::new(p.x, p.x * p.x), 0.1, SampleSettings::default());
+
+        assert_eq!(warped.len_recursive(), 2);
+        assert_eq!(warped.sublayers[0].props.name, Some("sub".to_string()));
+        assert!(warped.iter_flattened().all(|s| matches!(s, Shape::Path(_))));
+
+        let points = warped.shapes[0].get_points(SampleSettings::default());
+        assert_eq!(points.len(), 21);
+        for point in points {
+            assert!((point.y - point.x * point.x).abs() < LARGE_EPSILON);
+        }
+    }
 }
@@ -0,0 +1,247 @@
+use crate::{Alignment, BoundingBox, Layer, Rect, Translate, LARGE_EPSILON, V2};
+
+/// The axis along which [`Layer::stack_sublayers`] and [`Layer::distribute_sublayers`] arrange sublayers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutDirection {
+    /// Left to right.
+    Horizontal,
+    /// Top to bottom.
+    Vertical,
+}
+
+impl Layer {
+    /// Returns the bounding box of all sublayers, ignoring the layer's own shapes.
+    pub fn sublayers_bounding_box(&self) -> Option<Rect> {
+        self.iter_sublayers()
+            .filter_map(|sublayer| sublayer.bounding_box())
+            .reduce(|a, b| Rect::new(a.bl().min(b.bl()), a.tr().max(b.tr())))
+    }
+
+    /// Moves the sublayers so their bounding boxes line up with an edge or the center of `target`.
+    ///
+    /// `Left` and `Right` only move sublayers horizontally, `Top` and `Bottom` only vertically, and `Center` centers them on
+    /// both axes. `target` defaults to [`Layer::sublayers_bounding_box`]. Empty sublayers are not moved.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let a = Layer::new_from(vec![Rect::new_shape(V2::new(0.0, 0.0), V2::new(1.0, 1.0))]);
+    /// let b = Layer::new_from(vec![Rect::new_shape(V2::new(3.0, 2.0), V2::new(4.0, 5.0))]);
+    /// let layer = Layer::new_from_shapes_and_layers(vec![], vec![a, b]).align_sublayers(Alignment::Top, None);
+    /// assert_eq!(layer.sublayers[0].bounding_box().unwrap().tr(), V2::new(1.0, 5.0));
+    /// ```
+    pub fn align_sublayers(&self, alignment: Alignment, target: Option<Rect>) -> Self {
+        let mut layer = self.clone();
+        layer.align_sublayers_mut(alignment, target);
+        layer
+    }
+
+    /// see [`Layer::align_sublayers`].
+    pub fn align_sublayers_mut(&mut self, alignment: Alignment, target: Option<Rect>) {
+        let Some(target) = target.or_else(|| self.sublayers_bounding_box()) else {
+            return;
+        };
+        for sublayer in self.sublayers.iter_mut() {
+            let Some(bounds) = sublayer.bounding_box() else {
+                continue;
+            };
+            let offset = match alignment {
+                Alignment::Left => V2::new(target.bl().x - bounds.bl().x, 0.0),
+                Alignment::Right => V2::new(target.tr().x - bounds.tr().x, 0.0),
+                Alignment::Bottom => V2::new(0.0, target.bl().y - bounds.bl().y),
+                Alignment::Top => V2::new(0.0, target.tr().y - bounds.tr().y),
+                Alignment::Center => target.center() - bounds.center(),
+            };
+            sublayer.translate_mut(offset);
+        }
+    }
+
+    /// Moves the sublayers along `direction` so the gaps between their bounding boxes are equal.
+    ///
+    /// The outermost sublayers keep their positions, the ones in between are spread out in the order of their positions.
+    pub fn distribute_sublayers(&self, direction: LayoutDirection) -> Self {
+        let mut layer = self.clone();
+        layer.distribute_sublayers_mut(direction);
+        layer
+    }
+
+    /// see [`Layer::distribute_sublayers`].
+    pub fn distribute_sublayers_mut(&mut self, direction: LayoutDirection) {
+        let axis = |v: V2| match direction {
+            LayoutDirection::Horizontal => v.x,
+            LayoutDirection::Vertical => v.y,
+        };
+        let mut items: Vec<(usize, Rect)> = self
+            .iter_sublayers()
+            .enumerate()
+            .filter_map(|(i, sublayer)| sublayer.bounding_box().map(|bounds| (i, bounds)))
+            .collect();
+        if items.len() < 3 {
+            return;
+        }
+        items.sort_by(|(_, a), (_, b)| axis(a.bl()).total_cmp(&axis(b.bl())));
+
+        let start = axis(items[0].1.bl());
+        let end = items
+            .iter()
+            .map(|(_, bounds)| axis(bounds.tr()))
+            .fold(f32::MIN, f32::max);
+        let occupied: f32 = items.iter().map(|(_, bounds)| axis(bounds.size())).sum();
+        let gap = (end - start - occupied) / (items.len() - 1) as f32;
+
+        let mut position = start;
+        for (i, bounds) in items {
+            let distance = position - axis(bounds.bl());
+            let offset = match direction {
+                LayoutDirection::Horizontal => V2::new(distance, 0.0),
+                LayoutDirection::Vertical => V2::new(0.0, distance),
+            };
+            self.sublayers[i].translate_mut(offset);
+            position += axis(bounds.size()) + gap;
+        }
+    }
+
+    /// Places the sublayers next to each other along `direction`, in order and separated by `gap`.
+    ///
+    /// The first sublayer keeps its position. On the other axis the sublayers are aligned with `alignment` like in
+    /// [`Layer::align_sublayers`], alignments along the stacking axis leave the other axis unchanged.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let square = Layer::new_from(vec![Rect::new_shape(V2::new(0.0, 0.0), V2::new(1.0, 1.0))]);
+    /// let layer = Layer::new_from_shapes_and_layers(vec![], vec![square.clone(), square.clone(), square]);
+    /// let stacked = layer.stack_sublayers(LayoutDirection::Horizontal, 0.5, Alignment::Bottom);
+    /// assert_eq!(stacked.sublayers_bounding_box().unwrap().width(), 4.0);
+    /// ```
+    pub fn stack_sublayers(
+        &self,
+        direction: LayoutDirection,
+        gap: f32,
+        alignment: Alignment,
+    ) -> Self {
+        let mut layer = self.clone();
+        layer.stack_sublayers_mut(direction, gap, alignment);
+        layer
+    }
+
+    /// see [`Layer::stack_sublayers`].
+    pub fn stack_sublayers_mut(
+        &mut self,
+        direction: LayoutDirection,
+        gap: f32,
+        alignment: Alignment,
+    ) {
+        let mut previous: Option<Rect> = None;
+        for sublayer in self.sublayers.iter_mut() {
+            let Some(bounds) = sublayer.bounding_box() else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                let offset = match direction {
+                    LayoutDirection::Horizontal => {
+                        V2::new(previous.tr().x + gap - bounds.bl().x, 0.0)
+                    }
+                    LayoutDirection::Vertical => {
+                        V2::new(0.0, previous.bl().y - gap - bounds.tr().y)
+                    }
+                };
+                sublayer.translate_mut(offset);
+            }
+            previous = sublayer.bounding_box();
+        }
+
+        let Some(target) = self.sublayers_bounding_box() else {
+            return;
+        };
+        for sublayer in self.sublayers.iter_mut() {
+            let Some(bounds) = sublayer.bounding_box() else {
+                continue;
+            };
+            let offset = match (direction, alignment) {
+                (LayoutDirection::Horizontal, Alignment::Top) => {
+                    V2::new(0.0, target.tr().y - bounds.tr().y)
+                }
+                (LayoutDirection::Horizontal, Alignment::Bottom) => {
+                    V2::new(0.0, target.bl().y - bounds.bl().y)
+                }
+                (LayoutDirection::Horizontal, Alignment::Center) => {
+                    V2::new(0.0, target.center().y - bounds.center().y)
+                }
+                (LayoutDirection::Vertical, Alignment::Left) => {
+                    V2::new(target.bl().x - bounds.bl().x, 0.0)
+                }
+                (LayoutDirection::Vertical, Alignment::Right) => {
+                    V2::new(target.tr().x - bounds.tr().x, 0.0)
+                }
+                (LayoutDirection::Vertical, Alignment::Center) => {
+                    V2::new(target.center().x - bounds.center().x, 0.0)
+                }
+                _ => continue,
+            };
+            sublayer.translate_mut(offset);
+        }
+    }
+
+    /// Places the sublayers in rows inside `area`, starting at its top left corner and breaking into a new row when
+    /// the next sublayer would exceed the area's width. `gap` is the space between sublayers (`x`) and rows (`y`).
+    ///
+    /// `Left`, `Right` and `Center` align the rows horizontally inside the area, with sublayers aligned to the top of
+    /// their row (centered for `Center`). `Top` and `Bottom` align the sublayers to the top or bottom of their row, with
+    /// rows starting at the left. Rows that don't fit into the area's height continue below it.
+    pub fn flow_sublayers(&self, area: &Rect, gap: V2, alignment: Alignment) -> Self {
+        let mut layer = self.clone();
+        layer.flow_sublayers_mut(area, gap, alignment);
+        layer
+    }
+
+    /// see [`Layer::flow_sublayers`].
+    pub fn flow_sublayers_mut(&mut self, area: &Rect, gap: V2, alignment: Alignment) {
+        // break the sublayers into rows of (index, bounds)
+        let mut rows: Vec<Vec<(usize, Rect)>> = vec![Vec::new()];
+        let mut row_width = 0.0;
+        for (i, sublayer) in self.iter_sublayers().enumerate() {
+            let Some(bounds) = sublayer.bounding_box() else {
+                continue;
+            };
+            let row = rows.last_mut().unwrap();
+            let width = if row.is_empty() {
+                bounds.width()
+            } else {
+                row_width + gap.x + bounds.width()
+            };
+            if !row.is_empty() && width > area.width() + LARGE_EPSILON {
+                rows.push(vec![(i, bounds)]);
+                row_width = bounds.width();
+            } else {
+                row.push((i, bounds));
+                row_width = width;
+            }
+        }
+
+        let mut top = area.tr().y;
+        for row in rows.into_iter().filter(|row| !row.is_empty()) {
+            let width = row.iter().map(|(_, bounds)| bounds.width()).sum::<f32>()
+                + gap.x * (row.len() - 1) as f32;
+            let height = row
+                .iter()
+                .map(|(_, bounds)| bounds.height())
+                .fold(0.0, f32::max);
+            let mut x = match alignment {
+                Alignment::Right => area.tr().x - width,
+                Alignment::Center => area.center().x - width / 2.0,
+                _ => area.bl().x,
+            };
+            for (i, bounds) in row {
+                let y = match alignment {
+                    Alignment::Bottom => top - height,
+                    Alignment::Center => top - (height + bounds.height()) / 2.0,
+                    _ => top - bounds.height(),
+                };
+                self.sublayers[i].translate_mut(V2::new(x, y) - bounds.bl());
+                x += bounds.width() + gap.x;
+            }
+            top -= height + gap.y;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test_layout {
+    use crate::{Alignment, BoundingBox, Layer, LayoutDirection, Rect, V2};
+
+    fn boxes(rects: &[(V2, V2)]) -> Layer {
+        Layer::new_from_shapes_and_layers(
+            vec![],
+            rects
+                .iter()
+                .map(|(bl, tr)| Layer::new_from(vec![Rect::new_shape(*bl, *tr)]))
+                .collect(),
+        )
+    }
+
+    fn bounds(layer: &Layer) -> Vec<Rect> {
+        layer
+            .iter_sublayers()
+            .map(|sublayer| sublayer.bounding_box().unwrap())
+            .collect()
+    }
+
+    fn example() -> Layer {
+        boxes(&[
+            (V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            (V2::new(5.0, 3.0), V2::new(6.0, 6.0)),
+            (V2::new(1.0, -2.0), V2::new(4.0, 0.0)),
+        ])
+    }
+
+    #[test]
+    fn align() {
+        let left = bounds(&example().align_sublayers(Alignment::Left, None));
+        assert!(left.iter().all(|b| b.bl().x == 0.0));
+        assert_eq!(left[1].bl().y, 3.0);
+
+        let top = bounds(&example().align_sublayers(Alignment::Top, None));
+        assert!(top.iter().all(|b| b.tr().y == 6.0));
+        assert_eq!(top[2].bl().x, 1.0);
+
+        let target = Rect::new(V2::new(10.0, 10.0), V2::new(20.0, 20.0));
+        let centered = bounds(&example().align_sublayers(Alignment::Center, Some(target)));
+        assert!(centered.iter().all(|b| b.center() == V2::new(15.0, 15.0)));
+
+        // empty sublayers are ignored
+        let mut layer = example();
+        layer.push_layer(Layer::new());
+        let right = layer.align_sublayers(Alignment::Right, None);
+        assert!(right.sublayers[3].is_empty());
+        assert!(bounds(&boxes(&[]).align_sublayers(Alignment::Right, None)).is_empty());
+    }
+
+    #[test]
+    fn distribute() {
+        let layer = boxes(&[
+            (V2::new(0.0, 0.0), V2::new(1.0, 1.0)),
+            (V2::new(9.0, 0.0), V2::new(10.0, 1.0)),
+            (V2::new(1.5, 5.0), V2::new(3.5, 6.0)),
+            (V2::new(2.0, 2.0), V2::new(3.0, 3.0)),
+        ]);
+        let distributed = bounds(&layer.distribute_sublayers(LayoutDirection::Horizontal));
+        // 5 cm occupied on a 10 cm span leave three gaps of 5/3 cm
+        let gap = 5.0 / 3.0;
+        assert_eq!(distributed[0].bl().x, 0.0);
+        assert!((distributed[2].bl().x - (1.0 + gap)).abs() < 1e-5);
+        assert!((distributed[3].bl().x - (3.0 + gap * 2.0)).abs() < 1e-5);
+        assert_eq!(distributed[1].bl().x, 9.0);
+        assert_eq!(distributed[2].bl().y, 5.0);
+
+        let vertical = bounds(&layer.distribute_sublayers(LayoutDirection::Vertical));
+        // both squares at the bottom keep their position
+        assert!((vertical[3].bl().y - 10.0 / 3.0).abs() < 1e-5);
+        assert_eq!(vertical[3].bl().x, 2.0);
+    }
+
+    #[test]
+    fn stack() {
+        let horizontal =
+            bounds(&example().stack_sublayers(LayoutDirection::Horizontal, 0.5, Alignment::Bottom));
+        assert_eq!(
+            horizontal[0],
+            Rect::new(V2::new(0.0, -2.0), V2::new(2.0, -1.0))
+        );
+        assert_eq!(
+            horizontal[1],
+            Rect::new(V2::new(2.5, -2.0), V2::new(3.5, 1.0))
+        );
+        assert_eq!(
+            horizontal[2],
+            Rect::new(V2::new(4.0, -2.0), V2::new(7.0, 0.0))
+        );
+
+        let vertical =
+            bounds(&example().stack_sublayers(LayoutDirection::Vertical, 1.0, Alignment::Center));
+        assert_eq!(vertical[0], Rect::new(V2::new(2.0, 0.0), V2::new(4.0, 1.0)));
+        assert_eq!(vertical[1].tr().y, -1.0);
+        assert_eq!(vertical[2].tr().y, -5.0);
+        assert!(vertical.iter().all(|b| b.center().x == 3.0));
+
+        // an alignment along the stacking axis keeps the other axis
+        let unaligned =
+            bounds(&example().stack_sublayers(LayoutDirection::Horizontal, 0.0, Alignment::Left));
+        assert_eq!(unaligned[1].bl().y, 3.0);
+    }
+
+    #[test]
+    fn flow() {
+        let squares: Vec<(V2, V2)> = (0..5)
+            .map(|i| (V2::new(i as f32, 0.0), V2::new(i as f32 + 2.0, 2.0)))
+            .collect();
+        let area = Rect::new(V2::new(0.0, 0.0), V2::new(7.0, 10.0));
+        let gap = V2::new(0.5, 1.0);
+
+        let flowed = bounds(&boxes(&squares).flow_sublayers(&area, gap, Alignment::Left));
+        // three squares fit into a row of 7 cm
+        assert_eq!(flowed[0].tl(), V2::new(0.0, 10.0));
+        assert_eq!(flowed[1].tl(), V2::new(2.5, 10.0));
+        assert_eq!(flowed[2].tl(), V2::new(5.0, 10.0));
+        assert_eq!(flowed[3].tl(), V2::new(0.0, 7.0));
+        assert_eq!(flowed[4].tl(), V2::new(2.5, 7.0));
+
+        let right = bounds(&boxes(&squares).flow_sublayers(&area, gap, Alignment::Right));
+        assert_eq!(right[4].tr(), V2::new(7.0, 7.0));
+
+        let mixed = boxes(&[
+            (V2::new(0.0, 0.0), V2::new(2.0, 4.0)),
+            (V2::new(0.0, 0.0), V2::new(2.0, 1.0)),
+            (V2::new(0.0, 0.0), V2::new(10.0, 1.0)),
+        ]);
+        let bottom = bounds(&mixed.flow_sublayers(&area, gap, Alignment::Bottom));
+        assert_eq!(bottom[0].bl().y, 6.0);
+        assert_eq!(bottom[1].bl(), V2::new(2.5, 6.0));
+        // too wide for the area, placed in its own row
+        assert_eq!(bottom[2].tl(), V2::new(0.0, 5.0));
+
+        let centered = bounds(&mixed.flow_sublayers(&area, gap, Alignment::Center));
+        assert_eq!(centered[1].center().y, 8.0);
+        assert_eq!(centered[0].bl().x, 1.25);
+    }
+}
@@ -1,20 +1,72 @@
 mod color;
 pub mod color_names;
 mod color_test;
+pub mod dxf;
+mod dxf_test;
 pub mod frame;
 mod frame_test;
+pub mod geojson;
+mod geojson_test;
 pub mod grid;
 mod grid_combineable_test;
 pub mod grid_comineable;
+pub mod hpgl;
+mod hpgl_test;
+pub mod imposition;
+mod imposition_test;
+pub mod ink_preview;
+mod ink_preview_test;
+pub mod join;
+mod join_test;
+pub mod json;
+mod json_test;
 pub mod layer;
+pub mod layer_file;
+mod layer_file_test;
 mod layer_props;
 mod layer_props_test;
 mod layer_test;
+pub mod layout;
+mod layout_test;
+pub mod overlap;
+mod overlap_test;
 mod path_end;
+pub mod pdf;
+mod pdf_test;
+pub mod pen;
+mod pen_test;
+pub mod query;
+mod query_test;
+pub mod stats;
+mod stats_test;
+mod svg_curve;
+mod svg_curve_test;
+pub mod svg_export;
+mod svg_export_test;
+pub mod tiling;
+mod tiling_test;
+pub mod travel;
+mod travel_test;
 
 pub use color::*;
+pub use dxf::*;
 pub use frame::*;
+pub use geojson::*;
 pub use grid::*;
 pub use grid_comineable::*;
+pub use hpgl::*;
+pub use imposition::*;
+pub use ink_preview::*;
+pub use join::*;
+pub use json::*;
 pub use layer::*;
+pub use layer_file::*;
 pub use layer_props::*;
+pub use layout::*;
+pub use overlap::*;
+pub use pdf::*;
+pub use pen::*;
+pub use stats::*;
+pub use svg_export::*;
+pub use tiling::*;
+pub use travel::*;
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::{Layer, Line, Path, Plottable, SampleSettings, Shape, V2};
+
+/// Result of [`Layer::remove_overlaps`].
+#[derive(Clone, Debug)]
+pub struct OverlapRemoval {
+    pub layer: Layer,
+    /// Total length of all strokes that were removed because they were already drawn.
+    pub removed_length: f32,
+}
+
+impl Layer {
+    /// Removes strokes that are drawn on top of already existing strokes of the same `Layer`, for example shared edges of adjacent [`crate::Rect`]s.
+    ///
+    /// Segments are considered overlapping if they are collinear and closer than `tolerance`. Only the overlapping parts are removed,
+    /// splitting [`Path`]s where needed, and `removed_length` is their total length. Parts that are not overlapping are always kept, however short. [`Shape`]s without overlaps are kept as they are, all others are converted to [`Path`]s using `sample_settings`.
+    ///
+    /// Sublayers are processed independently, since they may be drawn with a different pen.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![
+    ///     Rect::new_shape(V2::zero(), V2::xy(1.0)),
+    ///     Rect::new_shape(V2::new(1.0, 0.0), V2::new(2.0, 1.0)),
+    /// ]);
+    /// let removed = layer.remove_overlaps(0.01, SampleSettings::default());
+    /// assert!((removed.removed_length - 1.0).abs() < 0.001);
+    /// let kept_length: f32 = removed.layer.iter().map(|shape| shape.length()).sum();
+    /// assert!((kept_length - 7.0).abs() < 0.001);
+    /// ```
+    pub fn remove_overlaps(
+        &self,
+        tolerance: f32,
+        sample_settings: SampleSettings,
+    ) -> OverlapRemoval {
+        let mut index = SegmentIndex::new(self.mean_segment_length(sample_settings), tolerance);
+        let mut removed_length = 0.0;
+        let mut shapes = Vec::with_capacity(self.shapes.len());
+
+        for shape in self.iter() {
+            let mut pieces = Vec::new();
+            let mut has_overlap = false;
+            for stroke in Self::strokes(shape, sample_settings) {
+                let (kept, removed) = Self::remove_stroke_overlaps(&stroke, &mut index, tolerance);
+                if removed > 0.0 {
+                    has_overlap = true;
+                    removed_length += removed;
+                }
+                pieces.extend(kept);
+            }
+
+            if has_overlap {
+                shapes.extend(pieces.into_iter().map(Shape::Path));
+            } else {
+                shapes.push(shape.clone());
+            }
+        }
+
+        let mut sublayers = Vec::with_capacity(self.sublayers.len());
+        for sublayer in self.iter_sublayers() {
+            let removal = sublayer.remove_overlaps(tolerance, sample_settings);
+            removed_length += removal.removed_length;
+            sublayers.push(removal.layer);
+        }
+
+        OverlapRemoval {
+            layer: Layer::new_from_shapes_and_layers(shapes, sublayers)
+                .with_props_inheritable(self.props_inheritable.clone())
+                .with_props(self.props.clone()),
+            removed_length,
+        }
+    }
+
+    /// Every continuous stroke of the `shape` as points.
+    fn strokes(shape: &Shape, sample_settings: SampleSettings) -> Vec<Vec<V2>> {
+        match shape {
+            Shape::Compound(compound) => compound
+                .rings()
+                .map(|ring| ring.get_points(sample_settings))
+                .collect(),
+            _ => vec![shape.get_points(sample_settings)],
+        }
+    }
+
+    fn mean_segment_length(&self, sample_settings: SampleSettings) -> f32 {
+        let (total_length, count) = self
+            .iter()
+            .flat_map(|shape| Self::strokes(shape, sample_settings))
+            .flat_map(|points| {
+                points
+                    .iter()
+                    .tuple_windows()
+                    .map(|(from, to)| from.dist(*to))
+                    .collect_vec()
+            })
+            .fold((0.0, 0), |(length, count), segment_length| {
+                (length + segment_length, count + 1)
+            });
+        if count == 0 {
+            1.0
+        } else {
+            total_length / count as f32
+        }
+    }
+
+    /// Returns the parts of the stroke that are not covered by segments in `index` and the length that was removed.
+    /// All kept segments are added to `index`.
+    fn remove_stroke_overlaps(
+        points: &[V2],
+        index: &mut SegmentIndex,
+        tolerance: f32,
+    ) -> (Vec<Path>, f32) {
+        let mut kept = Vec::new();
+        let mut removed_length = 0.0;
+        let mut current = Path::new();
+        let mut kept_segments = Vec::new();
+
+        for (from, to) in points.iter().tuple_windows() {
+            let segment = Line::new(*from, *to);
+            let length = from.dist(*to);
+            if length == 0.0 {
+                continue;
+            }
+
+            let covered = index.covered_intervals(&segment, tolerance);
+            let mut kept_intervals = Vec::new();
+            let mut t = 0.0;
+            for (covered_from, covered_to) in covered {
+                if covered_from > t {
+                    kept_intervals.push((t, covered_from));
+                }
+                t = t.max(covered_to);
+            }
+            if t < 1.0 {
+                kept_intervals.push((t, 1.0));
+            }
+
+            let kept_length: f32 = kept_intervals
+                .iter()
+                .map(|(t_from, t_to)| (t_to - t_from) * length)
+                .sum();
+            removed_length += length - kept_length;
+
+            for (t_from, t_to) in kept_intervals {
+                let piece_from = from.lerp(*to, t_from);
+                let piece_to = from.lerp(*to, t_to);
+                if current.get_end() != Some(&piece_from) {
+                    if current.get_points_ref().len() > 1 {
+                        kept.push(current);
+                    }
+                    current = Path::new_from(vec![piece_from]);
+                }
+                current.push(piece_to);
+                kept_segments.push(Line::new(piece_from, piece_to));
+            }
+        }
+        if current.get_points_ref().len() > 1 {
+            kept.push(current);
+        }
+
+        // added after processing the whole stroke, so consecutive segments of one stroke don't cover each other
+        for segment in kept_segments {
+            index.insert(segment);
+        }
+
+        (kept, removed_length)
+    }
+}
+
+/// Spatial hash of line segments for finding collinear neighbours.
+struct SegmentIndex {
+    cell_size: f32,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    segments: Vec<Line>,
+}
+
+impl SegmentIndex {
+    fn new(cell_size: f32, tolerance: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(tolerance * 2.0).max(f32::EPSILON),
+            cells: HashMap::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    fn cell_range(&self, segment: &Line, margin: f32) -> impl Iterator<Item = (i64, i64)> {
+        let min = segment.from.min(segment.to) - V2::xy(margin);
+        let max = segment.from.max(segment.to) + V2::xy(margin);
+        let to_cell = |value: f32| (value / self.cell_size).floor() as i64;
+        let (x_from, x_to) = (to_cell(min.x), to_cell(max.x));
+        let (y_from, y_to) = (to_cell(min.y), to_cell(max.y));
+        (x_from..=x_to).cartesian_product(y_from..=y_to)
+    }
+
+    fn insert(&mut self, segment: Line) {
+        let segment_index = self.segments.len();
+        let cells = self.cell_range(&segment, 0.0).collect_vec();
+        for cell in cells {
+            self.cells.entry(cell).or_default().push(segment_index);
+        }
+        self.segments.push(segment);
+    }
+
+    /// Returns the sorted parameter intervals (0..1) of `segment` that are covered by collinear segments in the index.
+    fn covered_intervals(&self, segment: &Line, tolerance: f32) -> Vec<(f32, f32)> {
+        let length = segment.from.dist(segment.to);
+        let candidates = self
+            .cell_range(segment, tolerance)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .unique();
+
+        let mut intervals = Vec::new();
+        for candidate in candidates {
+            let other = self.segments[candidate];
+            let is_collinear = [segment.from, segment.to]
+                .iter()
+                .all(|point| point.dist(other.closest_point_on_infinite_line(*point)) <= tolerance)
+                && [other.from, other.to].iter().all(|point| {
+                    point.dist(segment.closest_point_on_infinite_line(*point)) <= tolerance
+                });
+            if !is_collinear {
+                continue;
+            }
+
+            let direction = segment.to - segment.from;
+            let project = |point: V2| (point - segment.from).dot(direction) / (length * length);
+            let (t_a, t_b) = (project(other.from), project(other.to));
+            let t_from = t_a.min(t_b).max(0.0);
+            let t_to = t_a.max(t_b).min(1.0);
+            // touching ends are not an overlap
+            if (t_to - t_from) * length > tolerance {
+                intervals.push((t_from, t_to));
+            }
+        }
+        intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+        intervals
+    }
+}
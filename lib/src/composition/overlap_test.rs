@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod test_overlap {
+    use crate::{Circle, Layer, Path, Plottable, Rect, SampleSettings, Shape, LARGE_EPSILON, V2};
+
+    fn total_length(layer: &Layer) -> f32 {
+        layer.iter_flattened().map(|shape| shape.length()).sum()
+    }
+
+    #[test]
+    fn no_overlaps_keeps_shapes() {
+        let layer = Layer::new_from(vec![
+            Rect::new_shape(V2::zero(), V2::xy(1.0)),
+            Circle::new_shape(V2::xy(5.0), 1.0),
+        ]);
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert_eq!(removal.removed_length, 0.0);
+        assert_eq!(removal.layer.shapes, layer.shapes);
+    }
+
+    #[test]
+    fn coincident_paths() {
+        let line = Path::new_shape_from(vec![V2::zero(), V2::new(2.0, 0.0)]);
+        let reversed = Path::new_shape_from(vec![V2::new(2.0, 0.005), V2::new(0.0, 0.005)]);
+        let layer = Layer::new_from(vec![line.clone(), reversed]);
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert!((removal.removed_length - 2.0).abs() < LARGE_EPSILON);
+        assert_eq!(removal.layer.shapes, vec![line]);
+    }
+
+    #[test]
+    fn partial_overlap_splits_path() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::zero(), V2::new(3.0, 0.0), V2::new(3.0, 1.0)]),
+        ]);
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert!((removal.removed_length - 1.0).abs() < LARGE_EPSILON);
+        assert_eq!(removal.layer.len(), 3);
+        assert_eq!(
+            removal.layer.shapes[1],
+            Path::new_shape_from(vec![V2::zero(), V2::new(1.0, 0.0)])
+        );
+        assert_eq!(
+            removal.layer.shapes[2],
+            Path::new_shape_from(vec![
+                V2::new(2.0, 0.0),
+                V2::new(3.0, 0.0),
+                V2::new(3.0, 1.0)
+            ])
+        );
+        assert!((total_length(&removal.layer) - 4.0).abs() < LARGE_EPSILON);
+    }
+
+    #[test]
+    fn short_uncovered_pieces_are_kept() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::zero(), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::zero(), V2::new(2.005, 0.0)]),
+        ]);
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert!((removal.removed_length - 2.0).abs() < LARGE_EPSILON);
+        assert_eq!(removal.layer.len(), 2);
+        assert!((total_length(&removal.layer) - 2.005).abs() < LARGE_EPSILON);
+    }
+
+    #[test]
+    fn adjacent_rects() {
+        let grid: Vec<Shape> = (0..3)
+            .flat_map(|x| {
+                (0..3).map(move |y| {
+                    Rect::new_shape(
+                        V2::new(x as f32, y as f32),
+                        V2::new(x as f32 + 1.0, y as f32 + 1.0),
+                    )
+                })
+            })
+            .collect();
+        let layer = Layer::new_from(grid);
+        let removal = layer.remove_overlaps(0.001, SampleSettings::default());
+
+        // 9 rects with 36 edges of which 12 are shared
+        assert!((removal.removed_length - 12.0).abs() < 0.01);
+        assert!((total_length(&removal.layer) - 24.0).abs() < 0.01);
+        assert!((removal.removed_length + total_length(&removal.layer) - 36.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn touching_and_crossing_are_kept() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::zero(), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(0.5, -1.0), V2::new(0.5, 1.0)]),
+        ]);
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert_eq!(removal.removed_length, 0.0);
+        assert_eq!(removal.layer.shapes, layer.shapes);
+    }
+
+    #[test]
+    fn sublayers_are_independent() {
+        let line = Path::new_shape_from(vec![V2::zero(), V2::new(1.0, 0.0)]);
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![line.clone()],
+            vec![Layer::new_from(vec![line.clone(), line.clone()]).with_name("pen 2")],
+        );
+        let removal = layer.remove_overlaps(0.01, SampleSettings::default());
+
+        assert!((removal.removed_length - 1.0).abs() < LARGE_EPSILON);
+        assert_eq!(removal.layer.len(), 1);
+        assert_eq!(removal.layer.sublayers[0].len(), 1);
+        assert_eq!(
+            removal.layer.sublayers[0].props.name,
+            Some("pen 2".to_string())
+        );
+    }
+}
@@ -20,6 +20,22 @@ impl PathEnd {
         }
     }
 
+    /// Like [`PathEnd::is_compatible`], but the points only need to be within `tolerance` of each other.
+    pub fn is_compatible_within(
+        &self,
+        other: &PathEnd,
+        tolerance: f32,
+        max_delta: &Option<Angle>,
+    ) -> bool {
+        if self.point.dist(other.point) > tolerance {
+            return false;
+        }
+        match max_delta {
+            None => true,
+            Some(delta) => self.angle.dist_mod_one_rotation(other.angle).abs() <= *delta,
+        }
+    }
+
     pub fn from_path_start(path: &Path) -> Self {
         PathEnd {
             point: *path.get_start().unwrap(),
@@ -0,0 +1,335 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+
+use crate::{BoundingBox, Layer, Rect, Shape, V2};
+
+use super::LayerPropsInheritable;
+
+/// PDF points (1/72 inch) per cm.
+pub const PDF_POINTS_PER_CM: f32 = 72.0 / 2.54;
+
+/// Control point distance of a cubic bezier quarter circle, relative to the radius.
+const PDF_CIRCLE_KAPPA: f32 = 0.552_284_8;
+
+/// How [`Layer::to_pdf`] separates sublayers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PdfSublayers {
+    /// All layers on a single page.
+    SinglePage,
+    /// One page per direct sublayer. Shapes of the layer itself are put on a first page.
+    Pages,
+    /// A single page with one optional content group per direct sublayer, which PDF viewers show as toggleable layers.
+    OptionalContentGroups,
+}
+
+/// Settings for [`Layer::to_pdf`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfSettings {
+    /// Page size in cm. Defaults to the area from the origin to the top right corner of the bounding box, plus the margin.
+    pub page_size: Option<V2>,
+    /// Margin in cm. Layer coordinates start at the bottom left corner inside the margin, shapes outside the margin are clipped.
+    pub margin: f32,
+    /// Whether the layer is centered inside the margin instead of placing its origin at the margin's corner.
+    pub centered: bool,
+    pub sublayers: PdfSublayers,
+}
+
+impl PdfSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_page_size(mut self, page_size: V2) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+    pub fn with_centered(mut self, centered: bool) -> Self {
+        self.centered = centered;
+        self
+    }
+    pub fn with_sublayers(mut self, sublayers: PdfSublayers) -> Self {
+        self.sublayers = sublayers;
+        self
+    }
+}
+
+impl Default for PdfSettings {
+    fn default() -> Self {
+        Self {
+            page_size: None,
+            margin: 0.0,
+            centered: false,
+            sublayers: PdfSublayers::OptionalContentGroups,
+        }
+    }
+}
+
+impl Layer {
+    /// Returns the layer as a vector PDF document at its physical size, interpreting all coordinates as cm.
+    ///
+    /// Shapes are stroked with their layer's color and pen width. Depending on [`PdfSettings::sublayers`], direct
+    /// sublayers are put on separate pages or into optional content groups named like the sublayers.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Circle::new_shape(V2::new(10.5, 14.85), 5.0)]);
+    /// let settings = PdfSettings::new().with_page_size(V2::a4()).with_margin(1.0);
+    /// let pdf = layer.to_pdf(&settings);
+    /// assert!(pdf.starts_with(b"%PDF-1.5"));
+    /// ```
+    pub fn to_pdf(&self, settings: &PdfSettings) -> Vec<u8> {
+        let margin = V2::xy(settings.margin);
+        let page_size = settings.page_size.unwrap_or_else(|| {
+            let extent = match self.bounding_box() {
+                Some(bounding_box) => bounding_box.tr().max(V2::zero()),
+                None => V2::zero(),
+            };
+            extent + margin * 2.0
+        });
+        let inner = Rect::new(margin, page_size - margin);
+        let offset = match (settings.centered, self.bounding_box()) {
+            (true, Some(bounding_box)) => inner.center() - bounding_box.center(),
+            _ => margin,
+        };
+
+        // groups of layers with their resolved props, each a page or an optional content group
+        let root_props = LayerPropsInheritable::default().overwrite_with(&self.props_inheritable);
+        let mut groups: Vec<(String, Vec<(&Layer, LayerPropsInheritable)>)> = Vec::new();
+        if settings.sublayers == PdfSublayers::SinglePage {
+            groups.push((String::new(), self.layers_in_plot_order()));
+        } else {
+            if !self.shapes.is_empty() || self.sublayers.is_empty() {
+                groups.push((String::new(), vec![(self, root_props.clone())]));
+            }
+            for (i, sublayer) in self.iter_sublayers().enumerate() {
+                let name = sublayer
+                    .props
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("layer {}", i + 1));
+                let mut layers = Vec::new();
+                sublayer.collect_layers_with_props(&root_props, &mut layers);
+                layers.sort_by_key(|(_, props)| props.plot_priority.unwrap());
+                groups.push((name, layers));
+            }
+        }
+
+        let contents: Vec<String> = groups
+            .iter()
+            .map(|(_, layers)| {
+                layers
+                    .iter()
+                    .filter(|(layer, _)| !layer.shapes.is_empty())
+                    .map(|(layer, props)| pdf_stroke_layer(layer, props))
+                    .collect()
+            })
+            .collect();
+
+        let clip_and_place = format!(
+            "q\n{s} 0 0 {s} 0 0 cm\n{} {} {} {} re W n\n1 0 0 1 {} {} cm\n1 J 1 j\n",
+            inner.bl().x,
+            inner.bl().y,
+            inner.width(),
+            inner.height(),
+            offset.x,
+            offset.y,
+            s = PDF_POINTS_PER_CM,
+        );
+        let media_box = format!(
+            "[0 0 {} {}]",
+            page_size.x * PDF_POINTS_PER_CM,
+            page_size.y * PDF_POINTS_PER_CM
+        );
+
+        // objects 1 and 2 are the catalog and page tree
+        let mut objects: Vec<String> = vec![String::new(), String::new()];
+        fn add_object(objects: &mut Vec<String>, object: String) -> usize {
+            objects.push(object);
+            objects.len()
+        }
+        let mut page_ids = Vec::new();
+        let mut oc_properties = String::new();
+        if settings.sublayers == PdfSublayers::OptionalContentGroups {
+            let mut content = clip_and_place.clone();
+            let mut properties = String::new();
+            let mut ocg_ids = Vec::new();
+            for ((name, _), group_content) in groups.iter().zip(contents.iter()) {
+                if name.is_empty() {
+                    content += group_content;
+                    continue;
+                }
+                let id = add_object(
+                    &mut objects,
+                    format!("<< /Type /OCG /Name {} >>", pdf_text_string(name)),
+                );
+                content += &format!("/OC /oc{} BDC\n{}EMC\n", id, group_content);
+                properties += &format!("/oc{} {} 0 R ", id, id);
+                ocg_ids.push(format!("{} 0 R", id));
+            }
+            content += "Q\n";
+            let content_id = add_object(&mut objects, pdf_stream(&content));
+            page_ids.push(add_object(
+                &mut objects,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox {} /Contents {} 0 R /Resources << /Properties << {}>> >> >>",
+                    media_box, content_id, properties
+                ),
+            ));
+            if !ocg_ids.is_empty() {
+                let ocgs = ocg_ids.join(" ");
+                oc_properties = format!(
+                    " /OCProperties << /OCGs [{}] /D << /Order [{}] /ON [{}] >> >>",
+                    ocgs, ocgs, ocgs
+                );
+            }
+        } else {
+            for group_content in contents.iter() {
+                let content_id = add_object(
+                    &mut objects,
+                    pdf_stream(&format!("{}{}Q\n", clip_and_place, group_content)),
+                );
+                page_ids.push(add_object(
+                    &mut objects,
+                    format!(
+                        "<< /Type /Page /Parent 2 0 R /MediaBox {} /Contents {} 0 R /Resources << >> >>",
+                        media_box, content_id
+                    ),
+                ));
+            }
+        }
+
+        objects[0] = format!("<< /Type /Catalog /Pages 2 0 R{} >>", oc_properties);
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            page_ids.len()
+        );
+
+        let mut pdf: Vec<u8> = b"%PDF-1.5\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+        let xref_offset = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+
+    /// Writes the layer to a PDF file, see [`Layer::to_pdf`].
+    pub fn write_pdf(&self, path: &PathBuf, settings: &PdfSettings) -> Result<()> {
+        fs::write(path, self.to_pdf(settings))?;
+        Ok(())
+    }
+}
+
+/// Returns the content stream operators stroking the layer's own shapes, in cm.
+fn pdf_stroke_layer(layer: &Layer, props: &LayerPropsInheritable) -> String {
+    let color = props.color.unwrap();
+    let mut content = format!(
+        "{} {} {} RG\n{} w\n",
+        color.r,
+        color.g,
+        color.b,
+        props.pen_width_cm.unwrap()
+    );
+    for shape in layer.iter() {
+        match shape {
+            Shape::Path(path) => pdf_add_points(&mut content, path.get_points_ref()),
+            Shape::Compound(compound) => compound
+                .rings()
+                .for_each(|ring| pdf_add_points(&mut content, ring.get_points_ref())),
+            Shape::Rect(rect) => {
+                content += &format!(
+                    "{} {} {} {} re\n",
+                    rect.bl().x,
+                    rect.bl().y,
+                    rect.width(),
+                    rect.height()
+                );
+            }
+            Shape::Circle(circle) => {
+                let (c, r) = (circle.center, circle.radius);
+                let k = r * PDF_CIRCLE_KAPPA;
+                content += &format!("{} {} m\n", c.x + r, c.y);
+                for (from, to) in [
+                    (V2::new(1.0, 0.0), V2::new(0.0, 1.0)),
+                    (V2::new(0.0, 1.0), V2::new(-1.0, 0.0)),
+                    (V2::new(-1.0, 0.0), V2::new(0.0, -1.0)),
+                    (V2::new(0.0, -1.0), V2::new(1.0, 0.0)),
+                ] {
+                    let c1 = c + from * r + to * k;
+                    let c2 = c + to * r + from * k;
+                    let end = c + to * r;
+                    content += &format!(
+                        "{} {} {} {} {} {} c\n",
+                        c1.x, c1.y, c2.x, c2.y, end.x, end.y
+                    );
+                }
+                content += "h\n";
+            }
+        }
+    }
+    content += "S\n";
+    content
+}
+
+fn pdf_add_points(content: &mut String, points: &[V2]) {
+    let closed = points.len() > 2 && points.first() == points.last();
+    let points = if closed {
+        &points[..points.len() - 1]
+    } else {
+        points
+    };
+    for (i, point) in points.iter().enumerate() {
+        let operator = if i == 0 { "m" } else { "l" };
+        *content += &format!("{} {} {}\n", point.x, point.y, operator);
+    }
+    if closed {
+        *content += "h\n";
+    }
+}
+
+fn pdf_stream(content: &str) -> String {
+    format!(
+        "<< /Length {} >>\nstream\n{}endstream",
+        content.len(),
+        content
+    )
+}
+
+/// Returns `text` as a PDF string, UTF-16 encoded if it isn't ASCII.
+fn pdf_text_string(text: &str) -> String {
+    if text.is_ascii() {
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        return format!("({})", escaped);
+    }
+    let hex: String = text
+        .encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect();
+    format!("<FEFF{}>", hex)
+}
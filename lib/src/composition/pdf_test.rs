@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod test_pdf {
+    use crate::{
+        Circle, ColorRgb, Layer, Path, PdfSettings, PdfSublayers, Rect, PDF_POINTS_PER_CM, V2,
+    };
+
+    fn text(pdf: &[u8]) -> String {
+        String::from_utf8_lossy(pdf).to_string()
+    }
+
+    #[test]
+    fn structure_and_xref() {
+        let layer = Layer::new_from(vec![Circle::new_shape(V2::new(5.0, 5.0), 2.0)]);
+        let pdf = layer.to_pdf(&PdfSettings::new().with_page_size(V2::a4()));
+        let pdf_text = text(&pdf);
+        assert!(pdf_text.ends_with("%%EOF\n"));
+
+        // every xref entry points to its object
+        let start_xref: usize = pdf_text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[start_xref..].starts_with(b"xref\n"));
+        let entries: Vec<usize> = text(&pdf[start_xref..])
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert!(!entries.is_empty());
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(pdf[*offset..].starts_with(format!("{} 0 obj\n", i + 1).as_bytes()));
+        }
+
+        let media_box = format!(
+            "/MediaBox [0 0 {} {}]",
+            21.0 * PDF_POINTS_PER_CM,
+            29.7 * PDF_POINTS_PER_CM
+        );
+        assert!(pdf_text.contains(&media_box));
+    }
+
+    #[test]
+    fn strokes() {
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(V2::new(5.0, 5.0), 2.0)],
+            vec![
+                Layer::new_from(vec![Path::new_shape_from(vec![
+                    V2::new(1.0, 1.0),
+                    V2::new(2.0, 1.0),
+                    V2::new(2.0, 2.0),
+                    V2::new(1.0, 1.0),
+                ])])
+                .with_color(ColorRgb::red())
+                .with_pen_width_cm(0.05),
+                Layer::new_from(vec![Rect::new_shape(V2::new(0.0, 0.0), V2::new(3.0, 2.0))]),
+            ],
+        );
+        let pdf = text(&layer.to_pdf(&PdfSettings::new().with_margin(1.0)));
+        assert!(pdf.contains("1 0 0 RG\n0.05 w\n1 1 m\n2 1 l\n2 2 l\nh\nS\n"));
+        assert!(pdf.contains("0 0 3 2 re\n"));
+        assert_eq!(pdf.matches(" c\n").count(), 4);
+        // the margin is clipped and offsets the layer's origin
+        assert!(pdf.contains("1 1 7 7 re W n\n1 0 0 1 1 1 cm\n"));
+
+        let centered = text(
+            &layer.to_pdf(
+                &PdfSettings::new()
+                    .with_page_size(V2::new(11.0, 11.0))
+                    .with_centered(true),
+            ),
+        );
+        assert!(centered.contains("1 0 0 1 2 2 cm\n"));
+    }
+
+    #[test]
+    fn sublayers() {
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(V2::new(5.0, 5.0), 2.0)],
+            vec![
+                Layer::new_from(vec![Path::new_shape_from(vec![
+                    V2::new(1.0, 1.0),
+                    V2::new(2.0, 1.0),
+                ])])
+                .with_name("pen (red)"),
+                Layer::new_from(vec![Rect::new_shape(V2::new(0.0, 0.0), V2::new(3.0, 2.0))])
+                    .with_name("rahmen ä"),
+            ],
+        );
+
+        let ocg = text(&layer.to_pdf(&PdfSettings::new()));
+        assert_eq!(ocg.matches("/Type /Page ").count(), 1);
+        assert!(ocg.contains("/Type /OCG /Name (pen \\(red\\))"));
+        assert!(ocg.contains("/Type /OCG /Name <FEFF007200610068006D0065006E002000E4>"));
+        assert_eq!(ocg.matches(" BDC\n").count(), 2);
+        assert!(ocg.contains("/OCProperties"));
+
+        let pages = text(&layer.to_pdf(&PdfSettings::new().with_sublayers(PdfSublayers::Pages)));
+        assert_eq!(pages.matches("/Type /Page ").count(), 3);
+        assert!(pages.contains("/Count 3"));
+        assert!(!pages.contains("/OCG"));
+
+        let single =
+            text(&layer.to_pdf(&PdfSettings::new().with_sublayers(PdfSublayers::SinglePage)));
+        assert_eq!(single.matches("/Type /Page ").count(), 1);
+        assert_eq!(single.matches("S\n").count(), 3);
+    }
+
+    #[test]
+    fn write() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.pdf");
+        Layer::new_from(vec![Circle::new_shape(V2::new(5.0, 5.0), 2.0)])
+            .write_pdf(&path, &PdfSettings::new())
+            .unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(b"%PDF-1.5\n"));
+    }
+}
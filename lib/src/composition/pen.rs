@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Inheritable, Layer, LayerPropsInheritable};
+
+use super::ColorRgb;
+
+/// A physical pen that can be put into the plotter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pen {
+    /// Id used for [`LayerPropsInheritable::pen_id`].
+    pub id: u32,
+    pub name: String,
+    pub color: ColorRgb,
+    pub width_cm: f32,
+    pub ink: Option<String>,
+}
+
+impl Pen {
+    pub fn new(id: u32, name: &str, color: ColorRgb, width_cm: f32) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            color,
+            width_cm,
+            ink: None,
+        }
+    }
+    pub fn with_ink(mut self, ink: &str) -> Self {
+        self.ink = Some(ink.to_string());
+        self
+    }
+
+    /// Inheritable properties drawing with this pen.
+    pub fn props_inheritable(&self) -> LayerPropsInheritable {
+        LayerPropsInheritable::inherit_all()
+            .with_color(self.color)
+            .with_pen_width_cm(self.width_cm)
+            .with_pen_id(self.id)
+    }
+}
+
+/// The set of [`Pen`]s available for plotting.
+///
+/// ### Example
+/// ```
+/// # use plottery_lib::*;
+/// let pens = PenSet::new()
+///     .with_pen(Pen::new(0, "black fineliner", ColorRgb::black(), 0.03))
+///     .with_pen(Pen::new(1, "red marker", ColorRgb::red(), 0.1));
+/// let pen = pens.nearest(ColorRgb::new(0.8, 0.1, 0.1)).unwrap();
+/// assert_eq!(pen.name, "red marker");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PenSet {
+    pub pens: Vec<Pen>,
+}
+
+impl PenSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_pen(mut self, pen: Pen) -> Self {
+        self.pens.push(pen);
+        self
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Pen> {
+        self.pens.iter().find(|pen| pen.id == id)
+    }
+
+    /// Returns the pen with the perceptually closest color, see [`ColorRgb::dist_perceptual`].
+    pub fn nearest(&self, color: ColorRgb) -> Option<&Pen> {
+        self.pens.iter().min_by(|a, b| {
+            color
+                .dist_perceptual(a.color)
+                .total_cmp(&color.dist_perceptual(b.color))
+        })
+    }
+
+    /// Returns the pen with the perceptually closest color among the pens of the closest width.
+    pub fn nearest_with_width(&self, color: ColorRgb, width_cm: f32) -> Option<&Pen> {
+        let width_delta = |pen: &Pen| (pen.width_cm - width_cm).abs();
+        let best_width_delta = self
+            .pens
+            .iter()
+            .map(width_delta)
+            .min_by(|a, b| a.total_cmp(b))?;
+        self.pens
+            .iter()
+            .filter(|pen| width_delta(pen) <= best_width_delta + f32::EPSILON)
+            .min_by(|a, b| {
+                color
+                    .dist_perceptual(a.color)
+                    .total_cmp(&color.dist_perceptual(b.color))
+            })
+    }
+}
+
+impl Layer {
+    /// Returns a new `Layer` where the color of every layer is replaced by the color, width and id
+    /// of the perceptually nearest [`Pen`] in `pens`. The structure of the layers is kept.
+    pub fn map_to_pens(&self, pens: &PenSet) -> Self {
+        self.map_to_pens_recursive(pens, &LayerPropsInheritable::default())
+    }
+
+    fn map_to_pens_recursive(&self, pens: &PenSet, parent_props: &LayerPropsInheritable) -> Self {
+        let props = parent_props.overwrite_with(&self.props_inheritable);
+        let sublayers = self
+            .iter_sublayers()
+            .map(|sublayer| sublayer.map_to_pens_recursive(pens, &props))
+            .collect();
+
+        let layer = Layer::new_from_shapes_and_layers(self.shapes.clone(), sublayers)
+            .with_props_inheritable(self.props_inheritable.clone())
+            .with_props(self.props.clone());
+        match pens.nearest(props.color.unwrap()) {
+            Some(pen) => layer
+                .with_color(pen.color)
+                .with_pen_width_cm(pen.width_cm)
+                .with_pen_id(pen.id),
+            None => layer,
+        }
+    }
+
+    /// Returns a new `Layer` with one sublayer per used [`Pen`], named after the pen.
+    ///
+    /// Every layer with [`crate::Shape`]s is assigned to the perceptually nearest pen and moved into that pen's sublayer,
+    /// keeping its name and its other plotting properties. Pen sublayers are ordered from light to dark inks and from thin to thick pens,
+    /// so dark strokes are drawn on top and smudge light ones less. Plot priorities of the original layers are dropped.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let pens = PenSet::new()
+    ///     .with_pen(Pen::new(0, "black", ColorRgb::black(), 0.05))
+    ///     .with_pen(Pen::new(1, "yellow", ColorRgb::yellow(), 0.05));
+    /// let layer = Layer::new_from_shapes_and_layers(
+    ///     vec![Circle::new_shape(V2::zero(), 1.0)],
+    ///     vec![Layer::new_from(vec![Circle::new_shape(V2::zero(), 2.0)]).with_color(ColorRgb::new(0.9, 0.8, 0.1))],
+    /// );
+    /// let by_pen = layer.split_by_pen(&pens);
+    /// assert_eq!(by_pen.sublayers[0].props.name.as_deref(), Some("yellow"));
+    /// assert_eq!(by_pen.sublayers[1].props.name.as_deref(), Some("black"));
+    /// ```
+    pub fn split_by_pen(&self, pens: &PenSet) -> Self {
+        let mut pen_layers: Vec<(&Pen, Vec<Layer>)> = Vec::new();
+        for (layer, props) in self.layers_in_plot_order() {
+            if layer.shapes.is_empty() {
+                continue;
+            }
+            let Some(pen) = pens.nearest(props.color.unwrap()) else {
+                continue;
+            };
+
+            let remaining_props = LayerPropsInheritable {
+                color: Inheritable::Inherit,
+                pen_width_cm: Inheritable::Inherit,
+                pen_id: Inheritable::Inherit,
+                plot_priority: Inheritable::Inherit,
+                ..props
+            };
+            let moved = Layer::new_from(layer.shapes.clone())
+                .with_props_inheritable(Inheritable::Specified(remaining_props))
+                .with_props(layer.props.clone());
+
+            match pen_layers.iter_mut().find(|(p, _)| p.id == pen.id) {
+                Some((_, layers)) => layers.push(moved),
+                None => pen_layers.push((pen, vec![moved])),
+            }
+        }
+
+        pen_layers.sort_by(|(a, _), (b, _)| {
+            b.color
+                .oklab()
+                .l
+                .total_cmp(&a.color.oklab().l)
+                .then(a.width_cm.total_cmp(&b.width_cm))
+        });
+
+        let sublayers = pen_layers
+            .into_iter()
+            .map(|(pen, layers)| {
+                Layer::new_from_shapes_and_layers(Vec::new(), layers)
+                    .with_props_inheritable(Inheritable::Specified(pen.props_inheritable()))
+                    .with_name(&pen.name)
+            })
+            .collect();
+        Layer::new_from_shapes_and_layers(Vec::new(), sublayers).with_props(self.props.clone())
+    }
+}
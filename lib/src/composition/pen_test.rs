@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod test_pen {
+    use crate::{Circle, ColorRgb, Inheritable, Layer, Pen, PenSet, V2};
+
+    fn pens() -> PenSet {
+        PenSet::new()
+            .with_pen(Pen::new(0, "black", ColorRgb::black(), 0.05))
+            .with_pen(Pen::new(1, "blue", ColorRgb::blue(), 0.05).with_ink("gel"))
+            .with_pen(Pen::new(2, "blue thick", ColorRgb::blue(), 0.2))
+            .with_pen(Pen::new(3, "yellow", ColorRgb::yellow(), 0.05))
+    }
+
+    fn circle() -> Layer {
+        Layer::new_from(vec![Circle::new_shape(V2::zero(), 1.0)])
+    }
+
+    #[test]
+    fn nearest() {
+        let pens = pens();
+        assert_eq!(pens.nearest(ColorRgb::new(0.1, 0.1, 0.3)).unwrap().id, 0);
+        assert_eq!(pens.nearest(ColorRgb::new(0.1, 0.2, 0.8)).unwrap().id, 1);
+        assert_eq!(
+            pens.nearest_with_width(ColorRgb::new(0.1, 0.2, 0.8), 0.15)
+                .unwrap()
+                .id,
+            2
+        );
+        assert_eq!(pens.get(1).unwrap().ink.as_deref(), Some("gel"));
+        assert!(PenSet::new().nearest(ColorRgb::red()).is_none());
+    }
+
+    #[test]
+    fn map_to_pens() {
+        let l = Layer::new_from_shapes_and_layers(
+            circle().shapes,
+            vec![circle()
+                .with_color(ColorRgb::new(0.9, 0.9, 0.2))
+                .with_passes(2)],
+        );
+        let mapped = l.map_to_pens(&pens());
+
+        let Inheritable::Specified(root) = &mapped.props_inheritable else {
+            panic!("expected specified props");
+        };
+        assert_eq!(root.pen_id.unwrap(), 0);
+        let Inheritable::Specified(sub) = &mapped.sublayers[0].props_inheritable else {
+            panic!("expected specified props");
+        };
+        assert_eq!(sub.pen_id.unwrap(), 3);
+        assert_eq!(sub.color.unwrap(), ColorRgb::yellow());
+        assert_eq!(sub.passes.unwrap(), 2);
+        assert_eq!(mapped.len_recursive(), 2);
+    }
+
+    #[test]
+    fn split_by_pen() {
+        let l = Layer::new_from_shapes_and_layers(
+            circle().shapes,
+            vec![
+                circle().with_color(ColorRgb::blue()).with_name("sky"),
+                circle()
+                    .with_color(ColorRgb::yellow())
+                    .with_name("sun")
+                    .with_speed_factor(0.5)
+                    .with_plot_priority(-5),
+                circle().with_color(ColorRgb::new(0.0, 0.0, 0.9)),
+            ],
+        );
+        let split = l.split_by_pen(&pens());
+
+        let names: Vec<_> = split
+            .iter_sublayers()
+            .map(|layer| layer.props.name.clone().unwrap())
+            .collect();
+        assert_eq!(names, vec!["yellow", "blue", "black"]);
+        assert_eq!(split.len_recursive(), 4);
+        assert_eq!(split.get_path("blue").unwrap().len_sublayers(), 2);
+        assert!(split.get_path("blue/sky").is_some());
+
+        let (sun, props) = split
+            .layers_in_plot_order()
+            .into_iter()
+            .find(|(layer, _)| layer.props.name.as_deref() == Some("sun"))
+            .unwrap();
+        assert_eq!(sun.len(), 1);
+        assert_eq!(props.color.unwrap(), ColorRgb::yellow());
+        assert_eq!(props.pen_id.unwrap(), 3);
+        assert_eq!(props.speed_factor.unwrap(), 0.5);
+        assert_eq!(props.plot_priority.unwrap(), 0);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::Layer;
+
+impl Layer {
+    /// Returns the sublayer at the slash separated `path` of sublayer names, relative to this `Layer`.
+    /// If several sublayers share a name, the first one is used. An empty path returns `self`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let hatch = Layer::new().with_name("hatch");
+    /// let background = Layer::new_from_shapes_and_layers(vec![], vec![hatch]).with_name("background");
+    /// let root = Layer::new_from_shapes_and_layers(vec![], vec![background]);
+    /// assert!(root.get_path("background/hatch").is_some());
+    /// assert!(root.get_path("hatch").is_none());
+    /// ```
+    pub fn get_path(&self, path: &str) -> Option<&Layer> {
+        split_path(path).try_fold(self, |layer, name| {
+            layer
+                .sublayers
+                .iter()
+                .find(|sublayer| sublayer.props.name.as_deref() == Some(name))
+        })
+    }
+
+    /// Mutable version of [`Layer::get_path`].
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Layer> {
+        split_path(path).try_fold(self, |layer, name| {
+            layer
+                .sublayers
+                .iter_mut()
+                .find(|sublayer| sublayer.props.name.as_deref() == Some(name))
+        })
+    }
+
+    /// Returns all sublayers (recursively, in tree order) whose name path matches the glob `pattern`.
+    ///
+    /// Within a path segment `*` matches any number of characters and `?` matches a single character.
+    /// A `**` segment matches any number of path segments. Unnamed layers have an empty name.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let root = Layer::new_from_shapes_and_layers(
+    ///     vec![],
+    ///     vec![
+    ///         Layer::new_from_shapes_and_layers(vec![], vec![Layer::new().with_name("hatch")])
+    ///             .with_name("background"),
+    ///         Layer::new().with_name("hatch_2"),
+    ///     ],
+    /// );
+    /// assert_eq!(root.query("*/hatch").len(), 1);
+    /// assert_eq!(root.query("**/hatch*").len(), 2);
+    /// ```
+    pub fn query(&self, pattern: &str) -> Vec<&Layer> {
+        let pattern: Vec<_> = split_path(pattern).collect();
+        let mut matches = Vec::new();
+        self.query_recursive(&pattern, &mut Vec::new(), &mut matches);
+        matches
+    }
+
+    fn query_recursive<'a>(
+        &'a self,
+        pattern: &[&str],
+        path: &mut Vec<&'a str>,
+        matches: &mut Vec<&'a Layer>,
+    ) {
+        for sublayer in self.iter_sublayers() {
+            path.push(sublayer.props.name.as_deref().unwrap_or(""));
+            if matches_path(pattern, path) {
+                matches.push(sublayer);
+            }
+            sublayer.query_recursive(pattern, path, matches);
+            path.pop();
+        }
+    }
+
+    /// Calls `f` on all sublayers matching the glob `pattern`, see [`Layer::query`].
+    /// Matches are visited before their sublayers, so changes made by `f` are seen further down. Returns the number of matches.
+    pub fn query_mut<F>(&mut self, pattern: &str, mut f: F) -> usize
+    where
+        F: FnMut(&mut Layer),
+    {
+        let pattern: Vec<_> = split_path(pattern).collect();
+        self.query_mut_recursive(&pattern, &mut Vec::new(), &mut f)
+    }
+
+    fn query_mut_recursive<F>(
+        &mut self,
+        pattern: &[&str],
+        path: &mut Vec<String>,
+        f: &mut F,
+    ) -> usize
+    where
+        F: FnMut(&mut Layer),
+    {
+        let mut count = 0;
+        for sublayer in self.sublayers.iter_mut() {
+            path.push(sublayer.props.name.clone().unwrap_or_default());
+            let path_refs: Vec<_> = path.iter().map(String::as_str).collect();
+            if matches_path(pattern, &path_refs) {
+                f(sublayer);
+                count += 1;
+            }
+            count += sublayer.query_mut_recursive(pattern, path, f);
+            path.pop();
+        }
+        count
+    }
+
+    /// Returns all sublayers (recursively, in tree order) that have the `tag`.
+    pub fn query_tag(&self, tag: &str) -> Vec<&Layer> {
+        let mut matches = Vec::new();
+        for sublayer in self.iter_sublayers() {
+            if sublayer.props.has_tag(tag) {
+                matches.push(sublayer);
+            }
+            matches.extend(sublayer.query_tag(tag));
+        }
+        matches
+    }
+
+    /// Calls `f` on all sublayers that have the `tag`, see [`Layer::query_tag`]. Returns the number of matches.
+    pub fn query_tag_mut<F>(&mut self, tag: &str, mut f: F) -> usize
+    where
+        F: FnMut(&mut Layer),
+    {
+        self.query_tag_mut_recursive(tag, &mut f)
+    }
+
+    fn query_tag_mut_recursive<F>(&mut self, tag: &str, f: &mut F) -> usize
+    where
+        F: FnMut(&mut Layer),
+    {
+        let mut count = 0;
+        for sublayer in self.sublayers.iter_mut() {
+            if sublayer.props.has_tag(tag) {
+                f(sublayer);
+                count += 1;
+            }
+            count += sublayer.query_tag_mut_recursive(tag, f);
+        }
+        count
+    }
+
+    /// Returns the name paths of all sublayers, recursively and in tree order.
+    pub fn sublayer_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for sublayer in self.iter_sublayers() {
+            let name = sublayer.props.name.clone().unwrap_or_default();
+            paths.push(name.clone());
+            paths.extend(
+                sublayer
+                    .sublayer_paths()
+                    .into_iter()
+                    .map(|path| format!("{}/{}", name, path)),
+            );
+        }
+        paths
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+fn matches_path(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_path(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => matches_glob(segment, name) && matches_path(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn matches_glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // let the last `*` swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
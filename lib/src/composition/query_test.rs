@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test_query {
+    use crate::{ColorRgb, Inheritable, Layer, Path, V2};
+
+    fn tree() -> Layer {
+        let line = || Path::new_shape_from(vec![V2::zero(), V2::xy(1.0)]);
+        Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![
+                Layer::new_from_shapes_and_layers(
+                    vec![line()],
+                    vec![
+                        Layer::new_from(vec![line()])
+                            .with_name("hatch")
+                            .with_tag("fill"),
+                        Layer::new_from(vec![line()]).with_name("outline"),
+                    ],
+                )
+                .with_name("background"),
+                Layer::new_from_shapes_and_layers(
+                    vec![],
+                    vec![Layer::new_from(vec![line()])
+                        .with_name("hatch")
+                        .with_tag("fill")],
+                )
+                .with_name("foreground")
+                .with_metadata("seed", "7"),
+                Layer::new(),
+            ],
+        )
+        .with_name("root")
+    }
+
+    #[test]
+    fn get_path() {
+        let mut l = tree();
+        assert_eq!(l.get_path("").unwrap().props.name.as_deref(), Some("root"));
+        assert_eq!(
+            l.get_path("background/hatch").unwrap().props.tags,
+            vec!["fill".to_string()]
+        );
+        assert_eq!(
+            l.get_path("/foreground/")
+                .unwrap()
+                .props
+                .get_metadata("seed"),
+            Some("7")
+        );
+        assert!(l.get_path("background/missing").is_none());
+        assert!(l.get_path("hatch").is_none());
+
+        l.get_path_mut("background/outline").unwrap().shapes.clear();
+        assert_eq!(l.get_path("background/outline").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn query() {
+        let l = tree();
+        assert_eq!(l.query("*").len(), 3);
+        assert_eq!(l.query("*/hatch").len(), 2);
+        assert_eq!(l.query("**").len(), 6);
+        assert_eq!(l.query("**/h?tch").len(), 2);
+        assert_eq!(l.query("back*/**").len(), 3);
+        assert_eq!(l.query("*ground").len(), 2);
+        assert!(l.query("hatch").is_empty());
+        assert_eq!(l.query_tag("fill").len(), 2);
+    }
+
+    #[test]
+    fn query_mut() {
+        let mut l = tree();
+        let red = ColorRgb::new(1.0, 0.0, 0.0);
+        let count = l.query_mut("**/hatch", |layer| {
+            *layer = layer.clone().with_color(red);
+        });
+        assert_eq!(count, 2);
+        for hatch in l.query("**/hatch") {
+            match &hatch.props_inheritable {
+                Inheritable::Specified(props) => assert_eq!(props.color.unwrap(), red),
+                Inheritable::Inherit => panic!("expected color"),
+            }
+        }
+
+        let hidden = l.query_tag_mut("fill", |layer| layer.shapes.clear());
+        assert_eq!(hidden, 2);
+        assert_eq!(l.len_recursive(), 2);
+    }
+
+    #[test]
+    fn sublayer_paths() {
+        assert_eq!(
+            tree().sublayer_paths(),
+            vec![
+                "background",
+                "background/hatch",
+                "background/outline",
+                "foreground",
+                "foreground/hatch",
+                "",
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{BoundingBox, Layer, Plottable, Rect, SampleSettings, Shape, V2};
+
+use super::ColorRgb;
+
+/// Drawing figures of a set of [`Shape`]s, see [`Layer::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DrawStats {
+    /// Total length drawn with the pen down.
+    pub draw_length: f32,
+    /// Total distance travelled with the pen up, including the travel to the first [`Shape`].
+    pub travel_length: f32,
+    /// Number of times the pen is lifted after drawing a continuous stroke.
+    pub pen_lifts: usize,
+    pub num_shapes: usize,
+    pub num_points: usize,
+    pub bounding_box: Option<Rect>,
+}
+
+impl DrawStats {
+    fn add(&mut self, other: &DrawStats) {
+        self.draw_length += other.draw_length;
+        self.travel_length += other.travel_length;
+        self.pen_lifts += other.pen_lifts;
+        self.num_shapes += other.num_shapes;
+        self.num_points += other.num_points;
+        self.bounding_box = match (self.bounding_box, other.bounding_box) {
+            (Some(a), Some(b)) => Some(Rect::new(a.bl().min(b.bl()), a.tr().max(b.tr()))),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// [`DrawStats`] of all [`Shape`]s drawn with the same pen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenStats {
+    pub color: ColorRgb,
+    pub pen_width_cm: f32,
+    pub stats: DrawStats,
+}
+
+/// Result of [`Layer::stats`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LayerStats {
+    pub total: DrawStats,
+    /// Figures per color and pen width, in order of their first use.
+    pub per_pen: Vec<PenStats>,
+}
+
+impl Layer {
+    /// Returns drawing statistics of this `Layer` and all its sublayers.
+    ///
+    /// [`Shape`]s are visited in plot order (see [`Layer::layers_in_plot_order`]), entered the same way they are plotted and
+    /// starting from the origin. Lengths, travel and pen lifts include all [`crate::LayerPropsInheritable::passes`].
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![
+    ///     Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(2.0, 0.0)]),
+    ///     Path::new_shape_from(vec![V2::new(3.0, 0.0), V2::new(5.0, 0.0)]),
+    /// ]);
+    /// let stats = layer.stats(SampleSettings::default());
+    /// assert_eq!(stats.total.draw_length, 3.0);
+    /// assert_eq!(stats.total.travel_length, 2.0);
+    /// assert_eq!(stats.total.pen_lifts, 2);
+    /// ```
+    pub fn stats(&self, sample_settings: SampleSettings) -> LayerStats {
+        let mut stats = LayerStats::default();
+        let mut pos = V2::zero();
+
+        for (layer, props) in self.layers_in_plot_order() {
+            if layer.shapes.is_empty() {
+                continue;
+            }
+            let color = props.color.unwrap();
+            let pen_width_cm = props.pen_width_cm.unwrap();
+            let pen_index = stats
+                .per_pen
+                .iter()
+                .position(|pen| pen.color == color && pen.pen_width_cm == pen_width_cm)
+                .unwrap_or_else(|| {
+                    stats.per_pen.push(PenStats {
+                        color,
+                        pen_width_cm,
+                        stats: DrawStats::default(),
+                    });
+                    stats.per_pen.len() - 1
+                });
+
+            for shape in layer.iter() {
+                let shape_stats =
+                    Self::shape_stats(shape, &mut pos, props.plot_passes(), sample_settings);
+                stats.total.add(&shape_stats);
+                stats.per_pen[pen_index].stats.add(&shape_stats);
+            }
+        }
+        stats
+    }
+
+    fn shape_stats(
+        shape: &Shape,
+        pos: &mut V2,
+        passes: u32,
+        sample_settings: SampleSettings,
+    ) -> DrawStats {
+        let mut stats = DrawStats {
+            draw_length: shape.length() * passes as f32,
+            num_shapes: 1,
+            bounding_box: shape.bounding_box(),
+            ..Default::default()
+        };
+
+        let mut add_stroke = |stroke: &Shape, pass: u32| {
+            let points = stroke.get_points_from(*pos, sample_settings);
+            if pass == 0 {
+                stats.num_points += points.len();
+            }
+            if points.len() < 2 {
+                return;
+            }
+            stats.travel_length += pos.dist(points[0]);
+            stats.pen_lifts += 1;
+            *pos = *points.last().unwrap();
+        };
+        for pass in 0..passes {
+            match shape {
+                Shape::Compound(compound) => compound
+                    .rings()
+                    .for_each(|ring| add_stroke(&ring.into(), pass)),
+                _ => add_stroke(shape, pass),
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test_stats {
+    use crate::{
+        Circle, ColorRgb, Compound, Layer, Path, Rect, SampleSettings, TravelSettings, V2,
+    };
+
+    #[test]
+    fn totals() {
+        let sample_settings = SampleSettings::default();
+        let l = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Rect::new_shape(V2::new(2.0, 0.0), V2::new(3.0, 1.0)),
+            Path::new_shape(),
+        ]);
+        let stats = l.stats(sample_settings);
+
+        assert_eq!(stats.total.num_shapes, 3);
+        assert_eq!(stats.total.num_points, 7);
+        assert_eq!(stats.total.pen_lifts, 2);
+        assert_eq!(stats.total.draw_length, 5.0);
+        assert_eq!(stats.total.travel_length, 1.0);
+        assert_eq!(
+            stats.total.bounding_box,
+            Some(Rect::new(V2::zero(), V2::new(3.0, 1.0)))
+        );
+        assert_eq!(
+            stats.total.travel_length,
+            l.pen_up_distance(V2::zero(), sample_settings)
+        );
+    }
+
+    #[test]
+    fn compound_rings_are_lifted_separately() {
+        let square = |bl: V2, size: f32| {
+            Path::new_from(vec![
+                bl,
+                bl + V2::new(size, 0.0),
+                bl + V2::xy(size),
+                bl + V2::new(0.0, size),
+            ])
+        };
+        let compound = Compound::new(square(V2::zero(), 4.0), vec![square(V2::xy(1.0), 2.0)]);
+        let stats = Layer::new_from(vec![compound.into()]).stats(SampleSettings::default());
+
+        assert_eq!(stats.total.num_shapes, 1);
+        assert_eq!(stats.total.pen_lifts, 2);
+        assert_eq!(stats.total.num_points, 10);
+        assert_eq!(stats.total.draw_length, 24.0);
+        assert!((stats.total.travel_length - 2.0_f32.sqrt()).abs() < 0.001);
+    }
+
+    #[test]
+    fn per_pen() {
+        let red = ColorRgb::new(1.0, 0.0, 0.0);
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(0.0, 0.0),
+                V2::new(1.0, 0.0),
+            ])],
+            vec![
+                Layer::new_from(vec![Circle::new_shape(V2::xy(5.0), 1.0)]).with_color(red),
+                Layer::new_from_shapes_and_layers(
+                    vec![Path::new_shape_from(vec![
+                        V2::new(0.0, 2.0),
+                        V2::new(0.0, 4.0),
+                    ])],
+                    vec![Layer::new_from(vec![Path::new_shape_from(vec![
+                        V2::new(1.0, 2.0),
+                        V2::new(1.0, 4.0),
+                    ])])
+                    .with_color(red)],
+                ),
+            ],
+        );
+        let stats = l.stats(SampleSettings::default());
+
+        assert_eq!(stats.per_pen.len(), 2);
+        assert_eq!(stats.per_pen[0].color, ColorRgb::black());
+        assert_eq!(stats.per_pen[0].stats.num_shapes, 2);
+        assert_eq!(stats.per_pen[0].stats.draw_length, 3.0);
+        assert_eq!(stats.per_pen[1].color, red);
+        assert_eq!(stats.per_pen[1].stats.num_shapes, 2);
+        assert_eq!(stats.total.num_shapes, 4);
+
+        let pen_travel: f32 = stats
+            .per_pen
+            .iter()
+            .map(|pen| pen.stats.travel_length)
+            .sum();
+        assert!((pen_travel - stats.total.travel_length).abs() < 0.001);
+    }
+
+    #[test]
+    fn optimization_reduces_travel() {
+        let l = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(9.0, 0.0), V2::new(10.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(5.0, 0.0), V2::new(6.0, 0.0)]),
+        ]);
+        let optimized = l
+            .optimize_travel(&TravelSettings::default(), SampleSettings::default())
+            .layer;
+        let before = l.stats(SampleSettings::default());
+        let after = optimized.stats(SampleSettings::default());
+
+        assert_eq!(before.total.draw_length, after.total.draw_length);
+        assert!(after.total.travel_length < before.total.travel_length);
+    }
+
+    #[test]
+    fn passes() {
+        let l = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(1.0, 0.0),
+            V2::new(2.0, 0.0),
+        ])])
+        .with_passes(3);
+        let stats = l.stats(SampleSettings::default());
+
+        assert_eq!(stats.total.draw_length, 3.0);
+        assert_eq!(stats.total.pen_lifts, 3);
+        assert_eq!(stats.total.num_shapes, 1);
+        assert_eq!(stats.total.num_points, 2);
+        // following passes start at the closer end
+        assert_eq!(stats.total.travel_length, 1.0);
+    }
+
+    #[test]
+    fn zero_passes_are_drawn_once() {
+        let l = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(1.0, 0.0),
+            V2::new(2.0, 0.0),
+        ])])
+        .with_passes(0);
+        let stats = l.stats(SampleSettings::default());
+
+        assert_eq!(stats.total.draw_length, 1.0);
+        assert_eq!(stats.total.pen_lifts, 1);
+    }
+}
@@ -0,0 +1,141 @@
+use std::f32::consts::TAU;
+
+use crate::{Line, V2};
+
+const MAX_SUBDIVISIONS: usize = 16;
+
+/// Points of a cubic bezier curve from `from` (excluded) to `to` (included), deviating at most `tolerance` from the curve.
+pub(crate) fn flatten_cubic(from: V2, c1: V2, c2: V2, to: V2, tolerance: f32) -> Vec<V2> {
+    let mut points = Vec::new();
+    flatten_cubic_recursive([from, c1, c2, to], tolerance, 0, &mut points);
+    points
+}
+
+fn flatten_cubic_recursive(curve: [V2; 4], tolerance: f32, depth: usize, points: &mut Vec<V2>) {
+    let [p0, p1, p2, p3] = curve;
+    // the curve lies within the hull of its control points
+    let chord = Line::new(p0, p3);
+    let is_flat = p1.dist(chord.closest_point(p1)) <= tolerance
+        && p2.dist(chord.closest_point(p2)) <= tolerance;
+    if is_flat || depth >= MAX_SUBDIVISIONS {
+        points.push(p3);
+        return;
+    }
+
+    let p01 = p0.lerp(p1, 0.5);
+    let p12 = p1.lerp(p2, 0.5);
+    let p23 = p2.lerp(p3, 0.5);
+    let p012 = p01.lerp(p12, 0.5);
+    let p123 = p12.lerp(p23, 0.5);
+    let mid = p012.lerp(p123, 0.5);
+    flatten_cubic_recursive([p0, p01, p012, mid], tolerance, depth + 1, points);
+    flatten_cubic_recursive([mid, p123, p23, p3], tolerance, depth + 1, points);
+}
+
+/// Points of a quadratic bezier curve from `from` (excluded) to `to` (included), see [`flatten_cubic`].
+pub(crate) fn flatten_quadratic(from: V2, control: V2, to: V2, tolerance: f32) -> Vec<V2> {
+    let c1 = from + (control - from) * (2.0 / 3.0);
+    let c2 = to + (control - to) * (2.0 / 3.0);
+    flatten_cubic(from, c1, c2, to, tolerance)
+}
+
+/// Parameters of an svg elliptical arc (`A` path command).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SvgArc {
+    pub radius: V2,
+    pub x_axis_rotation_degrees: f32,
+    pub large_arc: bool,
+    pub sweep: bool,
+}
+
+/// Points of an svg elliptical arc from `from` (excluded) to `to` (included), deviating at most `tolerance` from the arc.
+///
+/// Follows the endpoint to center conversion of the svg specification, including scaling up radii that are too small.
+pub(crate) fn flatten_arc(from: V2, arc: &SvgArc, to: V2, tolerance: f32) -> Vec<V2> {
+    if from == to {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (arc.radius.x.abs(), arc.radius.y.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![to];
+    }
+
+    let phi = arc.x_axis_rotation_degrees.to_radians();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let half_delta = (from - to) * 0.5;
+    let x1 = cos_phi * half_delta.x + sin_phi * half_delta.y;
+    let y1 = -sin_phi * half_delta.x + cos_phi * half_delta.y;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if arc.large_arc == arc.sweep {
+        -1.0
+    } else {
+        1.0
+    };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let center_x = coefficient * rx * y1 / ry;
+    let center_y = -coefficient * ry * x1 / rx;
+    let mid = (from + to) * 0.5;
+    let center = V2::new(
+        cos_phi * center_x - sin_phi * center_y + mid.x,
+        sin_phi * center_x + cos_phi * center_y + mid.y,
+    );
+
+    let start = V2::new((x1 - center_x) / rx, (y1 - center_y) / ry);
+    let end = V2::new((-x1 - center_x) / rx, (-y1 - center_y) / ry);
+    let start_angle = angle_between(V2::new(1.0, 0.0), start);
+    let mut sweep_angle = angle_between(start, end) % TAU;
+    if !arc.sweep && sweep_angle > 0.0 {
+        sweep_angle -= TAU;
+    } else if arc.sweep && sweep_angle < 0.0 {
+        sweep_angle += TAU;
+    }
+
+    let segments = segment_count(sweep_angle.abs(), rx.max(ry), tolerance);
+    let mut points: Vec<V2> = (1..segments)
+        .map(|i| {
+            let angle = start_angle + sweep_angle * i as f32 / segments as f32;
+            let local = V2::new(rx * angle.cos(), ry * angle.sin());
+            center
+                + V2::new(
+                    cos_phi * local.x - sin_phi * local.y,
+                    sin_phi * local.x + cos_phi * local.y,
+                )
+        })
+        .collect();
+    points.push(to);
+    points
+}
+
+/// Closed outline of an axis aligned ellipse, deviating at most `tolerance` from it.
+pub(crate) fn flatten_ellipse(center: V2, radius: V2, tolerance: f32) -> Vec<V2> {
+    let segments = segment_count(TAU, radius.x.max(radius.y), tolerance).max(8);
+    let mut points: Vec<V2> = (0..segments)
+        .map(|i| {
+            let angle = TAU * i as f32 / segments as f32;
+            center + V2::new(radius.x * angle.cos(), radius.y * angle.sin())
+        })
+        .collect();
+    points.push(points[0]);
+    points
+}
+
+/// Number of segments needed for an arc of `angle` with `radius` so the chords deviate at most `tolerance`.
+pub(crate) fn segment_count(angle: f32, radius: f32, tolerance: f32) -> usize {
+    if tolerance <= 0.0 || tolerance >= radius {
+        return ((angle / TAU * 4.0).ceil() as usize).max(1);
+    }
+    let max_step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((angle / max_step).ceil() as usize).clamp(1, 10_000)
+}
+
+fn angle_between(u: V2, v: V2) -> f32 {
+    (u.x * v.y - u.y * v.x).atan2(u.dot(v))
+}
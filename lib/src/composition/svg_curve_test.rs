@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod test_svg_curve {
+    use crate::{
+        composition::svg_curve::{
+            flatten_arc, flatten_cubic, flatten_ellipse, flatten_quadratic, SvgArc,
+        },
+        V2,
+    };
+
+    fn cubic_at(p: [V2; 4], t: f32) -> V2 {
+        let u = 1.0 - t;
+        p[0] * (u * u * u)
+            + p[1] * (3.0 * u * u * t)
+            + p[2] * (3.0 * u * t * t)
+            + p[3] * (t * t * t)
+    }
+
+    fn dist_to_polyline(point: V2, polyline: &[V2]) -> f32 {
+        polyline
+            .windows(2)
+            .map(|segment| {
+                let direction = segment[1] - segment[0];
+                let t = ((point - segment[0]).dot(direction) / direction.dot(direction))
+                    .clamp(0.0, 1.0);
+                point.dist(segment[0] + direction * t)
+            })
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn cubic_within_tolerance() {
+        let curve = [
+            V2::new(0.0, 0.0),
+            V2::new(0.0, 10.0),
+            V2::new(10.0, 10.0),
+            V2::new(10.0, 0.0),
+        ];
+        for tolerance in [0.1, 0.01, 0.001] {
+            let mut points = vec![curve[0]];
+            points.extend(flatten_cubic(
+                curve[0], curve[1], curve[2], curve[3], tolerance,
+            ));
+            assert_eq!(*points.last().unwrap(), curve[3]);
+            for i in 0..=100 {
+                let on_curve = cubic_at(curve, i as f32 / 100.0);
+                assert!(dist_to_polyline(on_curve, &points) <= tolerance * 1.01);
+            }
+        }
+
+        let coarse = flatten_cubic(curve[0], curve[1], curve[2], curve[3], 0.1);
+        let fine = flatten_cubic(curve[0], curve[1], curve[2], curve[3], 0.001);
+        assert!(coarse.len() < fine.len());
+    }
+
+    #[test]
+    fn straight_curves_are_single_segments() {
+        let from = V2::new(0.0, 0.0);
+        let to = V2::new(3.0, 0.0);
+        assert_eq!(
+            flatten_cubic(from, V2::new(1.0, 0.0), V2::new(2.0, 0.0), to, 0.001),
+            vec![to]
+        );
+        assert_eq!(
+            flatten_quadratic(from, V2::new(1.5, 0.0), to, 0.001),
+            vec![to]
+        );
+    }
+
+    #[test]
+    fn quadratic_matches_cubic_elevation() {
+        let points = flatten_quadratic(
+            V2::new(0.0, 0.0),
+            V2::new(5.0, 10.0),
+            V2::new(10.0, 0.0),
+            0.01,
+        );
+        // the apex of the parabola is at t = 0.5
+        assert!(dist_to_polyline(V2::new(5.0, 5.0), &points) < 0.01);
+        assert_eq!(*points.last().unwrap(), V2::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn half_circle_arc() {
+        let arc = SvgArc {
+            radius: V2::new(5.0, 5.0),
+            x_axis_rotation_degrees: 0.0,
+            large_arc: false,
+            sweep: true,
+        };
+        let points = flatten_arc(V2::new(0.0, 0.0), &arc, V2::new(10.0, 0.0), 0.001);
+        assert_eq!(*points.last().unwrap(), V2::new(10.0, 0.0));
+        for point in points.iter() {
+            assert!((point.dist(V2::new(5.0, 0.0)) - 5.0).abs() < 0.001);
+        }
+        // a positive sweep goes from angle pi to 2 pi, through negative y
+        assert!(points.iter().any(|point| point.y < -4.99));
+
+        let other_side = flatten_arc(
+            V2::new(0.0, 0.0),
+            &SvgArc {
+                sweep: false,
+                ..arc
+            },
+            V2::new(10.0, 0.0),
+            0.001,
+        );
+        assert!(other_side.iter().any(|point| point.y > 4.99));
+    }
+
+    #[test]
+    fn arc_flags_and_radius_correction() {
+        let arc = SvgArc {
+            radius: V2::new(5.0, 5.0),
+            x_axis_rotation_degrees: 0.0,
+            large_arc: true,
+            sweep: true,
+        };
+        // quarter circle around (5, 5) vs. three quarters around (0, 0), both with increasing angle
+        let small = flatten_arc(
+            V2::new(0.0, 5.0),
+            &SvgArc {
+                large_arc: false,
+                ..arc
+            },
+            V2::new(5.0, 0.0),
+            0.001,
+        );
+        let large = flatten_arc(V2::new(0.0, 5.0), &arc, V2::new(5.0, 0.0), 0.001);
+        assert!(small
+            .iter()
+            .all(|point| (point.dist(V2::new(5.0, 5.0)) - 5.0).abs() < 0.001));
+        assert!(large
+            .iter()
+            .all(|point| (point.dist(V2::new(0.0, 0.0)) - 5.0).abs() < 0.001));
+        assert!(large.len() > small.len());
+
+        // radii too small to reach the end point are scaled up
+        let scaled = flatten_arc(
+            V2::new(0.0, 0.0),
+            &SvgArc {
+                radius: V2::new(1.0, 1.0),
+                ..arc
+            },
+            V2::new(10.0, 0.0),
+            0.001,
+        );
+        assert!(scaled
+            .iter()
+            .all(|point| (point.dist(V2::new(5.0, 0.0)) - 5.0).abs() < 0.001));
+    }
+
+    #[test]
+    fn degenerate_arcs() {
+        let arc = SvgArc {
+            radius: V2::new(0.0, 5.0),
+            x_axis_rotation_degrees: 0.0,
+            large_arc: false,
+            sweep: false,
+        };
+        assert_eq!(
+            flatten_arc(V2::zero(), &arc, V2::new(1.0, 1.0), 0.001),
+            vec![V2::new(1.0, 1.0)]
+        );
+        assert!(flatten_arc(V2::new(1.0, 1.0), &arc, V2::new(1.0, 1.0), 0.001).is_empty());
+    }
+
+    #[test]
+    fn ellipse_is_closed() {
+        let center = V2::new(1.0, 2.0);
+        let points = flatten_ellipse(center, V2::new(4.0, 2.0), 0.001);
+        assert_eq!(points.first(), points.last());
+        for point in points.iter() {
+            let normalized = (*point - center) / V2::new(4.0, 2.0);
+            assert!((normalized.len() - 1.0).abs() < 0.001);
+        }
+    }
+}
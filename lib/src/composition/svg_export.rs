@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use svg::Document;
+
+use crate::{BoundingBox, Layer, LayerPropsInheritable, Mirror, Rect, Translate, V2};
+
+const INKSCAPE_NAMESPACE: &str = "http://www.inkscape.org/namespaces/inkscape";
+
+/// Settings for [`Layer::to_svg_physical`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgExportSettings {
+    /// The page in layer coordinates (cm). Defaults to the area from the origin to the top right corner of the bounding box.
+    pub page: Option<Rect>,
+    /// Whether the layer and its sublayers are written as Inkscape layers.
+    pub inkscape_layers: bool,
+}
+
+impl SvgExportSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_page(mut self, page: Rect) -> Self {
+        self.page = Some(page);
+        self
+    }
+    pub fn with_inkscape_layers(mut self, inkscape_layers: bool) -> Self {
+        self.inkscape_layers = inkscape_layers;
+        self
+    }
+}
+
+impl Default for SvgExportSettings {
+    fn default() -> Self {
+        Self {
+            page: None,
+            inkscape_layers: true,
+        }
+    }
+}
+
+impl Layer {
+    /// Return the layer converted into a [`svg::Document`] at its physical size, interpreting all coordinates as cm.
+    ///
+    /// Unlike [`Layer::to_svg`], the document covers the page given in `settings`, so the origin and margins are preserved,
+    /// and `width` and `height` are written in cm (e.g. `width="21cm"`). Sublayers become Inkscape layers.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Circle::new_shape(V2::new(10.5, 14.85), 5.0)]);
+    /// let settings = SvgExportSettings::new().with_page(Rect::new(V2::zero(), V2::new(21.0, 29.7)));
+    /// let svg = layer.to_svg_physical(&settings).to_string();
+    /// assert!(svg.contains("width=\"21cm\""));
+    /// ```
+    pub fn to_svg_physical(&self, settings: &SvgExportSettings) -> Document {
+        let page = match settings.page {
+            Some(page) => page,
+            None => match self.bounding_box() {
+                Some(bounding_box) => Rect::new(
+                    bounding_box.bl().min(V2::zero()),
+                    bounding_box.tr().max(V2::zero()),
+                ),
+                None => return Document::new(),
+            },
+        };
+
+        // svg y points down, starting at the top of the page
+        let offset = V2::new(-page.bl().x, page.tr().y);
+        let prepared = self.map_recursive(|shape| shape.mirror_y().translate(offset));
+
+        let size = page.size();
+        let mut document = Document::new()
+            .set("viewBox", (0, 0, size.x, size.y))
+            .set("width", format!("{}cm", size.x))
+            .set("height", format!("{}cm", size.y));
+        if settings.inkscape_layers {
+            document = document.set("xmlns:inkscape", INKSCAPE_NAMESPACE);
+        }
+        document.add(prepared.get_svg_group(
+            1.0,
+            &LayerPropsInheritable::default(),
+            settings.inkscape_layers,
+        ))
+    }
+
+    /// Writes the layer to an .svg file at its physical size, see [`Layer::to_svg_physical`].
+    pub fn write_svg_physical(&self, path: PathBuf, settings: &SvgExportSettings) -> Result<()> {
+        let document = self.to_svg_physical(settings);
+        svg::save(path.to_str().unwrap(), &document)?;
+        Ok(())
+    }
+}
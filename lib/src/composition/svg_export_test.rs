@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test_svg_export {
+    use svg::parser::Event;
+
+    use crate::{Circle, Layer, Path, Rect, SvgExportSettings, V2};
+
+    fn attributes(svg: &str, tag: &str) -> Vec<svg::node::Attributes> {
+        svg::read(svg)
+            .unwrap()
+            .filter_map(|event| match event {
+                Event::Tag(name, svg::node::element::tag::Type::Start, attributes)
+                | Event::Tag(name, svg::node::element::tag::Type::Empty, attributes)
+                    if name == tag =>
+                {
+                    Some(attributes)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn physical_size() {
+        let l = Layer::new_from(vec![Circle::new_shape(V2::new(2.0, 3.0), 1.0)]);
+        let settings =
+            SvgExportSettings::new().with_page(Rect::new(V2::zero(), V2::new(21.0, 29.7)));
+        let svg = l.to_svg_physical(&settings).to_string();
+
+        let root = &attributes(&svg, "svg")[0];
+        assert_eq!(root["width"].to_string(), "21cm");
+        assert_eq!(root["height"].to_string(), "29.7cm");
+        assert_eq!(root["viewBox"].to_string(), "0 0 21 29.7");
+
+        // origin is kept, y is flipped on the page
+        let circle = &attributes(&svg, "circle")[0];
+        assert_eq!(circle["cx"].to_string(), "2");
+        assert!((circle["cy"].to_string().parse::<f32>().unwrap() - 26.7).abs() < 0.001);
+    }
+
+    #[test]
+    fn default_page_keeps_origin() {
+        let l = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(1.0, 1.0),
+            V2::new(4.0, 2.0),
+        ])]);
+        let svg = l.to_svg_physical(&SvgExportSettings::default()).to_string();
+        let root = &attributes(&svg, "svg")[0];
+        assert_eq!(root["width"].to_string(), "4cm");
+        assert_eq!(root["height"].to_string(), "2cm");
+        assert_eq!(attributes(&svg, "path")[0]["d"].to_string(), "M1,1 L4,0");
+    }
+
+    #[test]
+    fn inkscape_layers() {
+        let l = Layer::new_from_shapes_and_layers(
+            vec![Circle::new_shape(V2::xy(1.0), 1.0)],
+            vec![
+                Layer::new_from(vec![Circle::new_shape(V2::xy(2.0), 1.0)]).with_name("hatch"),
+                Layer::new_from(vec![Circle::new_shape(V2::xy(3.0), 1.0)]),
+            ],
+        )
+        .with_name("root");
+        let svg = l.to_svg_physical(&SvgExportSettings::default()).to_string();
+
+        assert!(svg.contains("xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\""));
+        let groups = attributes(&svg, "g");
+        assert_eq!(groups.len(), 3);
+        assert!(groups
+            .iter()
+            .all(|g| g["inkscape:groupmode"].to_string() == "layer"));
+        let labels: Vec<_> = groups
+            .iter()
+            .map(|g| g["inkscape:label"].to_string())
+            .collect();
+        assert_eq!(labels, vec!["root", "hatch", "layer"]);
+
+        let plain = l
+            .to_svg_physical(&SvgExportSettings::new().with_inkscape_layers(false))
+            .to_string();
+        assert!(!plain.contains("inkscape"));
+    }
+
+    #[test]
+    fn write() {
+        let l = Layer::new_from(vec![Circle::new_shape(V2::xy(1.0), 1.0)]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("physical.svg");
+        l.write_svg_physical(path.clone(), &SvgExportSettings::default())
+            .unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("width=\"2cm\""));
+    }
+}
@@ -0,0 +1,454 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    BoundingBox, Circle, Containment, Layer, Path, Plottable, Rect, SampleSettings, Shape,
+    Translate, LARGE_EPSILON, V2,
+};
+
+use super::{Frame, Grid};
+
+/// Settings for [`Layer::tile`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileSettings {
+    pub sheet_size: V2,
+    /// Margin of each sheet, holding the marks and labels. Tiles are placed at the bottom left corner inside the margin.
+    pub margin: f32,
+    /// Width of the strip of the layer that neighbouring tiles share, so sheets can be overlapped or trimmed when assembling.
+    pub overlap: f32,
+    /// Whether to draw crop marks at the corners of each tile, extending its edges into the margin.
+    pub crop_marks: bool,
+    /// Whether to draw registration marks in the margin, centered on the overlap with each neighbouring tile.
+    /// Marks of neighbouring sheets coincide when their tiles are aligned.
+    pub registration_marks: bool,
+    /// Length of crop marks and size of registration marks.
+    pub mark_size: f32,
+    /// Height of the `row-column` label drawn in the bottom margin of each sheet. `None` disables labels.
+    pub label_height: Option<f32>,
+    /// The area of the layer to tile. Defaults to the layer's bounding box.
+    pub area: Option<Rect>,
+}
+
+impl TileSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_sheet_size(mut self, sheet_size: V2) -> Self {
+        self.sheet_size = sheet_size;
+        self
+    }
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+    pub fn with_overlap(mut self, overlap: f32) -> Self {
+        self.overlap = overlap;
+        self
+    }
+    pub fn with_crop_marks(mut self, crop_marks: bool) -> Self {
+        self.crop_marks = crop_marks;
+        self
+    }
+    pub fn with_registration_marks(mut self, registration_marks: bool) -> Self {
+        self.registration_marks = registration_marks;
+        self
+    }
+    pub fn with_mark_size(mut self, mark_size: f32) -> Self {
+        self.mark_size = mark_size;
+        self
+    }
+    pub fn with_label_height(mut self, label_height: Option<f32>) -> Self {
+        self.label_height = label_height;
+        self
+    }
+    pub fn with_area(mut self, area: Rect) -> Self {
+        self.area = Some(area);
+        self
+    }
+
+    /// Returns the frame of a sheet, its inner rect is the largest possible tile.
+    pub fn sheet(&self) -> Frame {
+        Frame::new_xy(self.sheet_size, self.margin)
+    }
+
+    /// Returns the grid of tiles covering `area`, using as few sheets as possible.
+    ///
+    /// All cells have the same size and neighbouring cells overlap by [`TileSettings::overlap`]. Areas without width or
+    /// height, like the bounds of a single horizontal line, are padded by [`LARGE_EPSILON`] to cover them with one tile.
+    pub fn grid(&self, area: Rect) -> Result<Grid> {
+        let inner = self.sheet().inner_rect().size();
+        if inner.x <= self.overlap || inner.y <= self.overlap {
+            bail!(
+                "sheet size {:?} minus margins must be larger than the overlap {}",
+                self.sheet_size,
+                self.overlap
+            );
+        }
+        let count = |size: f32, inner: f32| {
+            let count = (size - self.overlap) / (inner - self.overlap) - LARGE_EPSILON;
+            (count.ceil() as usize).max(1)
+        };
+        let padding = V2::new(
+            if area.width() < LARGE_EPSILON {
+                LARGE_EPSILON
+            } else {
+                0.0
+            },
+            if area.height() < LARGE_EPSILON {
+                LARGE_EPSILON
+            } else {
+                0.0
+            },
+        );
+        let area = Rect::new(area.bl() - padding, area.tr() + padding);
+        let cols = count(area.width(), inner.x);
+        let rows = count(area.height(), inner.y);
+        // a negative margin between cells makes them overlap
+        Ok(Grid::new(area, rows, cols, -self.overlap))
+    }
+}
+
+impl Default for TileSettings {
+    fn default() -> Self {
+        Self {
+            sheet_size: V2::a4(),
+            margin: 1.5,
+            overlap: 0.0,
+            crop_marks: true,
+            registration_marks: false,
+            mark_size: 0.5,
+            label_height: Some(0.4),
+            area: None,
+        }
+    }
+}
+
+/// One sheet of a layer split by [`Layer::tile`].
+#[derive(Debug, Clone)]
+pub struct Tile {
+    /// Row of the tile, counted from the top.
+    pub row: usize,
+    /// Column of the tile, counted from the left.
+    pub col: usize,
+    /// The area of the original layer on this tile.
+    pub area: Rect,
+    /// Offset from the original layer's coordinates to sheet coordinates.
+    pub offset: V2,
+    /// The shapes inside [`Tile::area`], clipped and moved onto the sheet. Sublayers and props are kept.
+    pub layer: Layer,
+    /// Crop and registration marks in sheet coordinates.
+    pub marks: Layer,
+    /// The tile's label in sheet coordinates.
+    pub label: Layer,
+}
+
+impl Tile {
+    /// Returns the `row-column` label of the tile, both counted from 1.
+    pub fn label_text(&self) -> String {
+        format!("{}-{}", self.row + 1, self.col + 1)
+    }
+
+    /// Returns the tile as a single layer, with the marks and label as sublayers named `marks` and `label`.
+    pub fn to_layer(&self) -> Layer {
+        let mut layer = self.layer.clone();
+        if !self.marks.is_empty() {
+            layer.push_layer(self.marks.clone().with_name("marks"));
+        }
+        if !self.label.is_empty() {
+            layer.push_layer(self.label.clone().with_name("label"));
+        }
+        layer
+    }
+}
+
+impl Layer {
+    /// Splits the layer into tiles that each fit on a sheet of [`TileSettings::sheet_size`], for plotting works larger than the plotter.
+    ///
+    /// Tiles are returned row by row, starting at the top left. Shapes are clipped exactly at the tile borders, so lines
+    /// crossing between tiles continue seamlessly when the sheets are assembled.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![Rect::new_shape(V2::new(0.0, 0.0), V2::new(50.0, 30.0))]);
+    /// let settings = TileSettings::new().with_sheet_size(V2::a4()).with_overlap(1.0);
+    /// let tiles = layer.tile(&settings, SampleSettings::default()).unwrap();
+    /// assert_eq!(tiles.len(), 6);
+    /// assert_eq!(tiles[0].label_text(), "1-1");
+    /// ```
+    pub fn tile(
+        &self,
+        settings: &TileSettings,
+        sample_settings: SampleSettings,
+    ) -> Result<Vec<Tile>> {
+        let Some(area) = settings.area.or_else(|| self.bounding_box()) else {
+            return Ok(Vec::new());
+        };
+        let grid = settings.grid(area)?;
+        let origin = settings.sheet().inner_rect().bl();
+
+        let mut tiles = Vec::with_capacity(grid.rows * grid.cols);
+        for row in 0..grid.rows {
+            for col in 0..grid.cols {
+                // grid rows start at the bottom
+                let cell = grid.get_cell(grid.rows - 1 - row, col);
+                let offset = origin - cell.bl();
+                let placed = Rect::new(origin, origin + cell.size());
+
+                let mut marks = Layer::new();
+                if settings.crop_marks {
+                    marks.push_many(crop_marks(placed, settings.mark_size));
+                }
+                if settings.registration_marks {
+                    let neighbours = [row > 0, col + 1 < grid.cols, row + 1 < grid.rows, col > 0];
+                    marks.push_many(registration_marks(
+                        placed,
+                        neighbours,
+                        settings.overlap,
+                        settings.mark_size,
+                    ));
+                }
+
+                let mut tile = Tile {
+                    row,
+                    col,
+                    area: cell,
+                    offset,
+                    layer: self.clip_to_tile(&cell, offset, sample_settings),
+                    marks,
+                    label: Layer::new(),
+                };
+                if let Some(height) = settings.label_height {
+                    let text = tile.label_text();
+                    let width = label_width(&text, height);
+                    let bl = V2::new(
+                        placed.center().x - width / 2.0,
+                        (placed.bl().y - height) / 2.0,
+                    );
+                    tile.label = Layer::new_from(label_shapes(&text, bl, height));
+                }
+                tiles.push(tile);
+            }
+        }
+        Ok(tiles)
+    }
+
+    fn clip_to_tile(&self, area: &Rect, offset: V2, sample_settings: SampleSettings) -> Layer {
+        let mut shapes = Vec::new();
+        for shape in self.iter() {
+            match area.contains_shape(shape) {
+                Containment::Full => shapes.push(shape.translate(offset)),
+                Containment::None => {}
+                Containment::Partial => {
+                    let strokes: Vec<Vec<V2>> = match shape {
+                        Shape::Path(path) => vec![path.get_points_ref().to_vec()],
+                        Shape::Compound(compound) => compound
+                            .rings()
+                            .map(|ring| ring.points_closed().into_owned())
+                            .collect(),
+                        _ => {
+                            let mut points = shape.get_points(sample_settings);
+                            if points.first() != points.last() {
+                                points.push(points[0]);
+                            }
+                            vec![points]
+                        }
+                    };
+                    for stroke in strokes {
+                        shapes.extend(clip_points_to_rect(&stroke, area).into_iter().map(
+                            |points| {
+                                Path::new_shape_from(
+                                    points.into_iter().map(|point| point + offset).collect(),
+                                )
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        let sublayers = self
+            .iter_sublayers()
+            .map(|sublayer| sublayer.clip_to_tile(area, offset, sample_settings))
+            .collect();
+        Layer::new_from_shapes_and_layers(shapes, sublayers)
+            .with_props(self.props.clone())
+            .with_props_inheritable(self.props_inheritable.clone())
+    }
+}
+
+/// Clips a polyline to `rect` including its border, returning the parts inside.
+///
+/// Unlike masking with [`Plottable::mask_geo_inside`], lines running along the border are kept.
+fn clip_points_to_rect(points: &[V2], rect: &Rect) -> Vec<Vec<V2>> {
+    let mut parts: Vec<Vec<V2>> = Vec::new();
+    let mut current: Vec<V2> = Vec::new();
+    if points.len() == 1 && rect.contains_point(points[0]) {
+        return vec![points.to_vec()];
+    }
+    for segment in points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let delta = to - from;
+        // Liang-Barsky
+        let (mut t_min, mut t_max) = (0.0_f32, 1.0_f32);
+        let mut inside = true;
+        for (p, q) in [
+            (-delta.x, from.x - rect.bl().x),
+            (delta.x, rect.tr().x - from.x),
+            (-delta.y, from.y - rect.bl().y),
+            (delta.y, rect.tr().y - from.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    inside = false;
+                }
+            } else if p < 0.0 {
+                t_min = t_min.max(q / p);
+            } else {
+                t_max = t_max.min(q / p);
+            }
+        }
+        if !inside || t_min > t_max {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let start = if t_min > 0.0 {
+            from + delta * t_min
+        } else {
+            from
+        };
+        let end = if t_max < 1.0 {
+            from + delta * t_max
+        } else {
+            to
+        };
+        if current.last() != Some(&start) {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            current.push(start);
+        }
+        current.push(end);
+        if t_max < 1.0 {
+            parts.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Lines extending the edges of `rect` outwards from each corner, leaving a small gap.
+fn crop_marks(rect: Rect, length: f32) -> Vec<Shape> {
+    let gap = length * 0.25;
+    [
+        (rect.bl(), V2::new(-1.0, -1.0)),
+        (rect.br(), V2::new(1.0, -1.0)),
+        (rect.tr(), V2::new(1.0, 1.0)),
+        (rect.tl(), V2::new(-1.0, 1.0)),
+    ]
+    .into_iter()
+    .flat_map(|(corner, outwards)| {
+        [V2::new(outwards.x, 0.0), V2::new(0.0, outwards.y)].map(|direction| {
+            Path::new_shape_from(vec![
+                corner + direction * gap,
+                corner + direction * (gap + length),
+            ])
+        })
+    })
+    .collect()
+}
+
+/// Crosshairs in the margin around `rect`, on the center line of the overlap with each neighbour
+/// (`[top, right, bottom, left]`).
+fn registration_marks(rect: Rect, neighbours: [bool; 4], overlap: f32, size: f32) -> Vec<Shape> {
+    let outside = size * 0.75;
+    let [top, right, bottom, left] = neighbours;
+    let mut centers = Vec::new();
+    for (has_neighbour, x) in [
+        (left, rect.bl().x + overlap / 2.0),
+        (right, rect.tr().x - overlap / 2.0),
+    ] {
+        if has_neighbour {
+            centers.push(V2::new(x, rect.bl().y - outside));
+            centers.push(V2::new(x, rect.tr().y + outside));
+        }
+    }
+    for (has_neighbour, y) in [
+        (bottom, rect.bl().y + overlap / 2.0),
+        (top, rect.tr().y - overlap / 2.0),
+    ] {
+        if has_neighbour {
+            centers.push(V2::new(rect.bl().x - outside, y));
+            centers.push(V2::new(rect.tr().x + outside, y));
+        }
+    }
+
+    let half = size / 2.0;
+    centers
+        .into_iter()
+        .flat_map(|center| {
+            [
+                Path::new_shape_from(vec![
+                    center - V2::new(half, 0.0),
+                    center + V2::new(half, 0.0),
+                ]),
+                Path::new_shape_from(vec![
+                    center - V2::new(0.0, half),
+                    center + V2::new(0.0, half),
+                ]),
+                Circle::new_shape(center, size * 0.3),
+            ]
+        })
+        .collect()
+}
+
+/// Advance of a label character relative to the label height.
+const LABEL_ADVANCE: f32 = 0.75;
+
+fn label_width(text: &str, height: f32) -> f32 {
+    let count = text.chars().count() as f32;
+    (count * LABEL_ADVANCE - (LABEL_ADVANCE - 0.5)).max(0.0) * height
+}
+
+/// Strokes of a minimal seven segment font supporting digits and `-`, with glyphs half as wide as high.
+fn label_shapes(text: &str, bl: V2, height: f32) -> Vec<Shape> {
+    let mut shapes = Vec::new();
+    for (i, ch) in text.chars().enumerate() {
+        let origin = bl + V2::new(i as f32 * LABEL_ADVANCE * height, 0.0);
+        // corners of the glyph: top, middle and bottom, each left and right
+        let point = |name: &str| {
+            let (x, y) = match name {
+                "tl" => (0.0, 1.0),
+                "tr" => (0.5, 1.0),
+                "ml" => (0.0, 0.5),
+                "mr" => (0.5, 0.5),
+                "bl" => (0.0, 0.0),
+                _ => (0.5, 0.0),
+            };
+            origin + V2::new(x, y) * height
+        };
+        let strokes: &[&[&str]] = match ch {
+            '0' => &[&["tl", "tr", "br", "bl", "tl"]],
+            '1' => &[&["tr", "br"]],
+            '2' => &[&["tl", "tr", "mr", "ml", "bl", "br"]],
+            '3' => &[&["tl", "tr", "br", "bl"], &["ml", "mr"]],
+            '4' => &[&["tl", "ml", "mr"], &["tr", "br"]],
+            '5' => &[&["tr", "tl", "ml", "mr", "br", "bl"]],
+            '6' => &[&["tr", "tl", "bl", "br", "mr", "ml"]],
+            '7' => &[&["tl", "tr", "br"]],
+            '8' => &[&["tl", "tr", "br", "bl", "tl"], &["ml", "mr"]],
+            '9' => &[&["bl", "br", "tr", "tl", "ml", "mr"]],
+            '-' => &[&["ml", "mr"]],
+            _ => &[],
+        };
+        for stroke in strokes {
+            shapes.push(Path::new_shape_from(
+                stroke.iter().map(|name| point(name)).collect(),
+            ));
+        }
+    }
+    shapes
+}
@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod test_tiling {
+    use crate::{
+        BoundingBox, Circle, ColorRgb, Layer, Path, Plottable, Rect, SampleSettings, Shape,
+        TileSettings, Translate, V2,
+    };
+
+    fn settings() -> TileSettings {
+        TileSettings::new()
+            .with_sheet_size(V2::new(12.0, 10.0))
+            .with_margin(1.0)
+    }
+
+    #[test]
+    fn grid() {
+        let area = Rect::new(V2::zero(), V2::new(50.0, 30.0));
+        let grid = TileSettings::new()
+            .with_sheet_size(V2::a4())
+            .with_margin(1.5)
+            .with_overlap(1.0)
+            .grid(area)
+            .unwrap();
+        assert_eq!((grid.rows, grid.cols), (2, 3));
+        let cell = grid.get_cell_size();
+        assert!(cell.x <= 18.0 && cell.y <= 26.7);
+        assert!((grid.get_cell(0, 1).bl().x - (grid.get_cell(0, 0).tr().x - 1.0)).abs() < 1e-4);
+        assert!((grid.get_cell(1, 2).tr().dist(area.tr())) < 1e-4);
+
+        // an area fitting on one sheet exactly
+        let grid = settings()
+            .grid(Rect::new(V2::zero(), V2::new(10.0, 8.0)))
+            .unwrap();
+        assert_eq!((grid.rows, grid.cols), (1, 1));
+
+        assert!(settings()
+            .with_overlap(8.0)
+            .grid(Rect::new(V2::zero(), V2::new(30.0, 30.0)))
+            .is_err());
+    }
+
+    #[test]
+    fn degenerate_area() {
+        // a single horizontal line has a bounding box without height
+        let layer = Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(0.0, 2.0),
+            V2::new(25.0, 2.0),
+        ])]);
+        let tiles = layer.tile(&settings(), SampleSettings::default()).unwrap();
+        assert_eq!(tiles.len(), 3);
+        let length: f32 = tiles
+            .iter()
+            .flat_map(|tile| tile.layer.iter())
+            .map(|shape| shape.length())
+            .sum();
+        assert!((length - 25.0).abs() < 1e-3);
+
+        let point = Layer::new_from(vec![Path::new_shape_from(vec![V2::xy(1.0), V2::xy(1.0)])]);
+        assert_eq!(
+            point
+                .tile(&settings(), SampleSettings::default())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn lines_stitch_across_tiles() {
+        let layer = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(0.0, 5.0), V2::new(30.0, 11.0)]),
+            Rect::new_shape(V2::zero(), V2::new(30.0, 16.0)),
+        ]);
+        let tiles = layer.tile(&settings(), SampleSettings::default()).unwrap();
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[0].area.tl(), V2::new(0.0, 16.0));
+
+        // the diagonal line, moved back from the sheets, continues where the previous tile ends
+        let mut pieces: Vec<(V2, V2)> = tiles
+            .iter()
+            .flat_map(|tile| tile.layer.translate(V2::zero() - tile.offset).shapes)
+            .filter_map(|shape| {
+                let points = shape.get_points(SampleSettings::default());
+                let (first, last) = (points[0], *points.last().unwrap());
+                let on_line = |point: V2| (point.y - (5.0 + point.x * 0.2)).abs() < 1e-3;
+                (points.len() == 2 && on_line(first) && on_line(last) && first != last)
+                    .then_some((first.min(last), first.max(last)))
+            })
+            .collect();
+        pieces.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+        assert!(pieces.len() >= 3);
+        assert!(pieces[0].0.dist(V2::new(0.0, 5.0)) < 1e-3);
+        assert!(pieces.last().unwrap().1.dist(V2::new(30.0, 11.0)) < 1e-3);
+        for pair in pieces.windows(2) {
+            assert!(pair[0].1.dist(pair[1].0) < 1e-3);
+        }
+
+        // every tile fits into the sheet margin
+        let inner = settings().sheet().inner_rect();
+        for tile in tiles.iter() {
+            let bounding_box = tile.layer.bounding_box().unwrap();
+            assert!(bounding_box.bl().x >= inner.bl().x - 1e-3);
+            assert!(bounding_box.tr().x <= inner.tr().x + 1e-3);
+            assert!(bounding_box.tr().y <= inner.tr().y + 1e-3);
+        }
+    }
+
+    #[test]
+    fn keeps_sublayers_and_unclipped_shapes() {
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![
+                Layer::new_from(vec![Circle::new_shape(V2::new(3.0, 3.0), 1.0)])
+                    .with_name("dots")
+                    .with_color(ColorRgb::red()),
+            ],
+        );
+        let settings = settings().with_area(Rect::new(V2::zero(), V2::new(20.0, 8.0)));
+        let tiles = layer.tile(&settings, SampleSettings::default()).unwrap();
+        assert_eq!(tiles.len(), 2);
+
+        let dots = &tiles[0].layer.sublayers[0];
+        assert_eq!(dots.props, layer.sublayers[0].props);
+        assert_eq!(dots.props_inheritable, layer.sublayers[0].props_inheritable);
+        assert_eq!(
+            dots.shapes,
+            vec![Circle::new_shape(V2::new(3.0, 3.0) + tiles[0].offset, 1.0)]
+        );
+        assert!(tiles[1].layer.sublayers[0].shapes.is_empty());
+    }
+
+    #[test]
+    fn registration_marks_coincide() {
+        let layer = Layer::new_from(vec![Rect::new_shape(V2::zero(), V2::new(25.0, 8.0))]);
+        let settings = settings()
+            .with_overlap(2.0)
+            .with_crop_marks(false)
+            .with_registration_marks(true);
+        let tiles = layer.tile(&settings, SampleSettings::default()).unwrap();
+        assert_eq!(tiles.len(), 3);
+
+        let centers = |index: usize| -> Vec<V2> {
+            tiles[index]
+                .marks
+                .translate(V2::zero() - tiles[index].offset)
+                .iter()
+                .filter_map(|shape| match shape {
+                    Shape::Circle(circle) => Some(circle.center),
+                    _ => None,
+                })
+                .collect()
+        };
+        // outer tiles have one neighbour, the middle tile two
+        assert_eq!(centers(0).len(), 2);
+        assert_eq!(centers(1).len(), 4);
+        assert_eq!(centers(2).len(), 2);
+        for center in centers(0) {
+            assert!(centers(1).iter().any(|other| other.dist(center) < 1e-4));
+        }
+        for center in centers(2) {
+            assert!(centers(1).iter().any(|other| other.dist(center) < 1e-4));
+        }
+    }
+
+    #[test]
+    fn marks_and_labels() {
+        let layer = Layer::new_from(vec![Rect::new_shape(V2::zero(), V2::new(15.0, 15.0))]);
+        let tiles = layer.tile(&settings(), SampleSettings::default()).unwrap();
+        let labels: Vec<String> = tiles.iter().map(|tile| tile.label_text()).collect();
+        assert_eq!(labels, vec!["1-1", "1-2", "2-1", "2-2"]);
+        assert_eq!((tiles[1].row, tiles[1].col), (0, 1));
+        assert_eq!(tiles[0].area.tl(), V2::new(0.0, 15.0));
+
+        // two lines per corner
+        assert_eq!(tiles[0].marks.len(), 8);
+        let label_box = tiles[3].label.bounding_box().unwrap();
+        assert!(label_box.tr().y <= 1.0);
+        assert!(label_box.bl().y >= 0.0);
+
+        let combined = tiles[0].to_layer();
+        let names: Vec<_> = combined
+            .iter_sublayers()
+            .map(|sublayer| sublayer.props.name.clone().unwrap())
+            .collect();
+        assert_eq!(names, vec!["marks", "label"]);
+
+        let unlabeled = layer
+            .tile(
+                &settings().with_label_height(None).with_crop_marks(false),
+                SampleSettings::default(),
+            )
+            .unwrap();
+        assert!(unlabeled[0].to_layer().sublayers.is_empty());
+        assert!(Layer::new()
+            .tile(&settings(), SampleSettings::default())
+            .unwrap()
+            .is_empty());
+    }
+}
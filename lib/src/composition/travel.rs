@@ -0,0 +1,565 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{Layer, Path, Plottable, SampleSettings, Shape, V2};
+
+/// Settings for [`Layer::optimize_travel`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TravelSettings {
+    /// Position of the pen before the first [`Shape`] is drawn.
+    pub start: V2,
+    /// Maximum number of 2-opt passes over the drawing order. `0` keeps the greedy order.
+    pub max_iterations: usize,
+    /// Maximum number of consecutive [`Shape`]s reversed in a single 2-opt move.
+    pub window: usize,
+    /// Stops refining once this much time has passed.
+    pub time_budget: Option<Duration>,
+}
+
+impl TravelSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_start(mut self, start: V2) -> Self {
+        self.start = start;
+        self
+    }
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+    pub fn with_time_budget(mut self, time_budget: Option<Duration>) -> Self {
+        self.time_budget = time_budget;
+        self
+    }
+}
+
+impl Default for TravelSettings {
+    fn default() -> Self {
+        Self {
+            start: V2::zero(),
+            max_iterations: 20,
+            window: 100,
+            time_budget: None,
+        }
+    }
+}
+
+/// Result of [`Layer::optimize_travel`].
+#[derive(Clone, Debug)]
+pub struct TravelOptimization {
+    pub layer: Layer,
+    /// Pen-up travel distance of the original `Layer`.
+    pub travel_before: f32,
+    /// Pen-up travel distance of the optimized `Layer`.
+    pub travel_after: f32,
+}
+
+impl Layer {
+    /// Total distance travelled with the pen up when plotting all [`Shape`]s in plot order, starting at `start`.
+    ///
+    /// Every [`Shape`] is entered the same way it is plotted, see [`Plottable::get_points_from`] and [`Layer::layers_in_plot_order`].
+    pub fn pen_up_distance(&self, start: V2, sample_settings: SampleSettings) -> f32 {
+        let mut pos = start;
+        let mut distance = 0.0;
+        for (layer, props) in self.layers_in_plot_order() {
+            for shape in layer.iter() {
+                for _ in 0..props.plot_passes() {
+                    distance += Self::travel_through(shape, &mut pos, sample_settings);
+                }
+            }
+        }
+        distance
+    }
+
+    fn travel_through(shape: &Shape, pos: &mut V2, sample_settings: SampleSettings) -> f32 {
+        if let Shape::Compound(compound) = shape {
+            return compound
+                .rings()
+                .map(|ring| Self::travel_through(&ring.into(), pos, sample_settings))
+                .sum();
+        }
+
+        let points = shape.get_points_from(*pos, sample_settings);
+        if points.len() < 2 {
+            return 0.0;
+        }
+        let distance = pos.dist(points[0]);
+        *pos = *points.last().unwrap();
+        distance
+    }
+
+    /// Returns a new `Layer` with [`Shape`]s reordered to reduce the pen-up travel distance.
+    ///
+    /// Unlike [`Layer::optimize`], open [`Path`]s are reversed and closed [`Path`]s are rotated to start at their best vertex.
+    /// The greedy order is refined with 2-opt moves until no improvement is found or the budget in `settings` is used up.
+    /// Sublayers keep their structure and are optimized individually in plot order (see [`Layer::layers_in_plot_order`]),
+    /// continuing where the previous one ended.
+    ///
+    /// The original order is returned if the optimization doesn't reduce the travel distance.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let layer = Layer::new_from(vec![
+    ///     Path::new_shape_from(vec![V2::new(3.0, 0.0), V2::new(2.0, 0.0)]),
+    ///     Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+    /// ]);
+    /// let optimized = layer.optimize_travel(&TravelSettings::default(), SampleSettings::default());
+    /// assert_eq!(optimized.travel_before, 4.0);
+    /// assert_eq!(optimized.travel_after, 1.0);
+    /// ```
+    pub fn optimize_travel(
+        &self,
+        settings: &TravelSettings,
+        sample_settings: SampleSettings,
+    ) -> TravelOptimization {
+        let deadline = settings
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
+        let mut pos = settings.start;
+        let optimized_shapes: Vec<_> = self
+            .layers_in_plot_order()
+            .into_iter()
+            .map(|(layer, _)| {
+                (
+                    layer,
+                    layer.optimize_shapes_from(&mut pos, settings, deadline),
+                )
+            })
+            .collect();
+        let optimized = self.with_optimized_shapes(&optimized_shapes);
+
+        let travel_before = self.pen_up_distance(settings.start, sample_settings);
+        let travel_after = optimized.pen_up_distance(settings.start, sample_settings);
+        if travel_after < travel_before {
+            TravelOptimization {
+                layer: optimized,
+                travel_before,
+                travel_after,
+            }
+        } else {
+            TravelOptimization {
+                layer: self.clone(),
+                travel_before,
+                travel_after: travel_before,
+            }
+        }
+    }
+
+    /// Returns the layer's own shapes in an order that reduces travel, starting and updating `pos`.
+    fn optimize_shapes_from(
+        &self,
+        pos: &mut V2,
+        settings: &TravelSettings,
+        deadline: Option<Instant>,
+    ) -> Vec<Shape> {
+        let mut items = Vec::with_capacity(self.shapes.len());
+        let mut not_plotted = Vec::new();
+        for (i, shape) in self.iter().enumerate() {
+            match TravelEntry::new(shape) {
+                Some(entry) => items.push((i, entry)),
+                None => not_plotted.push(shape.clone()),
+            }
+        }
+        let entries: Vec<_> = items.iter().map(|(_, entry)| entry.clone()).collect();
+
+        let mut visits = greedy_order(&entries, *pos);
+        two_opt(&mut visits, *pos, settings, deadline);
+        select_entry_points(&mut visits, &entries, *pos);
+        if let Some(last) = visits.last() {
+            *pos = last.exit;
+        }
+
+        let mut shapes: Vec<_> = visits
+            .iter()
+            .map(|visit| {
+                let shape = &self.shapes[items[visit.item].0];
+                match (shape, &entries[visit.item]) {
+                    (Shape::Path(path), TravelEntry::Ends { .. }) if visit.reversed => {
+                        Shape::Path(path.reverse())
+                    }
+                    (Shape::Path(_), TravelEntry::Ring(vertices)) => {
+                        Shape::Path(rotate_ring(vertices, visit.entry))
+                    }
+                    _ => shape.clone(),
+                }
+            })
+            .collect();
+        shapes.extend(not_plotted);
+        shapes
+    }
+
+    /// Rebuilds the layer tree with the shapes of each layer replaced by its entry in `optimized_shapes`.
+    fn with_optimized_shapes(&self, optimized_shapes: &[(&Layer, Vec<Shape>)]) -> Self {
+        let shapes = optimized_shapes
+            .iter()
+            .find(|(layer, _)| std::ptr::eq(*layer, self))
+            .map(|(_, shapes)| shapes.clone())
+            .unwrap_or_else(|| self.shapes.clone());
+        let sublayers = self
+            .iter_sublayers()
+            .map(|sublayer| sublayer.with_optimized_shapes(optimized_shapes))
+            .collect();
+
+        Layer::new_from_shapes_and_layers(shapes, sublayers)
+            .with_props_inheritable(self.props_inheritable.clone())
+            .with_props(self.props.clone())
+    }
+}
+
+/// How the pen can enter and leave a [`Shape`].
+#[derive(Clone, Debug)]
+enum TravelEntry {
+    /// Starts and ends at fixed points, possibly reversible.
+    Ends {
+        start: V2,
+        end: V2,
+        reversible: bool,
+    },
+    /// Closed path that can be rotated to start at any of its vertices.
+    Ring(Vec<V2>),
+    /// Starts and ends at the corner closest to the pen.
+    Corners(Vec<V2>),
+    /// Starts and ends at the point closest to the pen.
+    Circle { center: V2, radius: f32 },
+}
+
+impl TravelEntry {
+    /// Returns `None` for [`Shape`]s that aren't plotted at all.
+    fn new(shape: &Shape) -> Option<Self> {
+        match shape {
+            Shape::Path(path) => {
+                let points = path.get_points_ref();
+                if points.len() < 2 {
+                    None
+                } else if path.is_closed() {
+                    Some(Self::Ring(points[..points.len() - 1].to_vec()))
+                } else {
+                    Some(Self::Ends {
+                        start: points[0],
+                        end: *points.last().unwrap(),
+                        reversible: true,
+                    })
+                }
+            }
+            Shape::Circle(circle) => Some(Self::Circle {
+                center: circle.center,
+                radius: circle.radius,
+            }),
+            Shape::Rect(rect) => Some(Self::Corners(vec![
+                rect.bl(),
+                rect.tl(),
+                rect.tr(),
+                rect.br(),
+            ])),
+            Shape::Compound(compound) => {
+                let start = *compound.outer().get_start()?;
+                let end = compound
+                    .rings()
+                    .last()
+                    .and_then(|ring| ring.get_start().copied())
+                    .unwrap_or(start);
+                Some(Self::Ends {
+                    start,
+                    end,
+                    reversible: false,
+                })
+            }
+        }
+    }
+
+    /// Points used to find this entry in the [`EntryIndex`].
+    fn index_points(&self, cell_size: f32) -> Vec<V2> {
+        match self {
+            Self::Ends { start, end, .. } => vec![*start, *end],
+            Self::Ring(vertices) | Self::Corners(vertices) => vertices.clone(),
+            Self::Circle { center, radius } => {
+                let count =
+                    ((std::f32::consts::TAU * radius / cell_size).ceil() as usize).clamp(8, 256);
+                (0..count)
+                    .map(|i| {
+                        let angle = std::f32::consts::TAU * i as f32 / count as f32;
+                        *center + V2::new(angle.cos(), angle.sin()) * *radius
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The cheapest way to draw this entry when the pen is at `pos`.
+    fn visit_from(&self, item: usize, pos: V2) -> Visit {
+        match self {
+            Self::Ends {
+                start,
+                end,
+                reversible,
+            } => {
+                let reversed = *reversible && pos.dist_squared(*end) < pos.dist_squared(*start);
+                let (entry, exit) = if reversed {
+                    (*end, *start)
+                } else {
+                    (*start, *end)
+                };
+                Visit {
+                    item,
+                    entry,
+                    exit,
+                    reversed,
+                    flippable: *reversible || start == end,
+                }
+            }
+            Self::Ring(vertices) | Self::Corners(vertices) => {
+                let point = closest(vertices, |vertex| pos.dist_squared(vertex));
+                Visit::closed(item, point)
+            }
+            Self::Circle { center, radius } => {
+                let direction = if pos == *center {
+                    V2::new(1.0, 0.0)
+                } else {
+                    (pos - *center).normalize()
+                };
+                Visit::closed(item, *center + direction * *radius)
+            }
+        }
+    }
+}
+
+/// A [`TravelEntry`] placed in the drawing order.
+#[derive(Clone, Copy, Debug)]
+struct Visit {
+    item: usize,
+    entry: V2,
+    exit: V2,
+    reversed: bool,
+    /// Whether the visit may be drawn in the opposite direction.
+    flippable: bool,
+}
+
+impl Visit {
+    fn closed(item: usize, point: V2) -> Self {
+        Self {
+            item,
+            entry: point,
+            exit: point,
+            reversed: false,
+            flippable: true,
+        }
+    }
+
+    fn flip(&mut self) {
+        std::mem::swap(&mut self.entry, &mut self.exit);
+        self.reversed = !self.reversed;
+    }
+}
+
+fn closest<F: Fn(V2) -> f32>(points: &[V2], cost: F) -> V2 {
+    points
+        .iter()
+        .copied()
+        .min_by(|a, b| cost(*a).total_cmp(&cost(*b)))
+        .unwrap()
+}
+
+fn rotate_ring(vertices: &[V2], start: V2) -> Path {
+    let offset = vertices.iter().position(|v| *v == start).unwrap_or(0);
+    Path::new_from_iter((0..=vertices.len()).map(|i| vertices[(offset + i) % vertices.len()]))
+}
+
+/// Nearest neighbour order, always continuing with the closest not yet drawn entry.
+fn greedy_order(entries: &[TravelEntry], start: V2) -> Vec<Visit> {
+    let mut index = EntryIndex::new(entries);
+    let mut used = vec![false; entries.len()];
+    let mut visits = Vec::with_capacity(entries.len());
+    let mut pos = start;
+    while let Some(visit) = index.nearest(pos, entries, &used) {
+        used[visit.item] = true;
+        pos = visit.exit;
+        visits.push(visit);
+    }
+    visits
+}
+
+/// Reverses sections of the drawing order as long as this reduces the travel distance.
+fn two_opt(visits: &mut [Visit], start: V2, settings: &TravelSettings, deadline: Option<Instant>) {
+    let is_over_budget = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    for _ in 0..settings.max_iterations {
+        let mut improved = false;
+        for i in 0..visits.len() {
+            if is_over_budget() {
+                return;
+            }
+            let before = if i == 0 { start } else { visits[i - 1].exit };
+            let last = visits.len().min(i + settings.window.max(1));
+            for j in i..last {
+                if !visits[j].flippable {
+                    break;
+                }
+                let (removed, added) = match visits.get(j + 1) {
+                    Some(after) => (
+                        before.dist(visits[i].entry) + visits[j].exit.dist(after.entry),
+                        before.dist(visits[j].exit) + visits[i].entry.dist(after.entry),
+                    ),
+                    None => (before.dist(visits[i].entry), before.dist(visits[j].exit)),
+                };
+                if added < removed - f32::EPSILON * removed.max(1.0) {
+                    visits[i..=j].reverse();
+                    visits[i..=j].iter_mut().for_each(Visit::flip);
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            return;
+        }
+    }
+}
+
+/// Picks the entry point of closed [`Shape`]s for their final neighbours in the drawing order.
+fn select_entry_points(visits: &mut [Visit], entries: &[TravelEntry], start: V2) {
+    for i in 0..visits.len() {
+        let before = if i == 0 { start } else { visits[i - 1].exit };
+        let visit = visits[i];
+        visits[i] = match &entries[visit.item] {
+            // closed paths are rotated explicitly, so their neighbours on both sides count
+            TravelEntry::Ring(vertices) => match visits.get(i + 1) {
+                Some(after) => {
+                    let after = after.entry;
+                    Visit::closed(
+                        visit.item,
+                        closest(vertices, |vertex| before.dist(vertex) + vertex.dist(after)),
+                    )
+                }
+                None => entries[visit.item].visit_from(visit.item, before),
+            },
+            // all others start where they are closest to the pen when plotted
+            TravelEntry::Corners(_) | TravelEntry::Circle { .. } => {
+                entries[visit.item].visit_from(visit.item, before)
+            }
+            TravelEntry::Ends { .. } => visit,
+        };
+    }
+}
+
+/// Spatial hash of the points where [`TravelEntry`]s can be entered.
+struct EntryIndex {
+    cell_size: f32,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    min_cell: (i64, i64),
+    max_cell: (i64, i64),
+}
+
+impl EntryIndex {
+    fn new(entries: &[TravelEntry]) -> Self {
+        let points: Vec<V2> = entries
+            .iter()
+            .flat_map(|entry| entry.index_points(f32::MAX))
+            .collect();
+        let (min, max) = points
+            .iter()
+            .fold((V2::xy(f32::MAX), V2::xy(f32::MIN)), |(min, max), point| {
+                (min.min(*point), max.max(*point))
+            });
+        let size = max - min;
+        let cell_size = if points.is_empty() {
+            1.0
+        } else {
+            (size.x.max(size.y) / (points.len() as f32).sqrt()).max(f32::EPSILON.sqrt())
+        };
+
+        let mut index = Self {
+            cell_size,
+            cells: HashMap::new(),
+            min_cell: (i64::MAX, i64::MAX),
+            max_cell: (i64::MIN, i64::MIN),
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            for point in entry.index_points(cell_size) {
+                let cell = index.cell(point);
+                index.min_cell = (index.min_cell.0.min(cell.0), index.min_cell.1.min(cell.1));
+                index.max_cell = (index.max_cell.0.max(cell.0), index.max_cell.1.max(cell.1));
+                let items = index.cells.entry(cell).or_default();
+                if items.last() != Some(&i) {
+                    items.push(i);
+                }
+            }
+        }
+        index
+    }
+
+    fn cell(&self, point: V2) -> (i64, i64) {
+        (
+            (point.x / self.cell_size).floor() as i64,
+            (point.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// Searches rings of cells around `pos` until no closer entry can be found further out.
+    fn nearest(&mut self, pos: V2, entries: &[TravelEntry], used: &[bool]) -> Option<Visit> {
+        let (cell_x, cell_y) = self.cell(pos);
+        let max_ring = [
+            cell_x - self.min_cell.0,
+            self.max_cell.0 - cell_x,
+            cell_y - self.min_cell.1,
+            self.max_cell.1 - cell_y,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+        .max(0);
+
+        let mut best: Option<(Visit, f32)> = None;
+        for ring in 0..=max_ring {
+            // far away from everything, checking all cells is cheaper than walking the ring
+            if ring * 8 > self.cells.len() as i64 {
+                let cells: Vec<_> = self.cells.keys().copied().collect();
+                self.check_cells(cells, pos, entries, used, &mut best);
+                break;
+            }
+            let cells = ((cell_x - ring)..=(cell_x + ring))
+                .flat_map(|x| ((cell_y - ring)..=(cell_y + ring)).map(move |y| (x, y)))
+                .filter(|(x, y)| (x - cell_x).abs() == ring || (y - cell_y).abs() == ring);
+            self.check_cells(cells, pos, entries, used, &mut best);
+            if best.is_some_and(|(_, best_dist)| best_dist <= ring as f32 * self.cell_size) {
+                break;
+            }
+        }
+        best.map(|(visit, _)| visit)
+    }
+
+    fn check_cells<I>(
+        &mut self,
+        cells: I,
+        pos: V2,
+        entries: &[TravelEntry],
+        used: &[bool],
+        best: &mut Option<(Visit, f32)>,
+    ) where
+        I: IntoIterator<Item = (i64, i64)>,
+    {
+        for cell in cells {
+            let Some(items) = self.cells.get_mut(&cell) else {
+                continue;
+            };
+            items.retain(|item| !used[*item]);
+            if items.is_empty() {
+                self.cells.remove(&cell);
+                continue;
+            }
+            for item in items.iter() {
+                let visit = entries[*item].visit_from(*item, pos);
+                let dist = pos.dist(visit.entry);
+                if best.is_none_or(|(_, best_dist)| dist < best_dist) {
+                    *best = Some((visit, dist));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod test_travel {
+    use std::time::Duration;
+
+    use crate::{Circle, Layer, Path, Rect, Rng, SampleSettings, Shape, TravelSettings, V2};
+
+    #[test]
+    fn pen_up_distance() {
+        let l = Layer::new_from(vec![
+            Path::new_shape_from(vec![V2::new(1.0, 0.0), V2::new(2.0, 0.0)]),
+            Path::new_shape_from(vec![V2::new(5.0, 0.0), V2::new(3.0, 0.0)]),
+        ]);
+        // the second path is entered from its closer end
+        assert_eq!(
+            l.pen_up_distance(V2::zero(), SampleSettings::default()),
+            2.0
+        );
+        assert_eq!(
+            l.pen_up_distance(V2::new(2.0, 0.0), SampleSettings::default()),
+            2.0
+        );
+        assert_eq!(
+            Layer::new().pen_up_distance(V2::zero(), SampleSettings::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn rotates_closed_paths() {
+        let square = vec![
+            V2::new(10.0, 0.0),
+            V2::new(11.0, 0.0),
+            V2::new(11.0, 1.0),
+            V2::new(10.0, 1.0),
+            V2::new(10.0, 0.0),
+        ];
+        let l = Layer::new_from(vec![Path::new_shape_from(square)]);
+        let optimized = l.optimize_travel(
+            &TravelSettings::new().with_start(V2::new(12.0, 2.0)),
+            SampleSettings::default(),
+        );
+
+        let Shape::Path(path) = &optimized.layer.shapes[0] else {
+            panic!("expected path");
+        };
+        assert_eq!(path.get_start(), Some(&V2::new(11.0, 1.0)));
+        assert_eq!(path.get_end(), Some(&V2::new(11.0, 1.0)));
+        assert_eq!(path.get_points_ref().len(), 5);
+        assert!((optimized.travel_after - 2.0_f32.sqrt()).abs() < 0.001);
+        assert!(optimized.travel_after < optimized.travel_before);
+    }
+
+    #[test]
+    fn keeps_structure() {
+        let sublayer = Layer::new_from(vec![
+            Circle::new_shape(V2::new(5.0, 0.0), 1.0),
+            Rect::new_shape(V2::new(1.0, 1.0), V2::new(2.0, 2.0)),
+        ])
+        .with_name("sub");
+        let l = Layer::new_from_shapes_and_layers(
+            vec![
+                Path::new_shape_from(vec![V2::new(9.0, 0.0), V2::new(8.0, 0.0)]),
+                Path::new_shape(),
+                Path::new_shape_from(vec![V2::new(0.0, 0.0), V2::new(1.0, 0.0)]),
+            ],
+            vec![sublayer],
+        )
+        .with_name("root");
+        let optimized = l.optimize_travel(&TravelSettings::default(), SampleSettings::default());
+
+        assert_eq!(optimized.layer.len(), 3);
+        assert_eq!(optimized.layer.len_recursive(), 5);
+        assert_eq!(optimized.layer.props.name, l.props.name);
+        assert_eq!(
+            optimized.layer.sublayers[0].props.name,
+            l.sublayers[0].props.name
+        );
+        assert!(optimized.travel_after < optimized.travel_before);
+        assert_eq!(
+            optimized.travel_after,
+            optimized
+                .layer
+                .pen_up_distance(V2::zero(), SampleSettings::default())
+        );
+    }
+
+    #[test]
+    fn follows_plot_priority() {
+        let left = Path::new_shape_from(vec![V2::new(-3.0, 0.0), V2::new(-4.0, 0.0)]);
+        let right = Path::new_shape_from(vec![V2::new(13.0, 0.0), V2::new(14.0, 0.0)]);
+        let layer = Layer::new_from_shapes_and_layers(
+            vec![],
+            vec![
+                Layer::new_from(vec![left.clone(), right.clone()]).with_plot_priority(1),
+                Layer::new_from(vec![Path::new_shape_from(vec![
+                    V2::new(10.0, 0.0),
+                    V2::new(11.0, 0.0),
+                ])]),
+            ],
+        );
+        let optimized =
+            layer.optimize_travel(&TravelSettings::default(), SampleSettings::default());
+
+        // the second sublayer is plotted first, ending at x = 11
+        assert_eq!(optimized.travel_before, 41.0);
+        assert_eq!(optimized.travel_after, 29.0);
+        assert_eq!(optimized.layer.sublayers[0].shapes, vec![right, left]);
+    }
+
+    #[test]
+    fn improves_random_segments() {
+        let mut rng = Rng::new(42);
+        let shapes: Vec<_> = (0..2000)
+            .map(|_| {
+                let from = V2::new(rng.rand_range(0.0, 100.0), rng.rand_range(0.0, 100.0));
+                let to = from + V2::new(rng.rand_range(-1.0, 1.0), rng.rand_range(-1.0, 1.0));
+                Path::new_shape_from(vec![from, to])
+            })
+            .collect();
+        let l = Layer::new_from(shapes);
+
+        let greedy = l.optimize_travel(
+            &TravelSettings::new().with_max_iterations(0),
+            SampleSettings::default(),
+        );
+        let refined = l.optimize_travel(&TravelSettings::default(), SampleSettings::default());
+
+        assert_eq!(refined.layer.len(), l.len());
+        assert!(greedy.travel_after < greedy.travel_before * 0.1);
+        assert!(refined.travel_after < greedy.travel_after);
+        assert_eq!(refined.travel_before, greedy.travel_before);
+    }
+
+    #[test]
+    fn time_budget() {
+        let shapes: Vec<_> = (0..100)
+            .map(|i| Circle::new_shape(V2::new((i * 37 % 100) as f32, (i * 11 % 100) as f32), 0.5))
+            .collect();
+        let l = Layer::new_from(shapes);
+        let optimized = l.optimize_travel(
+            &TravelSettings::new().with_time_budget(Some(Duration::ZERO)),
+            SampleSettings::default(),
+        );
+        assert_eq!(optimized.layer.len(), 100);
+        assert!(optimized.travel_after <= optimized.travel_before);
+    }
+}
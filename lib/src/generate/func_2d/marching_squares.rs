@@ -1,13 +1,20 @@
-use crate::{Line, Rect, SampleSettings, V2};
+use std::collections::HashMap;
+
+use crate::{Line, Path, Rect, SampleSettings, V2};
 
 use super::sample_triangle::{SamplePoint, SampleRect};
 
 pub struct MarchingSquares {
     rects: Vec<SampleRect>,
+    cell_size: V2,
 }
 
 impl MarchingSquares {
-    pub fn new(bounds: Rect, sample_settings: SampleSettings, function: fn(V2) -> f32) -> Self {
+    pub fn new<F: Fn(V2) -> f32>(
+        bounds: Rect,
+        sample_settings: SampleSettings,
+        function: F,
+    ) -> Self {
         let num = (bounds.size() * sample_settings.points_per_unit).ceil_to_int();
         let mut rects: Vec<SampleRect> =
             Vec::with_capacity((num.x as usize + 1) * (num.y as usize + 1));
@@ -45,7 +52,10 @@ impl MarchingSquares {
             last_row = new_row;
         }
 
-        Self { rects }
+        Self {
+            rects,
+            cell_size: bounds.size() / V2::new(num.x as f32, num.y as f32),
+        }
     }
 
     pub fn get_lines(&self, target_value: f32) -> Vec<Line> {
@@ -59,4 +69,78 @@ impl MarchingSquares {
         }
         lines
     }
+
+    /// Returns the iso-lines at `target_value` joined into connected [`Path`]s. Closed contours start and end at the same point.
+    ///
+    /// Unlike [`MarchingSquares::get_lines`], sample values equal to `target_value` count as above it, so contours
+    /// through sample points stay connected. Bounds without area have no contours.
+    pub fn get_paths(&self, target_value: f32) -> Vec<Path> {
+        if !(self.cell_size.x > 0.0 && self.cell_size.y > 0.0) {
+            return Vec::new();
+        }
+        let quantize = |point: V2| {
+            let scaled = point / self.cell_size * 1000.0;
+            (scaled.x.round() as i64, scaled.y.round() as i64)
+        };
+
+        let lines: Vec<Line> = self
+            .rects
+            .iter()
+            .flat_map(|rect| rect.triangles.iter())
+            .filter_map(|triangle| triangle.get_intersection_line_connected(target_value))
+            .filter(|line| quantize(line.from) != quantize(line.to))
+            .collect();
+
+        let mut lines_at_point: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (i, line) in lines.iter().enumerate() {
+            lines_at_point
+                .entry(quantize(line.from))
+                .or_default()
+                .push(i);
+            lines_at_point.entry(quantize(line.to)).or_default().push(i);
+        }
+
+        let mut used = vec![false; lines.len()];
+        let next_unused_line = |point: V2, used: &mut Vec<bool>| {
+            let candidates = lines_at_point.get(&quantize(point))?;
+            let i = *candidates.iter().find(|i| !used[**i])?;
+            used[i] = true;
+            let line = lines[i];
+            if quantize(line.from) == quantize(point) {
+                Some(line.to)
+            } else {
+                Some(line.from)
+            }
+        };
+
+        let mut paths = Vec::new();
+        for start in 0..lines.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+
+            let mut forward = vec![lines[start].from, lines[start].to];
+            while let Some(next) = next_unused_line(*forward.last().unwrap(), &mut used) {
+                forward.push(next);
+            }
+
+            if quantize(forward[0]) == quantize(*forward.last().unwrap()) {
+                *forward.last_mut().unwrap() = forward[0];
+                paths.push(Path::new_from(forward));
+                continue;
+            }
+
+            let mut backward = Vec::new();
+            let mut current = forward[0];
+            while let Some(next) = next_unused_line(current, &mut used) {
+                backward.push(next);
+                current = next;
+            }
+            backward.reverse();
+            backward.extend(forward);
+            paths.push(Path::new_from(backward));
+        }
+        paths
+    }
 }
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod test_marching_squares {
+    use crate::{func_2d::marching_squares::MarchingSquares, Line, Path, Rect, SampleSettings, V2};
+
+    #[test]
+    fn lines_through_sample_points() {
+        // the iso-line at 0.5 runs exactly through the center sample of the left cell
+        let marching_squares = MarchingSquares::new(
+            Rect::new(V2::zero(), V2::new(2.0, 1.0)),
+            SampleSettings::new(1.0),
+            |point| point.x,
+        );
+
+        // sample values equal to the target are neither above nor below it
+        let lines: Vec<(V2, V2)> = marching_squares
+            .get_lines(0.5)
+            .iter()
+            .map(|line: &Line| (line.from, line.to))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (V2::new(0.5, 0.0), V2::new(0.5, 0.5)),
+                (V2::new(0.5, 0.5), V2::new(0.5, 0.5)),
+                (V2::new(0.5, 1.0), V2::new(0.5, 0.5)),
+            ]
+        );
+
+        assert_eq!(
+            marching_squares.get_paths(0.5),
+            vec![Path::new_from(vec![
+                V2::new(0.5, 0.0),
+                V2::new(0.5, 0.5),
+                V2::new(0.5, 1.0)
+            ])]
+        );
+    }
+
+    #[test]
+    fn bounds_without_area() {
+        for bounds in [
+            Rect::new(V2::zero(), V2::new(0.0, 1.0)),
+            Rect::new(V2::zero(), V2::new(1.0, 0.0)),
+        ] {
+            let marching_squares =
+                MarchingSquares::new(bounds, SampleSettings::new(4.0), |point| point.x - point.y);
+            assert!(marching_squares.get_paths(0.0).is_empty());
+        }
+    }
+}
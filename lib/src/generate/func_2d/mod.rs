@@ -1,2 +1,3 @@
 pub mod marching_squares;
+mod marching_squares_test;
 mod sample_triangle;
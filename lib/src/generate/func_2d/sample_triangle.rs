@@ -59,6 +59,38 @@ impl SampleTriangle {
 
         Some(Line::new(points[0], points[1]))
     }
+
+    /// Like [`SampleTriangle::get_intersection_line`], but values equal to `target_value` count as above it, so lines
+    /// of neighbouring triangles meet at sample points lying exactly on the iso-line instead of leaving gaps or duplicates.
+    pub fn get_intersection_line_connected(&self, target_value: f32) -> Option<Line> {
+        if target_value < self.min_value || target_value > self.max_value {
+            return None;
+        }
+
+        let num_above = [self.a, self.b, self.c]
+            .iter()
+            .fold(0, |acc, point: &SamplePoint| {
+                acc + if point.value >= target_value { 1 } else { 0 }
+            });
+        if num_above == 0 || num_above == 3 {
+            return None;
+        }
+
+        let mut points = Vec::with_capacity(2);
+        for i in 0..3 {
+            let a = [self.a, self.b, self.c][i];
+            let b = [self.a, self.b, self.c][(i + 1) % 3];
+            if (a.value >= target_value) == (b.value >= target_value) {
+                continue;
+            }
+            let err_a = (target_value - a.value).abs();
+            let err_b = (target_value - b.value).abs();
+            let t = err_a / (err_a + err_b);
+            points.push(a.pos.lerp(b.pos, t));
+        }
+
+        Some(Line::new(points[0], points[1]))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
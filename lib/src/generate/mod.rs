@@ -1 +1,5 @@
 pub mod func_2d;
+pub mod sdf;
+mod sdf_test;
+
+pub use sdf::*;
@@ -0,0 +1,296 @@
+//! Contains [`Sdf`], a signed distance field for CSG-like modelling of 2D shapes with smooth blends.
+
+use std::sync::Arc;
+
+use crate::{
+    func_2d::marching_squares::MarchingSquares, Angle, Compound, Layer, Path, Plottable, Rect,
+    Rotate, SampleSettings, V2,
+};
+
+/// A signed distance field: a function returning the distance to the closest edge of a shape for every point.
+///
+/// Distances are negative inside the shape and positive outside. Fields can be combined with boolean operations
+/// ([`Sdf::union`], [`Sdf::smooth_union`], ...) and domain operations ([`Sdf::repeat`], [`Sdf::twist`], ...), then
+/// turned into plottable [`Path`]s with [`Sdf::contours`] or [`Sdf::fill_rings`].
+///
+/// Operations that distort space ([`Sdf::twist`], [`Sdf::smooth_union`], ...) may not return exact distances,
+/// but the zero contour is always correct.
+///
+/// ### Example
+/// ```
+/// # use plottery_lib::*;
+/// let blob = Sdf::circle(V2::new(-1.0, 0.0), 1.5)
+///     .smooth_union(&Sdf::circle(V2::new(1.0, 0.0), 1.5), 0.5)
+///     .difference(&Sdf::rect(&Rect::new_from_center(V2::zero(), V2::new(1.0, 0.5))));
+///
+/// let bounds = Rect::new_from_center(V2::zero(), V2::new(8.0, 6.0));
+/// let outline = blob.contours(&bounds, SampleSettings::new(20.0), 0.0);
+/// assert_eq!(outline.len(), 2); // outer edge and the hole
+/// ```
+#[derive(Clone)]
+pub struct Sdf {
+    function: Arc<dyn Fn(V2) -> f32 + Send + Sync>,
+}
+
+impl Sdf {
+    /// Creates a new `Sdf` from an arbitrary distance function.
+    pub fn new<F>(function: F) -> Self
+    where
+        F: Fn(V2) -> f32 + Send + Sync + 'static,
+    {
+        Self {
+            function: Arc::new(function),
+        }
+    }
+
+    /// Returns the signed distance of `point` to the edge of the shape.
+    pub fn distance(&self, point: V2) -> f32 {
+        (self.function)(point)
+    }
+
+    /// Returns whether `point` is inside or on the edge of the shape.
+    pub fn contains_point(&self, point: V2) -> bool {
+        self.distance(point) <= 0.0
+    }
+
+    // primitives
+
+    /// Circle around `center`, with exact distances.
+    pub fn circle(center: V2, radius: f32) -> Self {
+        Self::new(move |point| point.dist(center) - radius)
+    }
+
+    /// Axis aligned rectangle, with exact distances. Use [`Sdf::rotate_around`] to tilt it and [`Sdf::round`] to round its corners.
+    pub fn rect(rect: &Rect) -> Self {
+        let center = rect.center();
+        let half_size = rect.size() * 0.5;
+        Self::new(move |point| {
+            let offset = point - center;
+            let d = V2::new(offset.x.abs(), offset.y.abs()) - half_size;
+            d.max(V2::zero()).len() + d.max_axis().min(0.0)
+        })
+    }
+
+    /// Unsigned distance to the line segment from `from` to `to`. Use [`Sdf::round`] to give it a thickness.
+    pub fn segment(from: V2, to: V2) -> Self {
+        Self::new(move |point| {
+            let ab = to - from;
+            let len_squared = ab.len_squared();
+            if len_squared == 0.0 {
+                return point.dist(from);
+            }
+            let t = ((point - from).dot(ab) / len_squared).clamp(0.0, 1.0);
+            point.dist(from + ab * t)
+        })
+    }
+
+    /// Polygon from the points of a [`Path`]. The path is treated as closed.
+    pub fn polygon(path: &Path) -> Self {
+        let points = path.points_closed().into_owned();
+        Self::new(move |point| {
+            if points.is_empty() {
+                return f32::INFINITY;
+            }
+            let mut min_dist_squared = f32::INFINITY;
+            let mut inside = false;
+            for window in points.windows(2) {
+                let (a, b) = (window[0], window[1]);
+                let ab = b - a;
+                let len_squared = ab.len_squared();
+                let t = if len_squared == 0.0 {
+                    0.0
+                } else {
+                    ((point - a).dot(ab) / len_squared).clamp(0.0, 1.0)
+                };
+                min_dist_squared = min_dist_squared.min(point.dist_squared(a + ab * t));
+
+                if (a.y > point.y) != (b.y > point.y)
+                    && point.x < a.x + (point.y - a.y) / (b.y - a.y) * ab.x
+                {
+                    inside = !inside;
+                }
+            }
+            let dist = min_dist_squared.sqrt();
+            if inside {
+                -dist
+            } else {
+                dist
+            }
+        })
+    }
+
+    /// Area of a [`Compound`] including its holes, following its [`crate::FillRule`].
+    pub fn compound(compound: &Compound) -> Self {
+        let rings: Vec<Sdf> = compound.rings().map(Self::polygon).collect();
+        let compound = compound.clone();
+        Self::new(move |point| {
+            let dist = rings
+                .iter()
+                .map(|ring| ring.distance(point).abs())
+                .fold(f32::INFINITY, f32::min);
+            if compound.contains_point(point) {
+                -dist
+            } else {
+                dist
+            }
+        })
+    }
+
+    // boolean operations
+
+    /// Combines both shapes. Distances inside the result may be too large where the shapes overlap.
+    pub fn union(&self, other: &Sdf) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| a.distance(point).min(b.distance(point)))
+    }
+
+    /// Keeps only the area covered by both shapes.
+    pub fn intersection(&self, other: &Sdf) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| a.distance(point).max(b.distance(point)))
+    }
+
+    /// Removes `other` from `self`.
+    pub fn difference(&self, other: &Sdf) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| a.distance(point).max(-b.distance(point)))
+    }
+
+    /// Swaps inside and outside.
+    pub fn invert(&self) -> Self {
+        let a = self.clone();
+        Self::new(move |point| -a.distance(point))
+    }
+
+    /// Union with a smooth blend of size `smoothness` between the shapes.
+    pub fn smooth_union(&self, other: &Sdf, smoothness: f32) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| smooth_min(a.distance(point), b.distance(point), smoothness))
+    }
+
+    /// Intersection with a smooth blend of size `smoothness` between the shapes.
+    pub fn smooth_intersection(&self, other: &Sdf, smoothness: f32) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| -smooth_min(-a.distance(point), -b.distance(point), smoothness))
+    }
+
+    /// Difference with a smooth blend of size `smoothness` between the shapes.
+    pub fn smooth_difference(&self, other: &Sdf, smoothness: f32) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(move |point| -smooth_min(-a.distance(point), b.distance(point), smoothness))
+    }
+
+    // domain operations
+
+    /// Moves the shape by `offset`.
+    pub fn translate(&self, offset: V2) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point - offset))
+    }
+
+    /// Rotates the shape counterclockwise by `angle` around `pivot`.
+    pub fn rotate_around(&self, pivot: V2, angle: Angle) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point.rotate_around(pivot, angle.flip_sign())))
+    }
+
+    /// Scales the shape uniformly around the origin.
+    pub fn scale(&self, factor: f32) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point / factor) * factor)
+    }
+
+    /// Repeats the shape infinitely with the given `period`. The shape should fit into one cell centered around the origin.
+    pub fn repeat(&self, period: V2) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point - period * (point / period).round()))
+    }
+
+    /// Rotates space around `center` by `angle_per_unit` for every unit of distance from `center`.
+    pub fn twist(&self, center: V2, angle_per_unit: Angle) -> Self {
+        let a = self.clone();
+        Self::new(move |point| {
+            let angle = angle_per_unit * point.dist(center);
+            a.distance(point.rotate_around(center, angle.flip_sign()))
+        })
+    }
+
+    /// Grows the shape by `radius`, rounding off all corners.
+    pub fn round(&self, radius: f32) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point) - radius)
+    }
+
+    /// Turns the shape into an outline of the given `thickness` around its edge.
+    pub fn onion(&self, thickness: f32) -> Self {
+        let a = self.clone();
+        Self::new(move |point| a.distance(point).abs() - thickness)
+    }
+
+    // contours
+
+    /// Returns all contours at the given signed `distance` from the edge within `bounds`. A `distance` of `0.0` is the edge itself.
+    ///
+    /// This uses [`MarchingSquares`], so details smaller than `1.0 / sample_settings.points_per_unit` may be lost.
+    pub fn contours(
+        &self,
+        bounds: &Rect,
+        sample_settings: SampleSettings,
+        distance: f32,
+    ) -> Vec<Path> {
+        self.marching_squares(bounds, sample_settings)
+            .get_paths(distance)
+    }
+
+    /// Returns a [`Layer`] with the contours at all given `distances`. see [`Sdf::contours`].
+    pub fn contours_at(
+        &self,
+        bounds: &Rect,
+        sample_settings: SampleSettings,
+        distances: &[f32],
+    ) -> Layer {
+        let marching_squares = self.marching_squares(bounds, sample_settings);
+        distances
+            .iter()
+            .flat_map(|distance| marching_squares.get_paths(*distance))
+            .collect()
+    }
+
+    /// Fills the shape with concentric rings that are `spacing` apart, starting at the edge and moving inwards.
+    pub fn fill_rings(
+        &self,
+        bounds: &Rect,
+        sample_settings: SampleSettings,
+        spacing: f32,
+    ) -> Layer {
+        let marching_squares = self.marching_squares(bounds, sample_settings);
+        let mut rings = Layer::new();
+        if spacing <= 0.0 {
+            return rings;
+        }
+
+        let mut distance = 0.0;
+        loop {
+            let paths = marching_squares.get_paths(distance);
+            if paths.is_empty() {
+                break;
+            }
+            rings.push_many(paths);
+            distance -= spacing;
+        }
+        rings
+    }
+
+    fn marching_squares(&self, bounds: &Rect, sample_settings: SampleSettings) -> MarchingSquares {
+        MarchingSquares::new(*bounds, sample_settings, |point| self.distance(point))
+    }
+}
+
+/// Polynomial smooth minimum. see <https://iquilezles.org/articles/smin/>
+fn smooth_min(a: f32, b: f32, smoothness: f32) -> f32 {
+    if smoothness <= 0.0 {
+        return a.min(b);
+    }
+    let h = (smoothness - (a - b).abs()).max(0.0) / smoothness;
+    a.min(b) - h * h * smoothness * 0.25
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod test_sdf {
+    use crate::{
+        Angle, BoundingBox, Compound, Path, Plottable, Rect, SampleSettings, Sdf, LARGE_EPSILON, V2,
+    };
+
+    #[test]
+    fn primitives() {
+        let circle = Sdf::circle(V2::new(1.0, 1.0), 2.0);
+        assert!((circle.distance(V2::new(1.0, 1.0)) + 2.0).abs() < LARGE_EPSILON);
+        assert!((circle.distance(V2::new(4.0, 1.0)) - 1.0).abs() < LARGE_EPSILON);
+
+        let rect = Sdf::rect(&Rect::new(V2::zero(), V2::new(2.0, 1.0)));
+        assert!((rect.distance(V2::new(1.0, 0.5)) + 0.5).abs() < LARGE_EPSILON);
+        assert!((rect.distance(V2::new(3.0, 0.5)) - 1.0).abs() < LARGE_EPSILON);
+        assert!((rect.distance(V2::new(5.0, 5.0)) - 5.0).abs() < LARGE_EPSILON);
+
+        let segment = Sdf::segment(V2::zero(), V2::new(2.0, 0.0));
+        assert!((segment.distance(V2::new(1.0, 1.0)) - 1.0).abs() < LARGE_EPSILON);
+        assert!((segment.distance(V2::new(-1.0, 0.0)) - 1.0).abs() < LARGE_EPSILON);
+
+        let polygon = Sdf::polygon(&Path::new_from(vec![
+            V2::new(0.0, 0.0),
+            V2::new(2.0, 0.0),
+            V2::new(2.0, 1.0),
+            V2::new(0.0, 1.0),
+        ]));
+        for point in [V2::new(1.0, 0.5), V2::new(3.0, 0.5), V2::new(5.0, 5.0)] {
+            assert!((polygon.distance(point) - rect.distance(point)).abs() < LARGE_EPSILON);
+        }
+
+        let donut = Sdf::compound(&Compound::new(
+            Path::new_from(
+                Rect::new(V2::zero(), V2::xy(4.0)).get_points(SampleSettings::default()),
+            ),
+            vec![Path::new_from(
+                Rect::new(V2::xy(1.0), V2::xy(3.0)).get_points(SampleSettings::default()),
+            )],
+        ));
+        assert!((donut.distance(V2::new(0.5, 2.0)) + 0.5).abs() < LARGE_EPSILON);
+        assert!((donut.distance(V2::xy(2.0)) - 1.0).abs() < LARGE_EPSILON);
+        assert!((donut.distance(V2::new(5.0, 2.0)) - 1.0).abs() < LARGE_EPSILON);
+    }
+
+    #[test]
+    fn boolean_operations() {
+        let a = Sdf::circle(V2::new(-1.0, 0.0), 1.5);
+        let b = Sdf::circle(V2::new(1.0, 0.0), 1.5);
+
+        assert!(a.union(&b).contains_point(V2::new(-2.0, 0.0)));
+        assert!(a.union(&b).contains_point(V2::new(2.0, 0.0)));
+        assert!(!a.intersection(&b).contains_point(V2::new(-2.0, 0.0)));
+        assert!(a.intersection(&b).contains_point(V2::zero()));
+        assert!(a.difference(&b).contains_point(V2::new(-2.0, 0.0)));
+        assert!(!a.difference(&b).contains_point(V2::zero()));
+        assert!(!a.invert().contains_point(V2::new(-1.0, 0.0)));
+
+        // the smooth blend adds material between the shapes
+        let point = V2::new(0.0, 1.2);
+        assert!(!a.union(&b).contains_point(point));
+        assert!(a.smooth_union(&b, 1.0).contains_point(point));
+        assert!(a.smooth_union(&b, 1.0).distance(point) <= a.union(&b).distance(point));
+    }
+
+    #[test]
+    fn domain_operations() {
+        let circle = Sdf::circle(V2::zero(), 0.5);
+
+        let translated = circle.translate(V2::new(3.0, 0.0));
+        assert!(translated.contains_point(V2::new(3.0, 0.0)));
+        assert!(!translated.contains_point(V2::zero()));
+
+        let repeated = circle.repeat(V2::xy(2.0));
+        assert!(repeated.contains_point(V2::new(4.0, -6.0)));
+        assert!(!repeated.contains_point(V2::new(1.0, 1.0)));
+
+        let scaled = circle.scale(2.0);
+        assert!((scaled.distance(V2::zero()) + 1.0).abs() < LARGE_EPSILON);
+
+        let rect = Sdf::rect(&Rect::new_from_center(V2::zero(), V2::new(4.0, 0.5)));
+        let rotated = rect.rotate_around(V2::zero(), Angle::quarter_rotation());
+        assert!(rotated.contains_point(V2::new(0.0, 1.8)));
+        assert!(!rotated.contains_point(V2::new(1.8, 0.0)));
+
+        let twisted = rect.twist(V2::zero(), Angle::from_degrees(45.0));
+        assert!(twisted.contains_point(V2::polar(Angle::from_degrees(45.0), 1.0)));
+        assert!(!twisted.contains_point(V2::new(1.0, 0.0)));
+
+        assert!((circle.round(0.5).distance(V2::zero()) + 1.0).abs() < LARGE_EPSILON);
+        let onion = circle.onion(0.1);
+        assert!(onion.contains_point(V2::new(0.5, 0.0)));
+        assert!(!onion.contains_point(V2::zero()));
+    }
+
+    #[test]
+    fn contours() {
+        let circle = Sdf::circle(V2::zero(), 2.0);
+        let bounds = Rect::new_from_center(V2::zero(), V2::xy(6.0));
+        let sample_settings = SampleSettings::new(10.0);
+
+        let contours = circle.contours(&bounds, sample_settings, 0.0);
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].is_closed());
+        for point in contours[0].iter() {
+            assert!((point.len() - 2.0).abs() < 0.01);
+        }
+
+        let offset = circle.contours(&bounds, sample_settings, 0.5);
+        assert_eq!(offset.len(), 1);
+        let bounding_box = offset[0].bounding_box().unwrap();
+        assert!((bounding_box.width() - 5.0).abs() < 0.05);
+
+        // contours leaving the bounds are open
+        let cut = Sdf::circle(V2::new(3.0, 0.0), 1.0).contours(&bounds, sample_settings, 0.0);
+        assert_eq!(cut.len(), 1);
+        assert!(!cut[0].is_closed());
+
+        let layer = circle.contours_at(&bounds, sample_settings, &[-1.0, 0.0, 0.5]);
+        assert_eq!(layer.len(), 3);
+    }
+
+    #[test]
+    fn fill_rings() {
+        let circle = Sdf::circle(V2::zero(), 2.0);
+        let bounds = Rect::new_from_center(V2::zero(), V2::xy(6.0));
+        let rings = circle.fill_rings(&bounds, SampleSettings::new(10.0), 0.5);
+
+        assert_eq!(rings.len(), 4);
+        assert!(rings.iter().all(|ring| ring.is_closed()));
+        assert!(circle
+            .fill_rings(&bounds, SampleSettings::new(10.0), 0.0)
+            .is_empty());
+    }
+}
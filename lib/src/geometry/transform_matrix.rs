@@ -2,11 +2,11 @@ use crate::{Angle, V2};
 
 use super::TransformMatrixBuilder;
 
-/// A 2D transformation matrix represented in the following format:
+/// A 2D transformation matrix in homogeneous coordinates represented in the following format:
 /// ```text
 /// | tl tr u |
 /// | bl br v |
-/// | 0  0  1 |
+/// | p  q  w |
 /// ```
 ///
 /// This 3x3 matrix can represent various 2D transformations including:
@@ -14,9 +14,10 @@ use super::TransformMatrixBuilder;
 /// - Rotation
 /// - Scaling
 /// - Shearing
+/// - Perspective (projective) transformations, see [`TransformMatrix::perspective`]
 /// - Combinations of these transformations
 ///
-/// The bottom row is implicit as [0, 0, 1] and not stored.
+/// For affine transformations the bottom row is `[0, 0, 1]`.
 ///
 /// ### Example
 /// ```
@@ -41,6 +42,9 @@ pub struct TransformMatrix {
     pub br: f32,
     pub u: f32,
     pub v: f32,
+    pub p: f32,
+    pub q: f32,
+    pub w: f32,
 }
 
 impl TransformMatrix {
@@ -82,6 +86,9 @@ impl TransformMatrix {
             br: 1.0,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -104,6 +111,9 @@ impl TransformMatrix {
             br: scale.y,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -129,6 +139,9 @@ impl TransformMatrix {
             br: cos,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -151,6 +164,9 @@ impl TransformMatrix {
             br: 1.0,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -172,6 +188,9 @@ impl TransformMatrix {
             br: 1.0,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -193,6 +212,9 @@ impl TransformMatrix {
             br: -1.0,
             u: 0.0,
             v: 0.0,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -214,6 +236,9 @@ impl TransformMatrix {
             br: 1.0,
             u: offset.x,
             v: offset.y,
+            p: 0.0,
+            q: 0.0,
+            w: 1.0,
         }
     }
 
@@ -238,12 +263,15 @@ impl TransformMatrix {
     /// ```
     pub fn mul_matrix(&self, other: &TransformMatrix) -> Self {
         Self {
-            tl: self.tl * other.tl + self.tr * other.bl,
-            bl: self.bl * other.tl + self.br * other.bl,
-            tr: self.tl * other.tr + self.tr * other.br,
-            br: self.bl * other.tr + self.br * other.br,
-            u: self.tl * other.u + self.tr * other.v + self.u,
-            v: self.bl * other.u + self.br * other.v + self.v,
+            tl: self.tl * other.tl + self.tr * other.bl + self.u * other.p,
+            bl: self.bl * other.tl + self.br * other.bl + self.v * other.p,
+            tr: self.tl * other.tr + self.tr * other.br + self.u * other.q,
+            br: self.bl * other.tr + self.br * other.br + self.v * other.q,
+            u: self.tl * other.u + self.tr * other.v + self.u * other.w,
+            v: self.bl * other.u + self.br * other.v + self.v * other.w,
+            p: self.p * other.tl + self.q * other.bl + self.w * other.p,
+            q: self.p * other.tr + self.q * other.br + self.w * other.q,
+            w: self.p * other.u + self.q * other.v + self.w * other.w,
         }
     }
 
@@ -259,10 +287,13 @@ impl TransformMatrix {
     /// assert!((transformed.y - 1.0).abs() < 0.001);
     /// ```
     pub fn mul_vector(&self, v: V2) -> V2 {
-        V2 {
-            x: self.tl * v.x + self.tr * v.y + self.u,
-            y: self.bl * v.x + self.br * v.y + self.v,
+        let x = self.tl * v.x + self.tr * v.y + self.u;
+        let y = self.bl * v.x + self.br * v.y + self.v;
+        if self.is_affine() {
+            return V2 { x, y };
         }
+        let w = self.p * v.x + self.q * v.y + self.w;
+        V2 { x: x / w, y: y / w }
     }
 
     /// Combines multiple transformations into a single transformation matrix.
@@ -294,4 +325,254 @@ impl TransformMatrix {
             .rev()
             .fold(TransformMatrix::identity(), |acc, t| acc.mul_matrix(t))
     }
+
+    /// Returns `true` if the bottom row is `[0, 0, 1]`, meaning the matrix has no perspective component.
+    pub fn is_affine(&self) -> bool {
+        self.p == 0.0 && self.q == 0.0 && self.w == 1.0
+    }
+
+    /// Returns the determinant of the matrix.
+    pub fn determinant(&self) -> f32 {
+        self.tl * (self.br * self.w - self.v * self.q)
+            - self.tr * (self.bl * self.w - self.v * self.p)
+            + self.u * (self.bl * self.q - self.br * self.p)
+    }
+
+    /// Returns the inverse transformation, or `None` if the matrix is singular.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let transform = TransformMatrix::builder()
+    ///     .scale(2.0)
+    ///     .rotate(Angle::from_degrees(30.0))
+    ///     .translate(V2::new(5.0, 10.0))
+    ///     .build();
+    /// let inverse = transform.inverse().unwrap();
+    ///
+    /// let point = V2::new(1.0, 2.0);
+    /// assert_eq!(inverse.mul_vector(transform.mul_vector(point)), point);
+    /// ```
+    pub fn inverse(&self) -> Option<Self> {
+        let [tl, tr, u, bl, br, v, p, q, w] = self.to_f64_array();
+
+        let det = tl * (br * w - v * q) - tr * (bl * w - v * p) + u * (bl * q - br * p);
+        if det.abs() <= f64::EPSILON {
+            return None;
+        }
+
+        let inverse = [
+            (br * w - v * q) / det,
+            (u * q - tr * w) / det,
+            (tr * v - u * br) / det,
+            (v * p - bl * w) / det,
+            (tl * w - u * p) / det,
+            (u * bl - tl * v) / det,
+            (bl * q - br * p) / det,
+            (tr * p - tl * q) / det,
+            (tl * br - tr * bl) / det,
+        ];
+        Some(Self::from_f64_array(inverse).normalized())
+    }
+
+    /// new TransformMatrix that maps the four points `from` onto the four points `to`.
+    ///
+    /// This is a perspective (projective) transformation. It can be used to draw on a tilted plane
+    /// or to map calibration points. Straight lines stay straight, but parallel lines are not preserved.
+    ///
+    /// Returns `None` if three of the points in `from` or `to` are collinear.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let square = [V2::new(0.0, 0.0), V2::new(1.0, 0.0), V2::new(1.0, 1.0), V2::new(0.0, 1.0)];
+    /// let trapezoid = [V2::new(0.0, 0.0), V2::new(4.0, 0.0), V2::new(3.0, 2.0), V2::new(1.0, 2.0)];
+    ///
+    /// let perspective = TransformMatrix::perspective(square, trapezoid).unwrap();
+    /// let mapped = perspective.mul_vector(V2::new(1.0, 1.0));
+    /// assert!(mapped.dist(V2::new(3.0, 2.0)) < 0.001);
+    /// ```
+    pub fn perspective(from: [V2; 4], to: [V2; 4]) -> Option<Self> {
+        // solve for the 8 unknowns with w fixed at 1:
+        // x' = (tl x + tr y + u) / (p x + q y + 1)
+        // y' = (bl x + br y + v) / (p x + q y + 1)
+        let mut system = [[0.0_f64; 9]; 8];
+        for i in 0..4 {
+            let (x, y) = (from[i].x as f64, from[i].y as f64);
+            let (x_to, y_to) = (to[i].x as f64, to[i].y as f64);
+            system[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * x_to, -y * x_to, x_to];
+            system[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * y_to, -y * y_to, y_to];
+        }
+
+        let [tl, tr, u, bl, br, v, p, q] = solve_linear_system_8(system)?;
+        let matrix = Self::from_f64_array([tl, tr, u, bl, br, v, p, q, 1.0]);
+        if matrix.determinant().abs() <= f32::EPSILON {
+            return None;
+        }
+        Some(matrix)
+    }
+
+    /// Decomposes an affine matrix into translation, rotation, scale and shear. see [`TransformDecomposition`].
+    ///
+    /// Returns `None` if the matrix has a perspective component or is singular.
+    ///
+    /// ### Example
+    /// ```
+    /// # use plottery_lib::*;
+    /// let transform = TransformMatrix::builder()
+    ///     .scale_2d(V2::new(2.0, 3.0))
+    ///     .rotate(Angle::from_degrees(45.0))
+    ///     .translate(V2::new(10.0, 5.0))
+    ///     .build();
+    ///
+    /// let decomposed = transform.decompose().unwrap();
+    /// assert!((decomposed.rotation.to_degree() - 45.0).abs() < 0.001);
+    /// assert!(decomposed.scale.dist(V2::new(2.0, 3.0)) < 0.001);
+    /// assert!(decomposed.translation.dist(V2::new(10.0, 5.0)) < 0.001);
+    /// ```
+    pub fn decompose(&self) -> Option<TransformDecomposition> {
+        if !self.is_affine() {
+            return None;
+        }
+
+        let scale_x = (self.tl * self.tl + self.bl * self.bl).sqrt();
+        if scale_x <= f32::EPSILON {
+            return None;
+        }
+        let rotation = Angle::from_rad(self.bl.atan2(self.tl));
+        let (sin, cos) = rotation.rad_sin_cos();
+
+        let scale_y = (self.tl * self.br - self.tr * self.bl) / scale_x;
+        if scale_y.abs() <= f32::EPSILON {
+            return None;
+        }
+        let shear = (cos * self.tr + sin * self.br) / scale_y;
+
+        Some(TransformDecomposition {
+            translation: V2::new(self.u, self.v),
+            rotation,
+            scale: V2::new(scale_x, scale_y),
+            shear,
+        })
+    }
+
+    /// Returns the matrix scaled so that `w` is `1.0`. Homogeneous matrices that are multiples of each other represent the same transformation.
+    fn normalized(&self) -> Self {
+        if self.w.abs() <= f32::EPSILON || self.w == 1.0 {
+            return *self;
+        }
+        let factor = 1.0 / self.w;
+        Self {
+            tl: self.tl * factor,
+            bl: self.bl * factor,
+            tr: self.tr * factor,
+            br: self.br * factor,
+            u: self.u * factor,
+            v: self.v * factor,
+            p: self.p * factor,
+            q: self.q * factor,
+            w: 1.0,
+        }
+    }
+
+    fn to_f64_array(self) -> [f64; 9] {
+        [
+            self.tl as f64,
+            self.tr as f64,
+            self.u as f64,
+            self.bl as f64,
+            self.br as f64,
+            self.v as f64,
+            self.p as f64,
+            self.q as f64,
+            self.w as f64,
+        ]
+    }
+
+    fn from_f64_array(values: [f64; 9]) -> Self {
+        let [tl, tr, u, bl, br, v, p, q, w] = values.map(|value| value as f32);
+        Self {
+            tl,
+            bl,
+            tr,
+            br,
+            u,
+            v,
+            p,
+            q,
+            w,
+        }
+    }
+}
+
+/// The components of an affine [`TransformMatrix`], see [`TransformMatrix::decompose`].
+///
+/// Recomposing applies `scale`, then a horizontal `shear`, then `rotation` and finally `translation`:
+/// ```
+/// # use plottery_lib::*;
+/// let transform = TransformMatrix::builder()
+///     .shear(V2::new(0.5, 0.0))
+///     .rotate(Angle::from_degrees(20.0))
+///     .build();
+/// let recomposed = transform.decompose().unwrap().to_matrix();
+///
+/// let point = V2::new(3.0, 4.0);
+/// assert!(transform.mul_vector(point).dist(recomposed.mul_vector(point)) < 0.001);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformDecomposition {
+    pub translation: V2,
+    pub rotation: Angle,
+    /// Scale along x and y. A negative `scale.y` means the transformation mirrors.
+    pub scale: V2,
+    /// Horizontal shear factor, see [`TransformMatrix::shear`].
+    pub shear: f32,
+}
+
+impl TransformDecomposition {
+    /// Recomposes the components into a [`TransformMatrix`].
+    pub fn to_matrix(&self) -> TransformMatrix {
+        TransformMatrix::builder()
+            .scale_2d(self.scale)
+            .shear(V2::new(self.shear, 0.0))
+            .rotate(self.rotation)
+            .translate(self.translation)
+            .build()
+    }
+}
+
+/// Solves an 8x8 linear system given as augmented matrix rows using gaussian elimination with partial pivoting.
+fn solve_linear_system_8(mut system: [[f64; 9]; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot_row = (col..8).max_by(|a, b| {
+            system[*a][col]
+                .abs()
+                .partial_cmp(&system[*b][col].abs())
+                .unwrap()
+        })?;
+        if system[pivot_row][col].abs() < 1e-12 {
+            return None;
+        }
+        system.swap(col, pivot_row);
+
+        for row in 0..8 {
+            if row == col {
+                continue;
+            }
+            let factor = system[row][col] / system[col][col];
+            if factor == 0.0 {
+                continue;
+            }
+            let pivot = system[col];
+            for (value, pivot_value) in system[row].iter_mut().zip(pivot.iter()).skip(col) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut solution = [0.0; 8];
+    for (i, value) in solution.iter_mut().enumerate() {
+        *value = system[i][8] / system[i][i];
+    }
+    Some(solution)
 }
@@ -72,4 +72,10 @@ impl TransformMatrixBuilder {
         self.transforms.push(TransformMatrix::translate(offset));
         self
     }
+
+    /// Adds an arbitrary transformation matrix, for example a perspective transformation. see [`TransformMatrix::perspective`].
+    pub fn matrix(mut self, matrix: TransformMatrix) -> Self {
+        self.transforms.push(matrix);
+        self
+    }
 }
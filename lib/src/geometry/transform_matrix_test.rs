@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test_matrix {
-    use crate::{geometry::TransformMatrix, Angle, V2};
+    use crate::{
+        geometry::TransformMatrix, Angle, BoundingBox, Circle, Layer, Path, Rect, Transform,
+        LARGE_EPSILON, V2,
+    };
 
     #[test]
     fn multiply_matrix_0() {
@@ -11,6 +14,7 @@ mod test_matrix {
             br: 3.0,
             u: 1.0,
             v: 2.0,
+            ..TransformMatrix::identity()
         };
 
         let b = TransformMatrix {
@@ -20,6 +24,7 @@ mod test_matrix {
             br: 2.0,
             u: 2.0,
             v: 1.0,
+            ..TransformMatrix::identity()
         };
 
         let c = TransformMatrix {
@@ -29,6 +34,7 @@ mod test_matrix {
             br: 14.0,
             u: 5.0,
             v: 9.0,
+            ..TransformMatrix::identity()
         };
         assert_eq!(c, a.mul_matrix(&b));
     }
@@ -42,6 +48,7 @@ mod test_matrix {
             br: 5.0,
             u: 3.0,
             v: 6.0,
+            ..TransformMatrix::identity()
         };
 
         let b = TransformMatrix {
@@ -51,6 +58,7 @@ mod test_matrix {
             br: 9.0,
             u: 7.0,
             v: 10.0,
+            ..TransformMatrix::identity()
         };
 
         let c = TransformMatrix {
@@ -60,6 +68,7 @@ mod test_matrix {
             br: 69.0,
             u: 30.0,
             v: 84.0,
+            ..TransformMatrix::identity()
         };
         assert_eq!(c, a.mul_matrix(&b));
         assert_ne!(c, b.mul_matrix(&c)); // the order matters here
@@ -74,6 +83,7 @@ mod test_matrix {
             br: 5.0,
             u: 3.0,
             v: 6.0,
+            ..TransformMatrix::identity()
         };
 
         let v = V2 { x: 2.0, y: 1.0 };
@@ -192,4 +202,136 @@ mod test_matrix {
 
         assert_eq!(matrix, TransformMatrix::identity());
     }
+
+    #[test]
+    fn inverse() {
+        let matrix = TransformMatrix::builder()
+            .scale_2d(V2::new(2.0, 0.5))
+            .shear(V2::new(0.3, 0.1))
+            .rotate(Angle::from_degrees(33.0))
+            .translate(V2::new(-4.0, 7.0))
+            .build();
+        let inverse = matrix.inverse().unwrap();
+
+        assert!(inverse.is_affine());
+        for point in [V2::new(1.0, 2.0), V2::new(-3.0, 0.5), V2::zero()] {
+            let round_trip = inverse.mul_vector(matrix.mul_vector(point));
+            assert!(round_trip.dist(point) < LARGE_EPSILON);
+        }
+
+        let singular = TransformMatrix::scale_2d(V2::new(1.0, 0.0));
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn perspective() {
+        let from = [
+            V2::new(0.0, 0.0),
+            V2::new(10.0, 0.0),
+            V2::new(10.0, 10.0),
+            V2::new(0.0, 10.0),
+        ];
+        let to = [
+            V2::new(1.0, 1.0),
+            V2::new(9.0, 2.0),
+            V2::new(7.0, 6.0),
+            V2::new(3.0, 7.0),
+        ];
+        let matrix = TransformMatrix::perspective(from, to).unwrap();
+        assert!(!matrix.is_affine());
+
+        for (a, b) in from.iter().zip(to.iter()) {
+            assert!(matrix.mul_vector(*a).dist(*b) < LARGE_EPSILON);
+        }
+
+        // straight lines stay straight
+        let mid = matrix.mul_vector(V2::new(5.0, 5.0));
+        let diagonal_start = matrix.mul_vector(from[0]);
+        let diagonal_end = matrix.mul_vector(from[2]);
+        let cross = (diagonal_end - diagonal_start).x * (mid - diagonal_start).y
+            - (diagonal_end - diagonal_start).y * (mid - diagonal_start).x;
+        assert!(cross.abs() < 0.01);
+
+        // the inverse maps back
+        let inverse = matrix.inverse().unwrap();
+        for (a, b) in from.iter().zip(to.iter()) {
+            assert!(inverse.mul_vector(*b).dist(*a) < 0.01);
+        }
+
+        // composition with the inverse results in identity
+        let combined = inverse.mul_matrix(&matrix);
+        let point = V2::new(2.0, 3.0);
+        assert!(combined.mul_vector(point).dist(point) < 0.01);
+    }
+
+    #[test]
+    fn perspective_degenerate() {
+        let from = [
+            V2::new(0.0, 0.0),
+            V2::new(1.0, 0.0),
+            V2::new(2.0, 0.0),
+            V2::new(0.0, 1.0),
+        ];
+        let to = [
+            V2::new(0.0, 0.0),
+            V2::new(1.0, 0.0),
+            V2::new(1.0, 1.0),
+            V2::new(0.0, 1.0),
+        ];
+        assert!(TransformMatrix::perspective(from, to).is_none());
+    }
+
+    #[test]
+    fn decompose() {
+        let matrix = TransformMatrix::builder()
+            .scale_2d(V2::new(2.0, -3.0))
+            .shear(V2::new(0.4, 0.0))
+            .rotate(Angle::from_degrees(-70.0))
+            .translate(V2::new(1.0, 2.0))
+            .build();
+
+        let decomposed = matrix.decompose().unwrap();
+        assert!((decomposed.scale.x - 2.0).abs() < LARGE_EPSILON);
+        assert!((decomposed.scale.y + 3.0).abs() < LARGE_EPSILON);
+        assert!((decomposed.shear - 0.4).abs() < LARGE_EPSILON);
+        assert!((decomposed.rotation.to_degree() + 70.0).abs() < LARGE_EPSILON);
+        assert!(decomposed.translation.dist(V2::new(1.0, 2.0)) < LARGE_EPSILON);
+
+        let recomposed = decomposed.to_matrix();
+        for point in [V2::new(1.0, 2.0), V2::new(-3.0, 0.5)] {
+            assert!(recomposed.mul_vector(point).dist(matrix.mul_vector(point)) < LARGE_EPSILON);
+        }
+
+        let perspective = TransformMatrix {
+            p: 0.1,
+            ..TransformMatrix::identity()
+        };
+        assert!(perspective.decompose().is_none());
+    }
+
+    #[test]
+    fn transform_layer() {
+        let mut sublayer = Layer::new();
+        sublayer.push(Circle::new_shape(V2::xy(5.0), 1.0));
+
+        let mut layer = Layer::new();
+        layer.push(Rect::new_shape(V2::zero(), V2::xy(10.0)));
+        layer.push(Path::new_shape_from(vec![V2::zero(), V2::new(10.0, 0.0)]));
+        layer.push_layer(sublayer);
+
+        let matrix = TransformMatrix::builder()
+            .scale(2.0)
+            .translate(V2::new(1.0, 0.0))
+            .build();
+        let transformed = layer.transform(&matrix);
+
+        assert_eq!(transformed.len_recursive(), layer.len_recursive());
+        let bounds = transformed.bounding_box().unwrap();
+        assert!(bounds.bl().dist(V2::new(1.0, 0.0)) < LARGE_EPSILON);
+        assert!(bounds.tr().dist(V2::new(21.0, 20.0)) < LARGE_EPSILON);
+
+        let mut transformed_mut = layer.clone();
+        transformed_mut.transform_mut(&matrix);
+        assert_eq!(transformed_mut.bounding_box(), transformed.bounding_box());
+    }
 }
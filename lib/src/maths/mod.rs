@@ -7,6 +7,8 @@ mod float_test;
 pub mod noise;
 mod noise_test;
 pub mod random;
+pub mod rng;
+mod rng_test;
 mod thread_local;
 
 pub use angle::*;
@@ -14,4 +16,5 @@ pub use consts::*;
 pub use float::*;
 pub use noise::*;
 pub use random::*;
+pub use rng::*;
 pub use thread_local::{seed, seed_random};
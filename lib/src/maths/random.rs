@@ -1,5 +1,5 @@
 //! Contains functions for generating random numbers.
-//! see also [`crate::maths::seed`]
+//! see also [`crate::maths::seed`] and [`crate::Rng`] for deterministic generation that is independent of thread scheduling.
 
 use rand::Rng;
 use rand_distr::{Distribution, Normal, SkewNormal};
@@ -0,0 +1,263 @@
+//! Contains [`Rng`], an explicit and deterministic random number generator.
+//! see also [`crate::maths::random`] for the global, seedable functions.
+
+use std::hash::{Hash, Hasher};
+
+use rand::{rngs::StdRng, Rng as _, RngCore, SeedableRng};
+use rand_distr::{Distribution, Normal, SkewNormal};
+
+use super::thread_local::RNG;
+use crate::{Angle, Rect, V2};
+
+/// An explicit random number generator handle.
+///
+/// Unlike the functions in [`crate::maths::random`], which share one global generator,
+/// an `Rng` is owned by the caller. It can be forked into independent child generators by
+/// index or by key. A fork only depends on the seed of its parent and the index/key, so
+/// the same seed produces the same results regardless of iteration order or thread count.
+///
+/// ### Example
+/// ```
+/// # use plottery_lib::*;
+/// # use rayon::prelude::*;
+/// let rng = Rng::new(42);
+///
+/// // every item gets its own generator, independent of how rayon schedules the work
+/// let points: Vec<V2> = (0..100)
+///     .into_par_iter()
+///     .map(|i| rng.fork(i).point_in_rect(&Rect::new(V2::zero(), V2::xy(10.0))))
+///     .collect();
+///
+/// let points_again: Vec<V2> = (0..100)
+///     .map(|i| rng.fork(i).point_in_rect(&Rect::new(V2::zero(), V2::xy(10.0))))
+///     .collect();
+/// assert_eq!(points, points_again);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    seed: u64,
+    inner: StdRng,
+}
+
+impl Rng {
+    /// Creates a new `Rng` from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            inner: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates a new `Rng` with a seed drawn from the global generator. see [`crate::maths::seed`].
+    ///
+    /// After calling [`crate::maths::seed`] this is deterministic as well.
+    pub fn from_global() -> Self {
+        Self::new(RNG.lock().expect("Failed to acquire RNG lock").gen::<u64>())
+    }
+
+    /// Creates a new `Rng` with a random seed from the operating system.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::thread_rng().gen::<u64>())
+    }
+
+    /// Returns the seed this `Rng` was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Creates an independent child `Rng` for the given `index`.
+    ///
+    /// The child only depends on the seed of `self` and `index`, not on how many numbers have been drawn from `self`.
+    pub fn fork(&self, index: u64) -> Self {
+        Self::new(split_mix_64(self.seed ^ split_mix_64(index)))
+    }
+
+    /// Creates an independent child `Rng` for the given `key`. see [`Rng::fork`].
+    ///
+    /// ```
+    /// # use plottery_lib::*;
+    /// let rng = Rng::new(1);
+    /// assert_eq!(
+    ///     rng.fork_key("background").rand_range(0.0, 1.0),
+    ///     rng.fork_key("background").rand_range(0.0, 1.0)
+    /// );
+    /// ```
+    pub fn fork_key<K: Hash + ?Sized>(&self, key: &K) -> Self {
+        let mut hasher = StableHasher::new();
+        key.hash(&mut hasher);
+        self.fork(hasher.finish())
+    }
+
+    /// uniform random float between `from` and `to`
+    pub fn rand_range(&mut self, from: f32, to: f32) -> f32 {
+        self.inner.gen_range(from..to)
+    }
+
+    /// uniform random integer between `from` and `to`
+    pub fn rand_range_i(&mut self, from: i32, to: i32) -> i32 {
+        self.inner.gen_range(from..to)
+    }
+
+    /// random boolean with given `chance` of being true
+    pub fn coin(&mut self, chance: f32) -> bool {
+        self.inner.gen::<f32>() < chance
+    }
+
+    /// see [`rand_distr::Normal`]
+    pub fn rand_normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+        let normal = Normal::new(mean, std_dev).unwrap_or_else(|_| {
+            panic!(
+                "Invalid parameters for normal distribution: mean: {}, std_dev: {}",
+                mean, std_dev
+            )
+        });
+        normal.sample(&mut self.inner)
+    }
+
+    /// see [`rand_distr::SkewNormal`]
+    pub fn rand_normal_skewed(&mut self, location: f32, scale: f32, shape: f32) -> f32 {
+        let normal_skewed = SkewNormal::new(location, scale, shape).unwrap_or_else(|_| {
+            panic!(
+                "Invalid parameters for skewed normal distribution: location: {}, scale: {}, shape: {}",
+                location, scale, shape
+            )
+        });
+        normal_skewed.sample(&mut self.inner)
+    }
+
+    /// see [`rand_distr::Exp`]
+    pub fn rand_exponential(&mut self, lambda: f32) -> f32 {
+        let exponential = rand_distr::Exp::new(lambda).unwrap_or_else(|_| {
+            panic!(
+                "Invalid parameter for exponential distribution: lambda: {}",
+                lambda
+            )
+        });
+        exponential.sample(&mut self.inner)
+    }
+
+    /// Creates a random angle between 0 and 2π (0° and 360°). see [`Angle::rand`].
+    pub fn angle(&mut self) -> Angle {
+        Angle::from_rotations(self.inner.gen::<f32>())
+    }
+
+    /// Returns a random vector exactly on the unit circle. see [`V2::random_unit_circle`].
+    pub fn point_on_unit_circle(&mut self) -> V2 {
+        V2::polar(self.angle(), 1.0)
+    }
+
+    /// Returns a random vector inside or on the unit circle. see [`V2::random_unit_disk`].
+    pub fn point_in_unit_disk(&mut self) -> V2 {
+        let angle = self.angle();
+        let radius = self.rand_range(0.0, 1.0);
+        V2::polar(angle, radius)
+    }
+
+    /// Returns a random vector inside or on the given rectangle. see [`V2::random_in_rect`].
+    pub fn point_in_rect(&mut self, rect: &Rect) -> V2 {
+        V2::new(
+            self.rand_range(rect.bl().x, rect.tr().x),
+            self.rand_range(rect.bl().y, rect.tr().y),
+        )
+    }
+
+    /// Returns a random index into `weights`, where each index is chosen with a probability proportional to its weight.
+    ///
+    /// Returns `None` if `weights` is empty or all weights are zero. Negative weights are treated as zero.
+    pub fn choose_weighted_index(&mut self, weights: &[f32]) -> Option<usize> {
+        let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.inner.gen::<f32>() * total;
+        let mut last_valid = None;
+        for (i, weight) in weights.iter().enumerate() {
+            let weight = weight.max(0.0);
+            if weight <= 0.0 {
+                continue;
+            }
+            if target < weight {
+                return Some(i);
+            }
+            target -= weight;
+            last_valid = Some(i);
+        }
+        // only reachable due to floating point inaccuracies
+        last_valid
+    }
+
+    /// Returns a random element of `items`, where each element is chosen with a probability proportional to the corresponding entry in `weights`.
+    ///
+    /// Returns `None` if `items` and `weights` have different lengths, or see [`Rng::choose_weighted_index`].
+    pub fn choose_weighted<'a, T>(&mut self, items: &'a [T], weights: &[f32]) -> Option<&'a T> {
+        if items.len() != weights.len() {
+            return None;
+        }
+        self.choose_weighted_index(weights).map(|i| &items[i])
+    }
+
+    /// Returns a uniformly chosen random element of `items`, or `None` if `items` is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        Some(&items[self.inner.gen_range(0..items.len())])
+    }
+
+    /// Shuffles `items` in place using the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.inner.gen_range(0..=i);
+            items.swap(i, j);
+        }
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+/// see <https://prng.di.unimi.it/splitmix64.c>
+fn split_mix_64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a hasher. Unlike [`std::collections::hash_map::DefaultHasher`] its output is guaranteed to stay the same between Rust versions.
+struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    fn new() -> Self {
+        Self {
+            state: 0xcbf29ce484222325,
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(0x100000001b3);
+        }
+    }
+}
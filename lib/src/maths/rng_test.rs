@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test_rng {
+    use rayon::prelude::*;
+
+    use crate::{Plottable, Rect, Rng, V2};
+
+    #[test]
+    fn same_seed_same_values() {
+        let mut a = Rng::new(12);
+        let mut b = Rng::new(12);
+        for _ in 0..100 {
+            assert_eq!(a.rand_range(-5.0, 5.0), b.rand_range(-5.0, 5.0));
+            assert_eq!(a.rand_normal(0.0, 1.0), b.rand_normal(0.0, 1.0));
+            assert_eq!(a.coin(0.5), b.coin(0.5));
+        }
+
+        let mut c = Rng::new(13);
+        let values_a: Vec<_> = (0..10).map(|_| a.rand_range(0.0, 1.0)).collect();
+        let values_c: Vec<_> = (0..10).map(|_| c.rand_range(0.0, 1.0)).collect();
+        assert_ne!(values_a, values_c);
+    }
+
+    #[test]
+    fn fork_independent_of_parent_state() {
+        let rng = Rng::new(7);
+        let mut used = rng.clone();
+        for _ in 0..10 {
+            used.rand_range(0.0, 1.0);
+        }
+
+        assert_eq!(
+            rng.fork(3).rand_range(0.0, 1.0),
+            used.fork(3).rand_range(0.0, 1.0)
+        );
+        assert_ne!(
+            rng.fork(3).rand_range(0.0, 1.0),
+            rng.fork(4).rand_range(0.0, 1.0)
+        );
+        assert_eq!(
+            rng.fork_key("hatch").rand_range(0.0, 1.0),
+            rng.fork_key("hatch").rand_range(0.0, 1.0)
+        );
+        assert_ne!(
+            rng.fork_key("hatch").rand_range(0.0, 1.0),
+            rng.fork_key("outline").rand_range(0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let rng = Rng::new(99);
+        let rect = Rect::new(V2::zero(), V2::new(10.0, 5.0));
+
+        let sequential: Vec<V2> = (0..1000)
+            .map(|i| rng.fork(i).point_in_rect(&rect))
+            .collect();
+        let parallel: Vec<V2> = (0..1000)
+            .into_par_iter()
+            .map(|i| rng.fork(i).point_in_rect(&rect))
+            .collect();
+
+        assert_eq!(sequential, parallel);
+        assert!(sequential.iter().all(|p| rect.contains_point(*p)));
+    }
+
+    #[test]
+    fn choose_weighted() {
+        let mut rng = Rng::new(1);
+        let items = ["a", "b", "c"];
+
+        for _ in 0..100 {
+            assert_eq!(rng.choose_weighted(&items, &[0.0, 1.0, 0.0]), Some(&"b"));
+            assert_ne!(rng.choose_weighted(&items, &[1.0, 0.0, 1.0]), Some(&"b"));
+        }
+        assert_eq!(rng.choose_weighted(&items, &[0.0, 0.0, 0.0]), None);
+        assert_eq!(rng.choose_weighted(&items, &[1.0, 1.0]), None);
+        assert_eq!(rng.choose_weighted_index(&[]), None);
+
+        let counts = (0..10000).fold([0; 2], |mut counts, _| {
+            counts[rng.choose_weighted_index(&[1.0, 3.0]).unwrap()] += 1;
+            counts
+        });
+        let ratio = counts[1] as f32 / counts[0] as f32;
+        assert!((2.5..3.5).contains(&ratio));
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut items: Vec<usize> = (0..50).collect();
+        Rng::new(3).shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..50).collect::<Vec<_>>());
+
+        let mut items_again: Vec<usize> = (0..50).collect();
+        Rng::new(3).shuffle(&mut items_again);
+        assert_eq!(items, items_again);
+    }
+}
@@ -313,6 +313,7 @@ impl Circle {
             Shape::Circle(c) => self.contains_circle(c),
             Shape::Rect(r) => self.contains_rect(r),
             Shape::Path(p) => self.contains_path(p),
+            Shape::Compound(c) => c.containment_in(&self.into()),
         }
     }
 }
@@ -59,6 +59,7 @@ mod test_circle {
         match c_scaled {
             crate::Shape::Circle(_) => panic!("Expected Path, got circle {:?}", c_scaled),
             crate::Shape::Rect(_) => panic!("Expected Path, got rect {:?}", c_scaled),
+            crate::Shape::Compound(_) => panic!("Expected Path, got compound {:?}", c_scaled),
             crate::Shape::Path(_) => {}
         }
         assert!(c.length() < c_scaled.length());
@@ -0,0 +1,478 @@
+use geo_types::{LineString, Polygon};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::TransformMatrix,
+    traits::{ClosestPoint, Normalize, Scale, Scale2D, Transform, Translate},
+    Angle, BoundingBox, Circle, Containment, Layer, Line, Masked, Mirror, Path, Plottable, Rect,
+    Rotate, Rotate90, SampleSettings, Shape, V2,
+};
+
+/// Decides which areas of a [`Compound`] are inside, see <https://www.w3.org/TR/SVG2/painting.html#FillRuleProperty>.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is inside if a ray from it crosses an odd number of rings.
+    #[default]
+    EvenOdd,
+    /// A point is inside if the rings wind around it a non-zero number of times.
+    /// Holes only cut out area if they are oriented opposite to the outer ring.
+    NonZero,
+}
+
+/// A region made of a closed outer ring and any number of closed holes, for example a glyph or a masked area.
+///
+/// All rings are closed on creation.
+///
+/// ### Example
+/// ```
+/// # use plottery_lib::*;
+/// let outer = Path::new_from(vec![V2::zero(), V2::new(4.0, 0.0), V2::xy(4.0), V2::new(0.0, 4.0)]);
+/// let hole = Path::new_from(vec![V2::xy(1.0), V2::new(3.0, 1.0), V2::xy(3.0), V2::new(1.0, 3.0)]);
+/// let donut = Compound::new(outer, vec![hole]);
+/// assert!(donut.contains_point(V2::new(0.5, 2.0)));
+/// assert!(!donut.contains_point(V2::xy(2.0)));
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Compound {
+    outer: Path,
+    holes: Vec<Path>,
+    fill_rule: FillRule,
+}
+
+impl Compound {
+    pub fn new(outer: Path, holes: Vec<Path>) -> Self {
+        Self {
+            outer: Self::closed(outer),
+            holes: holes.into_iter().map(Self::closed).collect(),
+            fill_rule: FillRule::default(),
+        }
+    }
+    pub fn new_shape(outer: Path, holes: Vec<Path>) -> Shape {
+        Shape::Compound(Self::new(outer, holes))
+    }
+    pub fn new_from_geo_polygon(geo_polygon: &Polygon<f32>) -> Self {
+        let to_path = |line_string: &LineString<f32>| {
+            Path::new_from_iter(line_string.into_iter().map(V2::new_from_geo))
+        };
+        Self::new(
+            to_path(geo_polygon.exterior()),
+            geo_polygon
+                .interiors()
+                .iter()
+                .filter(|interior| interior.0.len() > 1)
+                .map(to_path)
+                .collect(),
+        )
+    }
+
+    /// Groups closed rings into [`Compound`]s, for example the subpaths of an SVG path.
+    ///
+    /// Rings nested an even number of times become outer rings, rings nested an odd number of times become holes of the closest outer ring around them.
+    pub fn new_from_rings(rings: Vec<Path>, fill_rule: FillRule) -> Vec<Self> {
+        let rings: Vec<Path> = rings
+            .into_iter()
+            .filter(|ring| ring.get_points_ref().len() > 2)
+            .map(Self::closed)
+            .collect();
+
+        let parents: Vec<Vec<usize>> = rings
+            .iter()
+            .enumerate()
+            .map(|(i, ring)| {
+                let point = ring.get_points_ref()[0];
+                (0..rings.len())
+                    .filter(|j| *j != i && rings[*j].contains_point(point))
+                    .collect()
+            })
+            .collect();
+
+        let mut compounds: Vec<(usize, Self)> = Vec::new();
+        for (i, ring) in rings.iter().enumerate() {
+            if parents[i].len().is_multiple_of(2) {
+                compounds.push((
+                    i,
+                    Self {
+                        outer: ring.clone(),
+                        holes: Vec::new(),
+                        fill_rule,
+                    },
+                ));
+            }
+        }
+        for (i, ring) in rings.iter().enumerate() {
+            if !parents[i].len().is_multiple_of(2) {
+                // the closest outer ring is the one nested the deepest
+                let parent = parents[i]
+                    .iter()
+                    .filter(|j| parents[**j].len().is_multiple_of(2))
+                    .max_by_key(|j| parents[**j].len());
+                if let Some((_, compound)) = compounds
+                    .iter_mut()
+                    .find(|(outer_index, _)| Some(outer_index) == parent)
+                {
+                    compound.holes.push(ring.clone());
+                }
+            }
+        }
+        compounds
+            .into_iter()
+            .map(|(_, compound)| compound)
+            .collect()
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    pub fn outer(&self) -> &Path {
+        &self.outer
+    }
+    pub fn holes(&self) -> &[Path] {
+        &self.holes
+    }
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    fn mask_rings(&self, mask_ring: impl Fn(&Path) -> Masked) -> Masked {
+        let mut inside = Layer::new();
+        let mut outside = Layer::new();
+        for ring in self.rings() {
+            let masked = mask_ring(ring);
+            inside.push_layer_flat(masked.inside);
+            outside.push_layer_flat(masked.outside);
+        }
+        Masked { inside, outside }
+    }
+
+    /// Iterates over the outer ring followed by all holes.
+    pub fn rings(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(&self.outer).chain(self.holes.iter())
+    }
+
+    /// Returns the holes that actually cut out area from the outer ring under the [`FillRule`].
+    pub fn effective_holes(&self) -> impl Iterator<Item = &Path> {
+        let outer_orientation = signed_area(self.outer.get_points_ref()).signum();
+        self.holes.iter().filter(move |hole| match self.fill_rule {
+            FillRule::EvenOdd => true,
+            FillRule::NonZero => signed_area(hole.get_points_ref()).signum() != outer_orientation,
+        })
+    }
+
+    /// The filled area. see [`Compound::effective_holes`].
+    pub fn area(&self) -> f32 {
+        signed_area(self.outer.get_points_ref()).abs()
+            - self
+                .effective_holes()
+                .map(|hole| signed_area(hole.get_points_ref()).abs())
+                .sum::<f32>()
+    }
+
+    pub fn intersects_circle(&self, other: &Circle) -> bool {
+        self.rings().any(|ring| ring.intersects_circle(other))
+    }
+
+    pub fn intersects_rect(&self, other: &Rect) -> bool {
+        self.rings().any(|ring| ring.intersects_rect(other))
+    }
+
+    pub fn intersects_path(&self, other: &Path) -> bool {
+        self.rings().any(|ring| ring.intersects_path(other))
+    }
+
+    pub fn intersects_compound(&self, other: &Compound) -> bool {
+        other.rings().any(|ring| self.intersects_path(ring))
+    }
+
+    pub fn contains_shape(&self, other: &Shape) -> Containment {
+        let outer_containment = self.outer.contains_shape(other);
+        if outer_containment == Containment::None {
+            return Containment::None;
+        }
+        for hole in self.effective_holes() {
+            match hole.contains_shape(other) {
+                Containment::Full => return Containment::None,
+                Containment::Partial => return Containment::Partial,
+                Containment::None => {}
+            }
+        }
+        outer_containment
+    }
+
+    /// How much of `self` lies inside `container`. A container that lies fully inside a hole contains nothing.
+    pub(crate) fn containment_in(&self, container: &Shape) -> Containment {
+        match container.contains_path(&self.outer) {
+            Containment::Partial
+                if self
+                    .effective_holes()
+                    .any(|hole| hole.contains_shape(container) == Containment::Full) =>
+            {
+                Containment::None
+            }
+            containment => containment,
+        }
+    }
+
+    fn closed(mut ring: Path) -> Path {
+        if !ring.is_empty() && !ring.is_closed() {
+            ring.close();
+        }
+        ring
+    }
+
+    fn map_rings<F: Fn(&Path) -> Path>(&self, f: F) -> Self {
+        Self {
+            outer: f(&self.outer),
+            holes: self.holes.iter().map(f).collect(),
+            fill_rule: self.fill_rule,
+        }
+    }
+
+    fn map_rings_mut<F: Fn(&mut Path)>(&mut self, f: F) {
+        f(&mut self.outer);
+        self.holes.iter_mut().for_each(f);
+    }
+}
+
+/// Shoelace formula, positive for counter-clockwise rings.
+fn signed_area(points: &[V2]) -> f32 {
+    points
+        .iter()
+        .tuple_windows()
+        .map(|(from, to)| from.x * to.y - to.x * from.y)
+        .sum::<f32>()
+        * 0.5
+}
+
+/// Number of times the closed ring `points` winds counter-clockwise around `point`.
+fn winding_number(points: &[V2], point: V2) -> i32 {
+    let mut winding = 0;
+    for (from, to) in points.iter().tuple_windows() {
+        let is_left = (to.x - from.x) * (point.y - from.y) - (point.x - from.x) * (to.y - from.y);
+        if from.y <= point.y {
+            if to.y > point.y && is_left > 0.0 {
+                winding += 1;
+            }
+        } else if to.y <= point.y && is_left < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+impl Plottable for Compound {
+    /// Returns the points of all rings after each other, the outer ring first. Consecutive rings are not connected
+    /// when plotting, so use [`Compound::rings`] or [`Plottable::get_line_segments`] to draw them separately.
+    fn get_points(&self, sample_settings: SampleSettings) -> Vec<V2> {
+        self.rings()
+            .flat_map(|ring| ring.get_points(sample_settings))
+            .collect()
+    }
+
+    /// see [`Compound::get_points`], each ring starts closest to the end of the previous one.
+    fn get_points_from(
+        &self,
+        current_drawing_head_pos: V2,
+        sample_settings: SampleSettings,
+    ) -> Vec<V2> {
+        let mut points: Vec<V2> = Vec::new();
+        for ring in self.rings() {
+            let from = points.last().copied().unwrap_or(current_drawing_head_pos);
+            points.extend(ring.get_points_from(from, sample_settings));
+        }
+        points
+    }
+
+    /// Returns the line segments of all rings.
+    fn get_line_segments(&self, sample_settings: SampleSettings) -> Vec<Line> {
+        self.rings()
+            .flat_map(|ring| ring.get_line_segments(sample_settings))
+            .collect()
+    }
+
+    fn length(&self) -> f32 {
+        self.rings().map(|ring| ring.length()).sum()
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn contains_point(&self, point: V2) -> bool {
+        match self.fill_rule {
+            FillRule::EvenOdd => self
+                .rings()
+                .fold(false, |inside, ring| inside ^ ring.contains_point(point)),
+            FillRule::NonZero => {
+                self.rings()
+                    .map(|ring| winding_number(ring.get_points_ref(), point))
+                    .sum::<i32>()
+                    != 0
+            }
+        }
+    }
+
+    fn reduce_points(&self, aggression_factor: f32) -> Self {
+        self.map_rings(|ring| ring.reduce_points(aggression_factor))
+    }
+
+    fn as_geo_polygon(&self, sample_settings: SampleSettings) -> Polygon<f32> {
+        Polygon::new(
+            self.outer.as_geo_line_string(sample_settings),
+            self.effective_holes()
+                .map(|hole| hole.as_geo_line_string(sample_settings))
+                .collect(),
+        )
+    }
+
+    fn as_geo_multi_line_string(
+        &self,
+        sample_settings: SampleSettings,
+    ) -> geo_types::MultiLineString<f32> {
+        geo_types::MultiLineString(
+            self.rings()
+                .map(|ring| ring.as_geo_line_string(sample_settings))
+                .collect(),
+        )
+    }
+
+    /// Masks every ring separately.
+    fn mask_brute_force(&self, mask: &Shape, sample_settings: SampleSettings) -> Masked {
+        self.mask_rings(|ring| ring.mask_brute_force(mask, sample_settings))
+    }
+
+    /// Masks every ring separately.
+    fn mask_by_intersections(&self, mask: &Shape, sample_settings: SampleSettings) -> Masked {
+        self.mask_rings(|ring| ring.mask_by_intersections(mask, sample_settings))
+    }
+}
+
+impl Rotate for Compound {
+    fn rotate(&self, angle: Angle) -> Self {
+        self.map_rings(|ring| ring.rotate(angle))
+    }
+    fn rotate_mut(&mut self, angle: Angle) {
+        self.map_rings_mut(|ring| ring.rotate_mut(angle));
+    }
+
+    fn rotate_around(&self, pivot: V2, angle: Angle) -> Self {
+        self.map_rings(|ring| ring.rotate_around(pivot, angle))
+    }
+    fn rotate_around_mut(&mut self, pivot: V2, angle: Angle) {
+        self.map_rings_mut(|ring| ring.rotate_around_mut(pivot, angle));
+    }
+}
+
+impl Rotate90 for Compound {
+    fn rotate_90(&self) -> Self {
+        self.map_rings(|ring| ring.rotate_90())
+    }
+    fn rotate_90_mut(&mut self) {
+        self.map_rings_mut(|ring| ring.rotate_90_mut());
+    }
+
+    fn rotate_180(&self) -> Self {
+        self.map_rings(|ring| ring.rotate_180())
+    }
+    fn rotate_180_mut(&mut self) {
+        self.map_rings_mut(|ring| ring.rotate_180_mut());
+    }
+
+    fn rotate_270(&self) -> Self {
+        self.map_rings(|ring| ring.rotate_270())
+    }
+    fn rotate_270_mut(&mut self) {
+        self.map_rings_mut(|ring| ring.rotate_270_mut());
+    }
+
+    fn rotate_90_around(&self, pivot: V2) -> Self {
+        self.map_rings(|ring| ring.rotate_90_around(pivot))
+    }
+    fn rotate_90_around_mut(&mut self, pivot: V2) {
+        self.map_rings_mut(|ring| ring.rotate_90_around_mut(pivot));
+    }
+
+    fn rotate_180_around(&self, pivot: V2) -> Self {
+        self.map_rings(|ring| ring.rotate_180_around(pivot))
+    }
+    fn rotate_180_around_mut(&mut self, pivot: V2) {
+        self.map_rings_mut(|ring| ring.rotate_180_around_mut(pivot));
+    }
+
+    fn rotate_270_around(&self, pivot: V2) -> Self {
+        self.map_rings(|ring| ring.rotate_270_around(pivot))
+    }
+    fn rotate_270_around_mut(&mut self, pivot: V2) {
+        self.map_rings_mut(|ring| ring.rotate_270_around_mut(pivot));
+    }
+}
+
+impl Translate for Compound {
+    fn translate(&self, dist: V2) -> Self {
+        self.map_rings(|ring| ring.translate(dist))
+    }
+    fn translate_mut(&mut self, dist: V2) {
+        self.map_rings_mut(|ring| ring.translate_mut(dist));
+    }
+}
+
+impl Scale for Compound {
+    fn scale(&self, scale: f32) -> Self {
+        self.map_rings(|ring| ring.scale(scale))
+    }
+    fn scale_mut(&mut self, scale: f32) {
+        self.map_rings_mut(|ring| ring.scale_mut(scale));
+    }
+}
+
+impl Scale2D for Compound {
+    fn scale_2d(&self, factor: V2) -> Self {
+        self.map_rings(|ring| ring.scale_2d(factor))
+    }
+    fn scale_2d_mut(&mut self, factor: V2) {
+        self.map_rings_mut(|ring| ring.scale_2d_mut(factor));
+    }
+}
+
+impl Normalize for Compound {}
+
+impl Mirror for Compound {
+    fn mirror_x(&self) -> Self {
+        self.map_rings(|ring| ring.mirror_x())
+    }
+    fn mirror_x_mut(&mut self) {
+        self.map_rings_mut(|ring| ring.mirror_x_mut());
+    }
+
+    fn mirror_y(&self) -> Self {
+        self.map_rings(|ring| ring.mirror_y())
+    }
+    fn mirror_y_mut(&mut self) {
+        self.map_rings_mut(|ring| ring.mirror_y_mut());
+    }
+}
+
+impl BoundingBox for Compound {
+    fn bounding_box(&self) -> Option<Rect> {
+        self.outer.bounding_box()
+    }
+}
+
+impl Transform for Compound {
+    fn transform(&self, matrix: &TransformMatrix) -> Self {
+        self.map_rings(|ring| ring.transform(matrix))
+    }
+    fn transform_mut(&mut self, matrix: &TransformMatrix) {
+        self.map_rings_mut(|ring| ring.transform_mut(matrix));
+    }
+}
+
+impl ClosestPoint for Compound {
+    fn closest_point(&self, sample_settings: SampleSettings, point: V2) -> Option<V2> {
+        self.rings()
+            .filter_map(|ring| ring.closest_point(sample_settings, point))
+            .min_by(|a, b| a.dist_squared(point).total_cmp(&b.dist_squared(point)))
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod test_compound {
+    use crate::{
+        BoundingBox, Circle, Compound, Containment, FillRule, Layer, Path, Plottable, Rect,
+        SampleSettings, Shape, Translate, LARGE_EPSILON, V2,
+    };
+
+    fn square(bl: V2, size: f32) -> Path {
+        Path::new_from(vec![
+            bl,
+            bl + V2::new(size, 0.0),
+            bl + V2::xy(size),
+            bl + V2::new(0.0, size),
+        ])
+    }
+
+    fn donut() -> Compound {
+        Compound::new(square(V2::zero(), 4.0), vec![square(V2::xy(1.0), 2.0)])
+    }
+
+    #[test]
+    fn new_closes_rings() {
+        let c = donut();
+        assert!(c.outer().is_closed());
+        assert!(c.holes().iter().all(|hole| hole.is_closed()));
+        assert_eq!(c.rings().count(), 2);
+        assert!((c.area() - 12.0).abs() < LARGE_EPSILON);
+        assert!((c.length() - 24.0).abs() < LARGE_EPSILON);
+    }
+
+    #[test]
+    fn contains_point_fill_rules() {
+        let even_odd = donut();
+        assert!(even_odd.contains_point(V2::new(0.5, 2.0)));
+        assert!(!even_odd.contains_point(V2::xy(2.0)));
+        assert!(!even_odd.contains_point(V2::xy(5.0)));
+
+        // hole with the same orientation as the outer ring is filled under non-zero
+        let non_zero = donut().with_fill_rule(FillRule::NonZero);
+        assert!(non_zero.contains_point(V2::xy(2.0)));
+        assert!((non_zero.area() - 16.0).abs() < LARGE_EPSILON);
+
+        // reversed hole cuts out area under non-zero as well
+        let non_zero_reversed = Compound::new(
+            square(V2::zero(), 4.0),
+            vec![square(V2::xy(1.0), 2.0).reverse()],
+        )
+        .with_fill_rule(FillRule::NonZero);
+        assert!(!non_zero_reversed.contains_point(V2::xy(2.0)));
+        assert!(non_zero_reversed.contains_point(V2::new(0.5, 2.0)));
+    }
+
+    #[test]
+    fn new_from_rings() {
+        let rings = vec![
+            square(V2::xy(1.0), 2.0),
+            square(V2::zero(), 4.0),
+            square(V2::xy(1.5), 1.0),
+            square(V2::new(10.0, 0.0), 1.0),
+        ];
+        let compounds = Compound::new_from_rings(rings, FillRule::EvenOdd);
+
+        assert_eq!(compounds.len(), 3);
+        let outer = compounds
+            .iter()
+            .find(|c| c.outer().bounding_box().unwrap().width() == 4.0)
+            .unwrap();
+        assert_eq!(outer.holes().len(), 1);
+        assert!(compounds
+            .iter()
+            .filter(|c| c.outer().bounding_box().unwrap().width() != 4.0)
+            .all(|c| c.holes().is_empty()));
+    }
+
+    #[test]
+    fn containment() {
+        let c: Shape = donut().into();
+
+        assert_eq!(
+            c.contains(&Circle::new_shape(V2::new(0.5, 2.0), 0.2)),
+            Containment::Full
+        );
+        assert_eq!(
+            c.contains(&Circle::new_shape(V2::xy(2.0), 0.2)),
+            Containment::None
+        );
+        assert_eq!(
+            c.contains(&Rect::new_shape(V2::new(0.5, 0.5), V2::new(1.5, 1.5))),
+            Containment::Partial
+        );
+        assert_eq!(
+            c.contains(&Rect::new_shape(V2::xy(10.0), V2::xy(11.0))),
+            Containment::None
+        );
+
+        // containers fully inside the hole contain nothing of the compound
+        let inside_hole = Circle::new(V2::xy(2.0), 0.5);
+        assert_eq!(inside_hole.contains_shape(&c), Containment::None);
+        let around = Rect::new(V2::xy(-1.0), V2::xy(5.0));
+        assert_eq!(around.contains_shape(&c), Containment::Full);
+
+        assert!(c.intersects(&Circle::new_shape(V2::xy(2.0), 1.0)));
+        assert!(!c.intersects(&Circle::new_shape(V2::xy(2.0), 0.5)));
+    }
+
+    #[test]
+    fn geo_polygon() {
+        let sample_settings = SampleSettings::default();
+        let c = donut();
+        let polygon = c.as_geo_polygon(sample_settings);
+        assert_eq!(polygon.interiors().len(), 1);
+
+        let shape = Path::new_shape_from_geo_polygon(polygon);
+        assert_eq!(shape, Shape::Compound(c));
+
+        let without_holes = Path::new_shape_from(square(V2::zero(), 1.0).get_points_ref().to_vec())
+            .as_geo_polygon(sample_settings);
+        assert!(matches!(
+            Path::new_shape_from_geo_polygon(without_holes),
+            Shape::Path(_)
+        ));
+    }
+
+    #[test]
+    fn mask_geo() {
+        let sample_settings = SampleSettings::default();
+        let mask: Shape = donut().into();
+        let line = Path::new_from(vec![V2::new(-1.0, 2.0), V2::new(5.0, 2.0)]);
+
+        let masked = line.mask_geo(&mask, sample_settings);
+        assert_eq!(masked.inside.len(), 2);
+        assert_eq!(masked.outside.len(), 3);
+        let inside_length: f32 = masked.inside.iter().map(|shape| shape.length()).sum();
+        assert!((inside_length - 2.0).abs() < LARGE_EPSILON);
+
+        let brute_force = line.mask_brute_force(&mask, sample_settings);
+        assert_eq!(brute_force.inside.len(), 2);
+    }
+
+    #[test]
+    fn mask_compound_keeps_holes() {
+        let sample_settings = SampleSettings::new(50.0);
+        let c: Shape = donut().into();
+        // covers the left half of the donut, including the left half of the hole
+        let mask = Rect::new_shape(V2::new(-1.0, -1.0), V2::new(2.0, 5.0));
+
+        let geo = c.mask_geo(&mask, sample_settings);
+        let brute_force = c.mask_brute_force(&mask, sample_settings);
+        let intersections = c.mask_by_intersections(&mask, sample_settings);
+        for masked in [geo, brute_force, intersections] {
+            let inside_length: f32 = masked.inside.iter().map(|shape| shape.length()).sum();
+            let outside_length: f32 = masked.outside.iter().map(|shape| shape.length()).sum();
+            // half of the outer ring and half of the hole on each side
+            assert!((inside_length - 12.0).abs() < 0.2, "{}", inside_length);
+            assert!((outside_length - 12.0).abs() < 0.2, "{}", outside_length);
+        }
+
+        let inside = c.mask_geo_inside(&mask, sample_settings);
+        assert!(inside
+            .iter_flattened()
+            .any(|shape| shape.bounding_box().unwrap().bl() == V2::new(1.0, 1.0)));
+    }
+
+    #[test]
+    fn line_segments_of_all_rings() {
+        let c: Shape = donut().into();
+        let segments = c.get_line_segments(SampleSettings::default());
+        assert_eq!(segments.len(), 8);
+        let length: f32 = segments.iter().map(|segment| segment.vector().len()).sum();
+        assert!((length - 24.0).abs() < LARGE_EPSILON);
+    }
+
+    #[test]
+    fn points_of_all_rings() {
+        let compound = donut();
+        let points = compound.get_points(SampleSettings::default());
+        let outer = compound.outer().get_points(SampleSettings::default());
+        assert_eq!(
+            points.len(),
+            outer.len()
+                + compound.holes()[0]
+                    .get_points(SampleSettings::default())
+                    .len()
+        );
+        assert_eq!(points[..outer.len()], outer[..]);
+        assert!(
+            compound
+                .get_points_oversampled(SampleSettings::default())
+                .len()
+                >= points.len()
+        );
+        assert_eq!(
+            compound
+                .as_geo_line_string(SampleSettings::default())
+                .0
+                .len(),
+            points.len()
+        );
+    }
+
+    #[test]
+    fn transforms_keep_holes() {
+        let c: Shape = donut().into();
+        let moved = c.translate(V2::new(10.0, 0.0));
+        match &moved {
+            Shape::Compound(compound) => assert_eq!(compound.holes().len(), 1),
+            _ => panic!("expected compound"),
+        }
+        assert!(!moved.contains_point(V2::new(12.0, 2.0)));
+        assert!(moved.contains_point(V2::new(10.5, 2.0)));
+    }
+
+    #[test]
+    fn svg_export() {
+        let layer = Layer::new_from(vec![donut().into()]);
+        let svg = layer.to_svg(1.0).to_string();
+        assert!(svg.contains("fill-rule=\"evenodd\""));
+        assert_eq!(svg.matches('M').count(), 2);
+    }
+}
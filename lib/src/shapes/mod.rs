@@ -1,5 +1,7 @@
 pub mod circle;
 mod circle_test;
+pub mod compound;
+mod compound_test;
 pub mod containment;
 pub mod path;
 mod path_test;
@@ -9,6 +11,7 @@ pub mod shape;
 mod shape_test;
 
 pub use circle::*;
+pub use compound::*;
 pub use containment::*;
 pub use path::*;
 pub use rect::*;
@@ -12,7 +12,7 @@ use std::{
 use crate::{
     geometry::TransformMatrix,
     traits::{ClosestPoint, Normalize, Scale, Scale2D, Transform, Translate},
-    Angle, BoundingBox, Circle, Containment, Line, LineIntersection, Mirror, Plottable,
+    Angle, BoundingBox, Circle, Compound, Containment, Line, LineIntersection, Mirror, Plottable,
     PointLineRelation, Rect, Rotate, Rotate90, SampleSettings, Shape, V2,
 };
 
@@ -39,7 +39,15 @@ impl Path {
     pub fn new_shape_from(points: Vec<V2>) -> Shape {
         Shape::Path(Self { points })
     }
+    /// Returns a [`Shape::Compound`] if the polygon has interior rings, otherwise a [`Shape::Path`] of the exterior.
     pub fn new_shape_from_geo_polygon(geo_polygon: Polygon<f32>) -> Shape {
+        if geo_polygon
+            .interiors()
+            .iter()
+            .any(|interior| interior.0.len() > 1)
+        {
+            return Shape::Compound(Compound::new_from_geo_polygon(&geo_polygon));
+        }
         Shape::Path(Self::from_iter(
             geo_polygon.exterior().into_iter().map(V2::new_from_geo),
         ))
@@ -105,6 +113,25 @@ impl Path {
         Self::new_from(new_points)
     }
 
+    /// Returns a new `Path` where every segment longer than `max_segment_length` is split into equally long segments.
+    ///
+    /// The original points are kept, so the shape of the `Path` does not change.
+    pub fn subdivide(&self, max_segment_length: f32) -> Self {
+        if self.points.len() < 2 || max_segment_length <= 0.0 {
+            return self.clone();
+        }
+
+        let mut new_points = Vec::with_capacity(self.points.len());
+        new_points.push(self.points[0]);
+        for (from, to) in self.points.iter().tuple_windows() {
+            let num_segments = (from.dist(*to) / max_segment_length).ceil().max(1.0) as usize;
+            for i in 1..=num_segments {
+                new_points.push(from.lerp(*to, i as f32 / num_segments as f32));
+            }
+        }
+        Self::new_from(new_points)
+    }
+
     fn rounded_chaikins_iteration(points: Vec<V2>) -> Vec<V2> {
         if points.len() <= 2 {
             return points;
@@ -397,6 +424,7 @@ impl Path {
             Shape::Circle(c) => self.contains_circle(c),
             Shape::Rect(r) => self.contains_rect(r),
             Shape::Path(p) => self.contains_path(p),
+            Shape::Compound(c) => c.containment_in(&self.into()),
         }
     }
 }
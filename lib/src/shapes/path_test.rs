@@ -287,4 +287,22 @@ mod test_path {
         assert!(!p.contains_point(V2::new(-0.5, 0.5)));
         assert!(!p.contains_point(V2::new(0.5, -0.5)));
     }
+
+    #[test]
+    fn subdivide() {
+        let p = Path::new_from(vec![
+            V2::new(0.0, 0.0),
+            V2::new(1.0, 0.0),
+            V2::new(1.0, 0.25),
+        ]);
+        let subdivided = p.subdivide(0.3);
+
+        assert_eq!(subdivided.get_points_ref().len(), 6);
+        assert_eq!(subdivided.get_start(), p.get_start());
+        assert_eq!(subdivided.get_end(), p.get_end());
+        assert!((subdivided.length() - p.length()).abs() < LARGE_EPSILON);
+        for (a, b) in subdivided.iter().zip(subdivided.iter().skip(1)) {
+            assert!(a.dist(*b) <= 0.3 + LARGE_EPSILON);
+        }
+    }
 }
@@ -394,6 +394,7 @@ impl Rect {
             Shape::Circle(c) => self.contains_circle(c),
             Shape::Rect(r) => self.contains_rect(r),
             Shape::Path(p) => self.contains_path(p),
+            Shape::Compound(c) => c.containment_in(&self.into()),
         }
     }
 
@@ -479,10 +480,22 @@ impl Plottable for Rect {
     }
     fn get_points_from(
         &self,
-        _current_drawing_head_pos: V2,
-        sample_settings: SampleSettings,
+        current_drawing_head_pos: V2,
+        _sample_settings: SampleSettings,
     ) -> Vec<V2> {
-        self.get_points(sample_settings)
+        let corners = [self.bl(), self.tl(), self.tr(), self.br()];
+        let start = corners
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.dist_squared(current_drawing_head_pos)
+                    .total_cmp(&b.dist_squared(current_drawing_head_pos))
+            })
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        (0..=corners.len())
+            .map(|i| corners[(start + i) % corners.len()])
+            .collect()
     }
 
     fn length(&self) -> f32 {
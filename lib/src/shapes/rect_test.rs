@@ -34,6 +34,16 @@ mod test_rect {
         assert_eq!(points.len(), 5);
     }
 
+    #[test]
+    fn rect_points_from_nearest_corner() {
+        let r = Rect::new_shape(V2::new(1.0, 2.0), V2::new(4.0, 4.0));
+        let points = r.get_points_from(V2::new(5.0, 5.0), SampleSettings::default());
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[0], V2::new(4.0, 4.0));
+        assert_eq!(points[4], V2::new(4.0, 4.0));
+        assert!((points.windows(2).map(|w| w[0].dist(w[1])).sum::<f32>() - 10.0).abs() < 0.001);
+    }
+
     #[test]
     fn scale() {
         let r = Rect::new(V2::new(1.0, 2.0), V2::new(4.0, 4.0));
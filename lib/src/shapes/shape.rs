@@ -1,12 +1,15 @@
 pub use crate::shapes::circle::Circle;
+pub use crate::shapes::compound::Compound;
 pub use crate::shapes::path::Path;
 pub use crate::shapes::rect::Rect;
 
 use crate::{
     geometry::TransformMatrix,
     traits::{ClosestPoint, Normalize, Scale, Scale2D, Transform, Translate},
-    Angle, BoundingBox, Containment, Mirror, Plottable, Rotate, Rotate90, SampleSettings, V2,
+    Angle, BoundingBox, Containment, Line, Masked, Mirror, Plottable, Rotate, Rotate90,
+    SampleSettings, V2,
 };
+use geo_types::{MultiLineString, Polygon};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,6 +17,7 @@ pub enum Shape {
     Circle(Circle),
     Rect(Rect),
     Path(Path),
+    Compound(Compound),
 }
 
 impl Shape {
@@ -22,6 +26,7 @@ impl Shape {
             Shape::Circle(c) => c.intersects_circle(other),
             Shape::Rect(r) => r.intersects_circle(other),
             Shape::Path(p) => p.intersects_circle(other),
+            Shape::Compound(c) => c.intersects_circle(other),
         }
     }
 
@@ -30,6 +35,7 @@ impl Shape {
             Shape::Circle(c) => c.intersects_rect(other),
             Shape::Rect(r) => r.intersects_rect(other),
             Shape::Path(p) => p.intersects_rect(other),
+            Shape::Compound(c) => c.intersects_rect(other),
         }
     }
 
@@ -38,6 +44,7 @@ impl Shape {
             Shape::Circle(c) => c.intersects_path(other),
             Shape::Rect(r) => r.intersects_path(other),
             Shape::Path(p) => p.intersects_path(other),
+            Shape::Compound(c) => c.intersects_path(other),
         }
     }
 
@@ -46,14 +53,20 @@ impl Shape {
             Shape::Circle(c) => self.intersects_circle(c),
             Shape::Rect(r) => self.intersects_rect(r),
             Shape::Path(p) => self.intersects_path(p),
+            Shape::Compound(c) => self.intersects_compound(c),
         }
     }
 
+    pub fn intersects_compound(&self, other: &Compound) -> bool {
+        other.rings().any(|ring| self.intersects_path(ring))
+    }
+
     pub fn contains_circle(&self, other: &Circle) -> Containment {
         match self {
             Shape::Circle(c) => c.contains_circle(other),
             Shape::Rect(r) => r.contains_circle(other),
             Shape::Path(p) => p.contains_circle(other),
+            Shape::Compound(c) => c.contains_shape(&other.into()),
         }
     }
 
@@ -62,6 +75,7 @@ impl Shape {
             Shape::Circle(c) => c.contains_rect(other),
             Shape::Rect(r) => r.contains_rect(other),
             Shape::Path(p) => p.contains_rect(other),
+            Shape::Compound(c) => c.contains_shape(&other.into()),
         }
     }
 
@@ -70,6 +84,7 @@ impl Shape {
             Shape::Circle(c) => c.contains_path(other),
             Shape::Rect(r) => r.contains_path(other),
             Shape::Path(p) => p.contains_path(other),
+            Shape::Compound(c) => c.contains_shape(&other.into()),
         }
     }
 
@@ -78,6 +93,43 @@ impl Shape {
             Shape::Circle(c) => self.contains_circle(c),
             Shape::Rect(r) => self.contains_rect(r),
             Shape::Path(p) => self.contains_path(p),
+            Shape::Compound(c) => self.contains_compound(c),
+        }
+    }
+
+    pub fn contains_compound(&self, other: &Compound) -> Containment {
+        match self {
+            Shape::Compound(c) => c.contains_shape(&other.into()),
+            _ => other.containment_in(self),
+        }
+    }
+
+    /// Applies an arbitrary distortion `f` to every point of the `Shape`. The result is a [`Path`], or a [`Compound`] if `self` is one.
+    ///
+    /// [`Circle`]s and [`Rect`]s are sampled with `sample_settings` first, then all segments are subdivided to at most `max_segment_length`
+    /// (see [`Path::subdivide`]), so straight lines bend smoothly under non-linear distortions.
+    pub fn warp<F: Fn(V2) -> V2>(
+        &self,
+        f: F,
+        max_segment_length: f32,
+        sample_settings: SampleSettings,
+    ) -> Shape {
+        let warp_path = |path: &Path| -> Path {
+            path.subdivide(max_segment_length)
+                .iter()
+                .map(|point| f(*point))
+                .collect()
+        };
+        match self {
+            Shape::Path(p) => Shape::Path(warp_path(p)),
+            Shape::Compound(c) => Shape::Compound(
+                Compound::new(
+                    warp_path(c.outer()),
+                    c.holes().iter().map(warp_path).collect(),
+                )
+                .with_fill_rule(c.fill_rule()),
+            ),
+            _ => Shape::Path(warp_path(&Path::new_from(self.get_points(sample_settings)))),
         }
     }
 }
@@ -88,6 +140,7 @@ impl Plottable for Shape {
             Shape::Circle(c) => c.get_points(sample_settings),
             Shape::Rect(r) => r.get_points(sample_settings),
             Shape::Path(p) => p.get_points(sample_settings),
+            Shape::Compound(c) => c.get_points(sample_settings),
         }
     }
     fn get_points_from(
@@ -99,6 +152,16 @@ impl Plottable for Shape {
             Shape::Circle(c) => c.get_points_from(current_drawing_head_pos, sample_settings),
             Shape::Rect(r) => r.get_points_from(current_drawing_head_pos, sample_settings),
             Shape::Path(p) => p.get_points_from(current_drawing_head_pos, sample_settings),
+            Shape::Compound(c) => c.get_points_from(current_drawing_head_pos, sample_settings),
+        }
+    }
+
+    fn get_line_segments(&self, sample_settings: SampleSettings) -> Vec<Line> {
+        match self {
+            Shape::Circle(c) => c.get_line_segments(sample_settings),
+            Shape::Rect(r) => r.get_line_segments(sample_settings),
+            Shape::Path(p) => p.get_line_segments(sample_settings),
+            Shape::Compound(c) => c.get_line_segments(sample_settings),
         }
     }
 
@@ -107,6 +170,7 @@ impl Plottable for Shape {
             Shape::Circle(c) => c.length(),
             Shape::Rect(r) => r.length(),
             Shape::Path(p) => p.length(),
+            Shape::Compound(c) => c.length(),
         }
     }
 
@@ -115,6 +179,7 @@ impl Plottable for Shape {
             Shape::Circle(c) => c.is_closed(),
             Shape::Rect(r) => r.is_closed(),
             Shape::Path(p) => p.is_closed(),
+            Shape::Compound(c) => c.is_closed(),
         }
     }
 
@@ -123,6 +188,7 @@ impl Plottable for Shape {
             Shape::Circle(c) => c.contains_point(point),
             Shape::Rect(r) => r.contains_point(point),
             Shape::Path(p) => p.contains_point(point),
+            Shape::Compound(c) => c.contains_point(point),
         }
     }
 
@@ -131,6 +197,39 @@ impl Plottable for Shape {
             Shape::Circle(c) => Shape::Circle(c.reduce_points(aggression_factor)),
             Shape::Rect(r) => Shape::Rect(r.reduce_points(aggression_factor)),
             Shape::Path(p) => Shape::Path(p.reduce_points(aggression_factor)),
+            Shape::Compound(c) => Shape::Compound(c.reduce_points(aggression_factor)),
+        }
+    }
+
+    fn as_geo_polygon(&self, sample_settings: SampleSettings) -> Polygon<f32> {
+        match self {
+            Shape::Compound(c) => c.as_geo_polygon(sample_settings),
+            _ => Polygon::new(self.as_geo_line_string(sample_settings), vec![]),
+        }
+    }
+
+    fn as_geo_multi_line_string(&self, sample_settings: SampleSettings) -> MultiLineString<f32> {
+        match self {
+            Shape::Compound(c) => c.as_geo_multi_line_string(sample_settings),
+            _ => MultiLineString(vec![self.as_geo_line_string(sample_settings)]),
+        }
+    }
+
+    fn mask_brute_force(&self, mask: &Shape, sample_settings: SampleSettings) -> Masked {
+        match self {
+            Shape::Circle(c) => c.mask_brute_force(mask, sample_settings),
+            Shape::Rect(r) => r.mask_brute_force(mask, sample_settings),
+            Shape::Path(p) => p.mask_brute_force(mask, sample_settings),
+            Shape::Compound(c) => c.mask_brute_force(mask, sample_settings),
+        }
+    }
+
+    fn mask_by_intersections(&self, mask: &Shape, sample_settings: SampleSettings) -> Masked {
+        match self {
+            Shape::Circle(c) => c.mask_by_intersections(mask, sample_settings),
+            Shape::Rect(r) => r.mask_by_intersections(mask, sample_settings),
+            Shape::Path(p) => p.mask_by_intersections(mask, sample_settings),
+            Shape::Compound(c) => c.mask_by_intersections(mask, sample_settings),
         }
     }
 }
@@ -143,6 +242,7 @@ impl Rotate for Shape {
                 Path::new_shape_from(vec![r.bl(), r.tl(), r.tr(), r.br(), r.bl()]).rotate(angle)
             }
             Shape::Path(p) => Shape::Path(p.rotate(angle)),
+            Shape::Compound(c) => Shape::Compound(c.rotate(angle)),
         }
     }
     fn rotate_mut(&mut self, angle: Angle) {
@@ -153,6 +253,7 @@ impl Rotate for Shape {
                     Path::new_shape_from(vec![r.bl(), r.tl(), r.tr(), r.br(), r.bl()]).rotate(angle)
             }
             Shape::Path(p) => p.rotate_mut(angle),
+            Shape::Compound(c) => c.rotate_mut(angle),
         }
     }
 
@@ -162,6 +263,7 @@ impl Rotate for Shape {
             Shape::Rect(r) => Path::new_shape_from(vec![r.bl(), r.tl(), r.tr(), r.br(), r.bl()])
                 .rotate_around(pivot, angle),
             Shape::Path(p) => Shape::Path(p.rotate_around(pivot, angle)),
+            Shape::Compound(c) => Shape::Compound(c.rotate_around(pivot, angle)),
         }
     }
     fn rotate_around_mut(&mut self, pivot: V2, angle: Angle) {
@@ -172,6 +274,7 @@ impl Rotate for Shape {
                     .rotate_around(pivot, angle)
             }
             Shape::Path(p) => p.rotate_around_mut(pivot, angle),
+            Shape::Compound(c) => c.rotate_around_mut(pivot, angle),
         }
     }
 }
@@ -182,6 +285,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_90()),
             Shape::Rect(r) => Shape::Rect(r.rotate_90()),
             Shape::Path(p) => Shape::Path(p.rotate_90()),
+            Shape::Compound(c) => Shape::Compound(c.rotate_90()),
         }
     }
     fn rotate_90_mut(&mut self) {
@@ -189,6 +293,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_90_mut(),
             Shape::Rect(r) => r.rotate_90_mut(),
             Shape::Path(p) => p.rotate_90_mut(),
+            Shape::Compound(c) => c.rotate_90_mut(),
         }
     }
 
@@ -197,6 +302,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_180()),
             Shape::Rect(r) => Shape::Rect(r.rotate_180()),
             Shape::Path(p) => Shape::Path(p.rotate_180()),
+            Shape::Compound(c) => Shape::Compound(c.rotate_180()),
         }
     }
     fn rotate_180_mut(&mut self) {
@@ -204,6 +310,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_180_mut(),
             Shape::Rect(r) => r.rotate_180_mut(),
             Shape::Path(p) => p.rotate_180_mut(),
+            Shape::Compound(c) => c.rotate_180_mut(),
         }
     }
 
@@ -212,6 +319,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_270()),
             Shape::Rect(r) => Shape::Rect(r.rotate_270()),
             Shape::Path(p) => Shape::Path(p.rotate_270()),
+            Shape::Compound(c) => Shape::Compound(c.rotate_270()),
         }
     }
     fn rotate_270_mut(&mut self) {
@@ -219,6 +327,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_270_mut(),
             Shape::Rect(r) => r.rotate_270_mut(),
             Shape::Path(p) => p.rotate_270_mut(),
+            Shape::Compound(c) => c.rotate_270_mut(),
         }
     }
 
@@ -227,6 +336,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_90_around(pivot)),
             Shape::Rect(r) => Shape::Rect(r.rotate_90_around(pivot)),
             Shape::Path(p) => Shape::Path(p.rotate_90_around(pivot)),
+            Shape::Compound(c) => Shape::Compound(c.rotate_90_around(pivot)),
         }
     }
     fn rotate_90_around_mut(&mut self, pivot: V2) {
@@ -234,6 +344,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_90_around_mut(pivot),
             Shape::Rect(r) => r.rotate_90_around_mut(pivot),
             Shape::Path(p) => p.rotate_90_around_mut(pivot),
+            Shape::Compound(c) => c.rotate_90_around_mut(pivot),
         }
     }
 
@@ -242,6 +353,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_180_around(pivot)),
             Shape::Rect(r) => Shape::Rect(r.rotate_180_around(pivot)),
             Shape::Path(p) => Shape::Path(p.rotate_180_around(pivot)),
+            Shape::Compound(c) => Shape::Compound(c.rotate_180_around(pivot)),
         }
     }
     fn rotate_180_around_mut(&mut self, pivot: V2) {
@@ -249,6 +361,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_180_around_mut(pivot),
             Shape::Rect(r) => r.rotate_180_around_mut(pivot),
             Shape::Path(p) => p.rotate_180_around_mut(pivot),
+            Shape::Compound(c) => c.rotate_180_around_mut(pivot),
         }
     }
 
@@ -257,6 +370,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => Shape::Circle(c.rotate_270_around(pivot)),
             Shape::Rect(r) => Shape::Rect(r.rotate_270_around(pivot)),
             Shape::Path(p) => Shape::Path(p.rotate_270_around(pivot)),
+            Shape::Compound(c) => Shape::Compound(c.rotate_270_around(pivot)),
         }
     }
 
@@ -265,6 +379,7 @@ impl Rotate90 for Shape {
             Shape::Circle(c) => c.rotate_270_around_mut(pivot),
             Shape::Rect(r) => r.rotate_270_around_mut(pivot),
             Shape::Path(p) => p.rotate_270_around_mut(pivot),
+            Shape::Compound(c) => c.rotate_270_around_mut(pivot),
         }
     }
 }
@@ -275,6 +390,7 @@ impl Translate for Shape {
             Shape::Circle(c) => Shape::Circle(c.translate(dist)),
             Shape::Rect(r) => Shape::Rect(r.translate(dist)),
             Shape::Path(p) => Shape::Path(p.translate(dist)),
+            Shape::Compound(c) => Shape::Compound(c.translate(dist)),
         }
     }
 
@@ -283,6 +399,7 @@ impl Translate for Shape {
             Shape::Circle(c) => c.translate_mut(dist),
             Shape::Rect(r) => r.translate_mut(dist),
             Shape::Path(p) => p.translate_mut(dist),
+            Shape::Compound(c) => c.translate_mut(dist),
         }
     }
 }
@@ -293,6 +410,7 @@ impl Scale for Shape {
             Shape::Circle(c) => Shape::Circle(c.scale(scale)),
             Shape::Rect(r) => Shape::Rect(r.scale(scale)),
             Shape::Path(p) => Shape::Path(p.scale(scale)),
+            Shape::Compound(c) => Shape::Compound(c.scale(scale)),
         }
     }
 
@@ -301,6 +419,7 @@ impl Scale for Shape {
             Shape::Circle(c) => c.scale_mut(scale),
             Shape::Rect(r) => r.scale_mut(scale),
             Shape::Path(p) => p.scale_mut(scale),
+            Shape::Compound(c) => c.scale_mut(scale),
         }
     }
 }
@@ -317,6 +436,7 @@ impl Scale2D for Shape {
             }
             Shape::Rect(r) => Shape::Rect(r.scale_2d(factor)),
             Shape::Path(p) => Shape::Path(p.scale_2d(factor)),
+            Shape::Compound(c) => Shape::Compound(c.scale_2d(factor)),
         }
     }
 
@@ -331,6 +451,7 @@ impl Scale2D for Shape {
             }
             Shape::Rect(r) => r.scale_2d_mut(factor),
             Shape::Path(p) => p.scale_2d_mut(factor),
+            Shape::Compound(c) => c.scale_2d_mut(factor),
         }
     }
 }
@@ -343,6 +464,7 @@ impl Mirror for Shape {
             Shape::Circle(c) => Shape::Circle(c.mirror_x()),
             Shape::Rect(r) => Shape::Rect(r.mirror_x()),
             Shape::Path(p) => Shape::Path(p.mirror_x()),
+            Shape::Compound(c) => Shape::Compound(c.mirror_x()),
         }
     }
 
@@ -351,6 +473,7 @@ impl Mirror for Shape {
             Shape::Circle(c) => c.mirror_x_mut(),
             Shape::Rect(r) => r.mirror_x_mut(),
             Shape::Path(p) => p.mirror_x_mut(),
+            Shape::Compound(c) => c.mirror_x_mut(),
         }
     }
 
@@ -359,6 +482,7 @@ impl Mirror for Shape {
             Shape::Circle(c) => Shape::Circle(c.mirror_y()),
             Shape::Rect(r) => Shape::Rect(r.mirror_y()),
             Shape::Path(p) => Shape::Path(p.mirror_y()),
+            Shape::Compound(c) => Shape::Compound(c.mirror_y()),
         }
     }
 
@@ -367,6 +491,7 @@ impl Mirror for Shape {
             Shape::Circle(c) => c.mirror_y_mut(),
             Shape::Rect(r) => r.mirror_y_mut(),
             Shape::Path(p) => p.mirror_y_mut(),
+            Shape::Compound(c) => c.mirror_y_mut(),
         }
     }
 }
@@ -377,6 +502,7 @@ impl BoundingBox for Shape {
             Shape::Circle(c) => c.bounding_box(),
             Shape::Rect(r) => r.bounding_box(),
             Shape::Path(p) => p.bounding_box(),
+            Shape::Compound(c) => c.bounding_box(),
         }
     }
 }
@@ -401,6 +527,7 @@ impl Transform for Shape {
                 Path::new_shape_from(points)
             }
             Shape::Path(p) => Shape::Path(p.transform(matrix)),
+            Shape::Compound(c) => Shape::Compound(c.transform(matrix)),
         }
     }
 
@@ -425,6 +552,7 @@ impl Transform for Shape {
                 *self = Path::new_shape_from(points);
             }
             Shape::Path(p) => p.transform_mut(matrix),
+            Shape::Compound(c) => c.transform_mut(matrix),
         }
     }
 }
@@ -435,6 +563,7 @@ impl ClosestPoint for Shape {
             Shape::Circle(c) => c.closest_point(sample_settings, point),
             Shape::Rect(r) => r.closest_point(sample_settings, point),
             Shape::Path(p) => p.closest_point(sample_settings, point),
+            Shape::Compound(c) => c.closest_point(sample_settings, point),
         }
     }
 }
@@ -475,6 +604,18 @@ impl From<&Rect> for Shape {
     }
 }
 
+impl From<Compound> for Shape {
+    fn from(compound: Compound) -> Self {
+        Shape::Compound(compound)
+    }
+}
+
+impl From<&Compound> for Shape {
+    fn from(compound: &Compound) -> Self {
+        Shape::Compound(compound.clone())
+    }
+}
+
 impl From<&Path> for Shape {
     fn from(path: &Path) -> Self {
         Shape::Path(path.clone())
@@ -3,6 +3,7 @@ use anyhow::Result;
 use super::Scale;
 use crate::{BoundingBox, Rect, Translate, LARGE_EPSILON};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Alignment {
     Top,
     Right,
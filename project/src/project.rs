@@ -328,4 +328,15 @@ impl Project {
 
         Ok(())
     }
+
+    pub fn write_pdf(
+        &self,
+        path: PathBuf,
+        release: bool,
+        params: &ProjectParamsListWrapper,
+        settings: &PdfSettings,
+    ) -> Result<()> {
+        let layer = self.run(release, params)?;
+        layer.write_pdf(&path, settings)
+    }
 }
@@ -0,0 +1,450 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use plottery_lib::*;
+
+use crate::plot_setting::PlotSettings;
+
+/// How the pen is lifted and lowered in G-code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GcodePen {
+    /// Moves the Z axis to the given heights in mm. The pen down height is lowered further by
+    /// [`LayerPropsInheritable::pen_down_depth_cm`].
+    ZAxis { up_mm: f32, down_mm: f32 },
+    /// Sets a servo (or laser/spindle output) with `M3 S<value>` and waits `dwell_ms` for it to move.
+    Servo { up: f32, down: f32, dwell_ms: u32 },
+    /// Custom G-code snippets, written as they are.
+    Custom { up: String, down: String },
+}
+
+/// Firmware family of the plotter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcodeFlavor {
+    /// GRBL, where `G4 P` dwells in seconds.
+    Grbl,
+    /// Marlin, where `G4 P` dwells in milliseconds.
+    Marlin,
+}
+
+/// Settings for [`layer_to_gcode`] and [`layer_from_gcode`].
+///
+/// `header`, `footer` and `tool_change` are templates in which `{pen_up}` and `{pen_down}` are replaced by the pen commands.
+/// In `tool_change`, `{tool}`, `{color}` and `{name}` are replaced by the pen id, the hex color and the name of the layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeSettings {
+    pub pen: GcodePen,
+    pub flavor: GcodeFlavor,
+    pub header: String,
+    pub footer: String,
+    /// Written before the first drawn layer and before every layer that uses another pen id than the previous one.
+    /// `None` never stops for pen changes and assumes the right pen is loaded.
+    pub tool_change: Option<String>,
+}
+
+impl GcodeSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_pen(mut self, pen: GcodePen) -> Self {
+        self.pen = pen;
+        self
+    }
+    pub fn with_flavor(mut self, flavor: GcodeFlavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+    pub fn with_header(mut self, header: &str) -> Self {
+        self.header = header.to_string();
+        self
+    }
+    pub fn with_footer(mut self, footer: &str) -> Self {
+        self.footer = footer.to_string();
+        self
+    }
+    pub fn with_tool_change(mut self, tool_change: Option<&str>) -> Self {
+        self.tool_change = tool_change.map(str::to_string);
+        self
+    }
+
+    fn pen_up(&self) -> String {
+        match &self.pen {
+            GcodePen::ZAxis { up_mm, .. } => format!("G0 Z{}", format_mm(*up_mm)),
+            GcodePen::Servo { up, dwell_ms, .. } => {
+                format!("M3 S{}\n{}", up, self.dwell(*dwell_ms))
+            }
+            GcodePen::Custom { up, .. } => up.trim_end().to_string(),
+        }
+    }
+
+    fn pen_down(&self, props: &LayerPropsInheritable, plot_settings: &PlotSettings) -> String {
+        match &self.pen {
+            GcodePen::ZAxis { down_mm, .. } => format!(
+                "G1 Z{} F{}",
+                format_mm(down_mm - props.pen_down_depth_cm.unwrap() * 10.0),
+                feed_rate(plot_settings.speed_head_down.max)
+            ),
+            GcodePen::Servo { down, dwell_ms, .. } => {
+                format!("M3 S{}\n{}", down, self.dwell(*dwell_ms))
+            }
+            GcodePen::Custom { down, .. } => down.trim_end().to_string(),
+        }
+    }
+
+    fn dwell(&self, dwell_ms: u32) -> String {
+        match self.flavor {
+            GcodeFlavor::Grbl => format!("G4 P{}", dwell_ms as f32 / 1000.0),
+            GcodeFlavor::Marlin => format!("G4 P{}", dwell_ms),
+        }
+    }
+
+    fn fill_template(&self, template: &str, pen_down: &str) -> String {
+        template
+            .replace("{pen_up}", &self.pen_up())
+            .replace("{pen_down}", pen_down)
+    }
+}
+
+impl Default for GcodeSettings {
+    fn default() -> Self {
+        Self {
+            pen: GcodePen::Servo {
+                up: 0.0,
+                down: 1000.0,
+                dwell_ms: 150,
+            },
+            flavor: GcodeFlavor::Grbl,
+            header: "G21\nG90\n{pen_up}\n".to_string(),
+            footer: "{pen_up}\nG0 X0 Y0\nM5\n".to_string(),
+            tool_change: Some("{pen_up}\nM0 (insert pen {tool}: {name} {color})\n".to_string()),
+        }
+    }
+}
+
+/// G-code feed rate in mm/min of a speed in cm/s.
+fn feed_rate(speed_cm_per_s: f32) -> String {
+    format!("{}", (speed_cm_per_s * 600.0).round())
+}
+
+fn format_mm(value: f32) -> String {
+    let formatted = format!("{:.3}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Returns G-code drawing `layer`, interpreting all coordinates as cm.
+///
+/// Layers are drawn in plot order (see [`Layer::layers_in_plot_order`]) including their passes. Travel moves use `G0` with
+/// the maximum travel speed and drawing moves use `G1` with the maximum drawing speed of `plot_settings`, adjusted to each
+/// layer with [`PlotSettings::for_layer`].
+///
+/// ### Example
+/// ```
+/// # use plottery_lib::*;
+/// # use plottery_server_lib::*;
+/// let layer = Layer::new_from(vec![Path::new_shape_from(vec![V2::new(1.0, 1.0), V2::new(2.0, 1.0)])]);
+/// let gcode = layer_to_gcode(&layer, &GcodeSettings::new(), &PlotSettings::default(), SampleSettings::default());
+/// assert!(gcode.contains("G0 X10 Y10"));
+/// assert!(gcode.contains("G1 X20 Y10 F2700"));
+/// ```
+pub fn layer_to_gcode(
+    layer: &Layer,
+    settings: &GcodeSettings,
+    plot_settings: &PlotSettings,
+    sample_settings: SampleSettings,
+) -> String {
+    let default_pen_down = settings.pen_down(&LayerPropsInheritable::default(), plot_settings);
+    let mut lines = vec![settings.fill_template(&settings.header, &default_pen_down)];
+    let travel_feed = feed_rate(plot_settings.speed_travel.max);
+
+    let mut current_pen_id = None;
+    let mut pos = V2::zero();
+    for (sublayer, props) in layer.layers_in_plot_order() {
+        if sublayer.shapes.is_empty() {
+            continue;
+        }
+        let layer_plot_settings = plot_settings.for_layer(&props);
+        let pen_down = settings.pen_down(&props, plot_settings);
+        let draw_feed = feed_rate(layer_plot_settings.speed_draw.max);
+
+        let pen_id = props.pen_id.unwrap();
+        if let Some(tool_change) = &settings.tool_change {
+            if current_pen_id != Some(pen_id) {
+                lines.push(
+                    settings
+                        .fill_template(tool_change, &pen_down)
+                        .replace("{tool}", &pen_id.to_string())
+                        .replace("{color}", &props.color.unwrap().hex())
+                        .replace("{name}", sublayer.props.name.as_deref().unwrap_or("")),
+                );
+            }
+        }
+        current_pen_id = Some(pen_id);
+        if let Some(name) = &sublayer.props.name {
+            lines.push(format!("(layer {})", name));
+        }
+
+        let mut write_stroke = |stroke: &Shape| {
+            let points = stroke.get_points_from(pos, sample_settings);
+            if points.len() < 2 {
+                return;
+            }
+            lines.push(format!(
+                "G0 X{} Y{} F{}",
+                format_mm(points[0].x * 10.0),
+                format_mm(points[0].y * 10.0),
+                travel_feed
+            ));
+            lines.push(pen_down.clone());
+            for (i, point) in points.iter().enumerate().skip(1) {
+                let mut line = format!(
+                    "G1 X{} Y{}",
+                    format_mm(point.x * 10.0),
+                    format_mm(point.y * 10.0)
+                );
+                if i == 1 {
+                    line += &format!(" F{}", draw_feed);
+                }
+                lines.push(line);
+            }
+            lines.push(settings.pen_up());
+            pos = *points.last().unwrap();
+        };
+        for shape in sublayer.iter() {
+            for _ in 0..props.plot_passes() {
+                match shape {
+                    Shape::Compound(compound) => {
+                        compound.rings().for_each(|ring| write_stroke(&ring.into()))
+                    }
+                    _ => write_stroke(shape),
+                }
+            }
+        }
+    }
+
+    lines.push(settings.fill_template(&settings.footer, &default_pen_down));
+    let mut gcode = lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    gcode.push('\n');
+    gcode
+}
+
+/// Writes G-code drawing `layer` to a file, see [`layer_to_gcode`].
+pub fn write_gcode(
+    layer: &Layer,
+    path: &PathBuf,
+    settings: &GcodeSettings,
+    plot_settings: &PlotSettings,
+    sample_settings: SampleSettings,
+) -> Result<()> {
+    fs::write(
+        path,
+        layer_to_gcode(layer, settings, plot_settings, sample_settings),
+    )?;
+    Ok(())
+}
+
+/// Rebuilds the drawn paths of G-code as a [`Layer`] in cm, for example to preview it.
+///
+/// Whether the pen is down is read according to `settings.pen`: from the Z height, from `M3 S<value>` (with `M5` lifting the pen),
+/// or from lines matching the first line of the custom snippets. `G0`, `G1`, `G2` and `G3` (with `I`/`J` centers) moves are supported,
+/// as well as `G20`/`G21` units and `G90`/`G91` positioning. Comments and other commands are ignored.
+pub fn layer_from_gcode(gcode: &str, settings: &GcodeSettings) -> Result<Layer> {
+    let custom_lines = match &settings.pen {
+        GcodePen::Custom { up, down } => {
+            let first_line =
+                |snippet: &str| snippet.lines().next().unwrap_or("").trim().to_string();
+            Some((first_line(up), first_line(down)))
+        }
+        _ => None,
+    };
+
+    let mut layer = Layer::new();
+    let mut stroke: Vec<V2> = Vec::new();
+    let mut pos = V2::zero();
+    let mut is_down = false;
+    let mut is_relative = false;
+    let mut mm_per_unit = 1.0;
+    let mut motion = 0;
+
+    let finish_stroke = |stroke: &mut Vec<V2>, layer: &mut Layer| {
+        if stroke.len() >= 2 {
+            layer.push(Path::new_from(std::mem::take(stroke)));
+        }
+        stroke.clear();
+    };
+
+    for (line_index, raw_line) in gcode.lines().enumerate() {
+        let line = strip_gcode_comments(raw_line);
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some((up, down)) = &custom_lines {
+            if line == *down {
+                is_down = true;
+                continue;
+            }
+            if line == *up {
+                finish_stroke(&mut stroke, &mut layer);
+                is_down = false;
+                continue;
+            }
+        }
+
+        let words = parse_gcode_words(&line)
+            .map_err(|error| anyhow!("line {}: {}", line_index + 1, error))?;
+        let word = |letter: char| {
+            words
+                .iter()
+                .find(|(l, _)| *l == letter)
+                .map(|(_, value)| *value)
+        };
+
+        let mut has_motion = false;
+        for (letter, value) in words.iter() {
+            match (letter, *value as i32) {
+                ('G', code @ 0..=3) => {
+                    motion = code;
+                    has_motion = true;
+                }
+                ('G', 20) => mm_per_unit = 25.4,
+                ('G', 21) => mm_per_unit = 1.0,
+                ('G', 90) => is_relative = false,
+                ('G', 91) => is_relative = true,
+                ('M', 3) | ('M', 4) => {
+                    if let (GcodePen::Servo { up, down, .. }, Some(s)) = (&settings.pen, word('S'))
+                    {
+                        let new_down = (s - down).abs() < (s - up).abs();
+                        if is_down && !new_down {
+                            finish_stroke(&mut stroke, &mut layer);
+                        }
+                        is_down = new_down;
+                    }
+                }
+                ('M', 5) => {
+                    if matches!(settings.pen, GcodePen::Servo { .. }) {
+                        finish_stroke(&mut stroke, &mut layer);
+                        is_down = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let (GcodePen::ZAxis { up_mm, down_mm }, Some(z)) = (&settings.pen, word('Z')) {
+            let new_down = z * mm_per_unit < (up_mm + down_mm) * 0.5;
+            if is_down && !new_down {
+                finish_stroke(&mut stroke, &mut layer);
+            }
+            is_down = new_down;
+        }
+
+        let (x, y) = (word('X'), word('Y'));
+        if x.is_none() && y.is_none() {
+            continue;
+        }
+        if !has_motion && words.iter().any(|(letter, _)| *letter == 'G') {
+            // coordinates of non motion commands like G92
+            continue;
+        }
+
+        let to_cm = |value: f32| value * mm_per_unit / 10.0;
+        let from = pos;
+        let target = if is_relative {
+            from + V2::new(to_cm(x.unwrap_or(0.0)), to_cm(y.unwrap_or(0.0)))
+        } else {
+            V2::new(
+                x.map(to_cm).unwrap_or(from.x),
+                y.map(to_cm).unwrap_or(from.y),
+            )
+        };
+
+        let points = match motion {
+            2 | 3 => {
+                let (Some(i), Some(j)) = (word('I'), word('J')) else {
+                    bail!("line {}: arcs need I and J offsets", line_index + 1);
+                };
+                let center = from + V2::new(to_cm(i), to_cm(j));
+                arc_points(from, target, center, motion == 2)
+            }
+            _ => vec![target],
+        };
+        if is_down {
+            if stroke.is_empty() {
+                stroke.push(from);
+            }
+            stroke.extend(points);
+        }
+        pos = target;
+    }
+    finish_stroke(&mut stroke, &mut layer);
+
+    Ok(layer)
+}
+
+fn strip_gcode_comments(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_parentheses = false;
+    for c in line.chars() {
+        match c {
+            ';' if !in_parentheses => break,
+            '(' => in_parentheses = true,
+            ')' => in_parentheses = false,
+            _ if !in_parentheses => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped.trim().to_string()
+}
+
+fn parse_gcode_words(line: &str) -> Result<Vec<(char, f32)>> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(letter) = chars.next() {
+        if !letter.is_ascii_alphabetic() {
+            bail!("expected a letter, found '{}'", letter);
+        }
+        let mut number = String::new();
+        while let Some(c) = chars.peek().copied() {
+            if c.is_ascii_alphabetic() {
+                break;
+            }
+            number.push(c);
+            chars.next();
+        }
+        let value = number
+            .parse::<f32>()
+            .map_err(|_| anyhow!("invalid number '{}' after '{}'", number, letter))?;
+        words.push((letter.to_ascii_uppercase(), value));
+    }
+    Ok(words)
+}
+
+/// Points of a circular arc from `from` (excluded) to `to` (included) around `center`, in segments of at most 5°.
+fn arc_points(from: V2, to: V2, center: V2, clockwise: bool) -> Vec<V2> {
+    let radius = from.dist(center);
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let end = (to.y - center.y).atan2(to.x - center.x);
+    let mut sweep = end - start;
+    if clockwise && sweep >= 0.0 {
+        sweep -= std::f32::consts::TAU;
+    } else if !clockwise && sweep <= 0.0 {
+        sweep += std::f32::consts::TAU;
+    }
+
+    let segments = (sweep.abs() / 5.0_f32.to_radians()).ceil().max(1.0) as usize;
+    let mut points: Vec<V2> = (1..segments)
+        .map(|i| {
+            let angle = start + sweep * i as f32 / segments as f32;
+            center + V2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect();
+    points.push(to);
+    points
+}
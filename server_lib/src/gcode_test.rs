@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod test_gcode {
+    use plottery_lib::{Circle, ColorRgb, Layer, Path, Plottable, SampleSettings, V2};
+
+    use crate::{
+        layer_from_gcode, layer_to_gcode, GcodeFlavor, GcodePen, GcodeSettings, PlotSettings,
+    };
+
+    fn layer() -> Layer {
+        Layer::new_from_shapes_and_layers(
+            vec![Path::new_shape_from(vec![
+                V2::new(1.0, 1.0),
+                V2::new(6.0, 1.0),
+                V2::new(6.0, 4.0),
+            ])],
+            vec![
+                Layer::new_from(vec![Circle::new_shape(V2::new(3.0, 3.0), 1.0)])
+                    .with_name("circles")
+                    .with_pen_id(1)
+                    .with_color(ColorRgb::red())
+                    .with_speed_factor(0.5),
+            ],
+        )
+    }
+
+    fn round_trip(settings: &GcodeSettings) -> (String, Layer) {
+        let gcode = layer_to_gcode(
+            &layer(),
+            settings,
+            &PlotSettings::default(),
+            SampleSettings::default(),
+        );
+        let imported = layer_from_gcode(&gcode, settings).unwrap();
+        (gcode, imported)
+    }
+
+    fn assert_same_drawing(imported: &Layer) {
+        let original = layer().flatten();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(
+            imported.shapes[0].get_points(SampleSettings::default()),
+            original.shapes[0].get_points(SampleSettings::default())
+        );
+        assert!((imported.shapes[1].length() - original.shapes[1].length()).abs() < 0.05);
+    }
+
+    #[test]
+    fn servo() {
+        let (gcode, imported) = round_trip(&GcodeSettings::new());
+        assert!(gcode.starts_with("G21\nG90\nM3 S0\nG4 P0.15\n"));
+        assert!(
+            gcode.contains("G0 X10 Y10 F4200\nM3 S1000\nG4 P0.15\nG1 X60 Y10 F2700\nG1 X60 Y40\n")
+        );
+        // the speed factor of the sublayer halves the drawing speed
+        assert!(gcode.contains(" F1350"));
+        // the first layer also asks for its pen
+        assert_eq!(gcode.matches("M0 (insert pen").count(), 2);
+        assert!(gcode.find("M0 (insert pen 0: ").unwrap() < gcode.find("G0 X10 Y10").unwrap());
+        assert!(gcode.contains("M0 (insert pen 1: circles #ff0000)"));
+        assert!(gcode.ends_with("G0 X0 Y0\nM5\n"));
+        assert_same_drawing(&imported);
+    }
+
+    #[test]
+    fn z_axis_and_marlin() {
+        let settings = GcodeSettings::new()
+            .with_pen(GcodePen::ZAxis {
+                up_mm: 5.0,
+                down_mm: 0.0,
+            })
+            .with_flavor(GcodeFlavor::Marlin)
+            .with_tool_change(None);
+        let (gcode, imported) = round_trip(&settings);
+        assert!(gcode.contains("G0 Z5\n"));
+        assert!(gcode.contains("G1 Z0 F2400\n"));
+        assert!(!gcode.contains("M0"));
+        assert_same_drawing(&imported);
+
+        let depth = layer_to_gcode(
+            &layer().with_pen_down_depth_cm(0.1),
+            &settings,
+            &PlotSettings::default(),
+            SampleSettings::default(),
+        );
+        assert!(depth.contains("G1 Z-1 F2400\n"));
+    }
+
+    #[test]
+    fn custom_snippets_and_templates() {
+        let settings = GcodeSettings::new()
+            .with_pen(GcodePen::Custom {
+                up: "M280 P0 S90\nG4 P200".to_string(),
+                down: "M280 P0 S30\nG4 P200".to_string(),
+            })
+            .with_header("; start\n{pen_up}")
+            .with_footer("{pen_up}\nM84");
+        let (gcode, imported) = round_trip(&settings);
+        assert!(gcode.starts_with("; start\nM280 P0 S90\nG4 P200\n"));
+        assert!(gcode.ends_with("M84\n"));
+        assert_same_drawing(&imported);
+    }
+
+    #[test]
+    fn import_units_relative_and_arcs() {
+        let settings = GcodeSettings::new().with_pen(GcodePen::ZAxis {
+            up_mm: 1.0,
+            down_mm: 0.0,
+        });
+        let gcode = "G20 ; inches\n\
+                     G0 X1 Y0 (start)\n\
+                     G1 Z0\n\
+                     G91\n\
+                     G1 X1\n\
+                     G90 G21\n\
+                     G2 X76.2 Y0 I12.7 J0\n\
+                     G0 Z1\n\
+                     G0 X0 Y0\n";
+        let imported = layer_from_gcode(gcode, &settings).unwrap();
+        assert_eq!(imported.len(), 1);
+
+        let points = imported.shapes[0].get_points(SampleSettings::default());
+        assert!(points[0].dist(V2::new(2.54, 0.0)) < 0.001);
+        assert!(points[1].dist(V2::new(5.08, 0.0)) < 0.001);
+        // clockwise half circle around (6.35, 0) passes through y = 1.27
+        let top = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((top - 1.27).abs() < 0.005);
+        assert!(points.last().unwrap().dist(V2::new(5.08 * 1.5, 0.0)) < 0.001);
+
+        assert!(layer_from_gcode("G1 X1 Yfoo", &settings).is_err());
+        assert!(layer_from_gcode("G1 Z0\nG2 X1 Y1", &settings).is_err());
+    }
+}
@@ -2,15 +2,19 @@ use serde::{Deserialize, Serialize};
 
 pub mod accelleration;
 pub mod estimation;
+pub mod gcode;
+mod gcode_test;
 pub mod hardware;
 pub mod midi;
 pub mod pins;
 pub mod plot_execution;
+mod plot_execution_test;
 pub mod plot_setting;
 pub mod server_state;
 pub mod task;
 
 pub use estimation::*;
+pub use gcode::*;
 pub use midi::*;
 pub use pins::*;
 pub use plot_setting::*;
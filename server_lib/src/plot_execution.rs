@@ -15,11 +15,34 @@ pub fn plot_layer<E: HardwareExecutor>(
     sample_settings: SampleSettings,
     plot_settings: &PlotSettings,
 ) {
-    for shape in layer.iter_flattened() {
-        plot_shape(hardware, shape, sample_settings, plot_settings);
+    for (sublayer, props) in layer.layers_in_plot_order() {
+        if sublayer.shapes.is_empty() {
+            continue;
+        }
+        let layer_plot_settings = plot_settings.for_layer(&props);
+        for shape in sublayer.iter() {
+            for _ in 0..props.plot_passes() {
+                plot_shape(hardware, shape, sample_settings, &layer_plot_settings);
+            }
+        }
+        // lift by the same distance the pen was lowered
+        lift_head(hardware, &layer_plot_settings);
     }
 }
 
+pub fn lift_head<E: HardwareExecutor>(hardware: &mut Hardware<E>, plot_settings: &PlotSettings) {
+    let speed_head_up = SpeedDelayHandler::new_from_speed_range(
+        &plot_settings.speed_head_up,
+        hardware.get_hardware_profile().dist_per_step_head_cm,
+    );
+    hardware.set_head(
+        false,
+        plot_settings.head_travel_beyond_paper_cm,
+        plot_settings.speed_head_up.accelleration_distance,
+        speed_head_up,
+    );
+}
+
 pub fn travel_to<E: HardwareExecutor>(
     hardware: &mut Hardware<E>,
     target_pos: V2,
@@ -37,17 +60,8 @@ pub fn travel_to<E: HardwareExecutor>(
         &plot_settings.speed_travel,
         hardware_profile.dist_per_step_axis_cm,
     );
-    let speed_head_up = SpeedDelayHandler::new_from_speed_range(
-        &plot_settings.speed_head_up,
-        hardware_profile.dist_per_step_head_cm,
-    );
 
-    hardware.set_head(
-        false,
-        plot_settings.head_travel_beyond_paper_cm,
-        plot_settings.speed_head_up.accelleration_distance,
-        speed_head_up,
-    );
+    lift_head(hardware, plot_settings);
 
     for (from, to) in acc_path.points.iter().tuple_windows() {
         hardware.move_to(from.speed, *to, &speed_travel);
@@ -60,6 +74,13 @@ pub fn plot_shape<E: HardwareExecutor>(
     sample_settings: SampleSettings,
     plot_settings: &PlotSettings,
 ) {
+    if let Shape::Compound(compound) = shape {
+        for ring in compound.rings() {
+            plot_shape(hardware, &ring.into(), sample_settings, plot_settings);
+        }
+        return;
+    }
+
     let points = shape.get_points_from(hardware.get_pos(), sample_settings);
     if points.len() < 2 {
         return;
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod test_plot_execution {
+    use plottery_lib::{Layer, LayerPropsInheritable, Path, SampleSettings, MIN_SPEED_FACTOR, V2};
+
+    use crate::{estimate_plot_layer_duration, PlotSettings};
+
+    fn layer() -> Layer {
+        Layer::new_from(vec![Path::new_shape_from(vec![
+            V2::new(1.0, 1.0),
+            V2::new(6.0, 1.0),
+            V2::new(6.0, 4.0),
+        ])])
+    }
+
+    #[test]
+    fn for_layer() {
+        let plot_settings = PlotSettings::default();
+        let props = LayerPropsInheritable::default()
+            .with_speed_factor(0.5)
+            .with_pen_down_depth_cm(0.1);
+        let layer_settings = plot_settings.for_layer(&props);
+
+        assert_eq!(
+            layer_settings.speed_draw.max,
+            plot_settings.speed_draw.max * 0.5
+        );
+        assert_eq!(
+            layer_settings.speed_travel.max,
+            plot_settings.speed_travel.max
+        );
+        assert!(
+            (layer_settings.head_travel_beyond_paper_cm
+                - plot_settings.head_travel_beyond_paper_cm
+                - 0.1)
+                .abs()
+                < 0.0001
+        );
+    }
+
+    #[test]
+    fn for_layer_clamps_speed_factor() {
+        let plot_settings = PlotSettings::default();
+        for speed_factor in [0.0, -1.0, f32::NAN] {
+            let props = LayerPropsInheritable::default().with_speed_factor(speed_factor);
+            let layer_settings = plot_settings.for_layer(&props);
+            assert_eq!(
+                layer_settings.speed_draw.max,
+                plot_settings.speed_draw.max * MIN_SPEED_FACTOR
+            );
+        }
+    }
+
+    #[test]
+    fn layer_props_change_duration() {
+        let sample_settings = SampleSettings::default();
+        let plot_settings = PlotSettings::default();
+        let base = estimate_plot_layer_duration(&layer(), sample_settings, &plot_settings);
+        let slow = estimate_plot_layer_duration(
+            &layer().with_speed_factor(0.5),
+            sample_settings,
+            &plot_settings,
+        );
+        let twice =
+            estimate_plot_layer_duration(&layer().with_passes(2), sample_settings, &plot_settings);
+
+        assert!(slow > base);
+        assert!(twice > base);
+        assert!(twice < base * 3);
+    }
+}
@@ -1,3 +1,4 @@
+use plottery_lib::LayerPropsInheritable;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,16 @@ pub struct SpeedRange {
     pub accelleration_distance: f32,
 }
 
+impl SpeedRange {
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            min: self.min * factor,
+            max: self.max * factor,
+            accelleration_distance: self.accelleration_distance,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlotSettings {
     pub corner_slowdown_power: f32, // the lower the slower
@@ -46,3 +57,16 @@ impl Default for PlotSettings {
         }
     }
 }
+
+impl PlotSettings {
+    /// Returns the settings adjusted to the plotting properties of a layer: the drawing speed is scaled
+    /// by the speed factor and the pen is lowered further by the pen down depth.
+    pub fn for_layer(&self, props: &LayerPropsInheritable) -> Self {
+        Self {
+            head_travel_beyond_paper_cm: self.head_travel_beyond_paper_cm
+                + props.pen_down_depth_cm.unwrap(),
+            speed_draw: self.speed_draw.scaled(props.plot_speed_factor()),
+            ..self.clone()
+        }
+    }
+}